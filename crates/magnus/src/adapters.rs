@@ -1,3 +1,4 @@
+use magnus_shared::Dex;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey};
@@ -8,6 +9,7 @@ use crate::adapters::amms::Target;
 pub mod aggregators;
 pub mod amms;
 pub mod helpers;
+pub mod token_meta;
 
 /// Defines the base traits for downstream liquidity adapters
 /// Implementations usually rely on a child interface, like [`Amm`] and [`Aggregator`]
@@ -53,6 +55,8 @@ pub struct Quote {
     pub fee_amount: u64,
     pub fee_mint: Pubkey,
     pub fee_pct: Decimal,
+    /// The slot of the state the quote was computed against, 0 if unknown.
+    pub context_slot: u64,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -68,6 +72,29 @@ pub enum AmmKind {
     RaydiumCLV2,
     ObricV2,
     Humidifi,
+    Whirlpool,
+    MeteoraDlmm,
+    Phoenix,
+    RaydiumAmmV4,
+    SplStakePool,
+    SplTokenSwap,
+}
+
+impl From<&AmmKind> for Dex {
+    fn from(kind: &AmmKind) -> Self {
+        match kind {
+            AmmKind::RaydiumCP => Dex::RaydiumCp,
+            AmmKind::RaydiumCLV2 => Dex::RaydiumClV2,
+            AmmKind::ObricV2 => Dex::ObricV2,
+            AmmKind::Humidifi => Dex::HumidiFi,
+            AmmKind::Whirlpool => Dex::Whirlpool,
+            AmmKind::MeteoraDlmm => Dex::MeteoraDlmm,
+            AmmKind::Phoenix => Dex::Phoenix,
+            AmmKind::RaydiumAmmV4 => Dex::RaydiumAmmV4,
+            AmmKind::SplStakePool => Dex::SplStakePool,
+            AmmKind::SplTokenSwap => Dex::SplTokenSwap,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Serialize, ToSchema)]
@@ -79,6 +106,31 @@ pub struct IntQuoteResponse {
     pub in_amount: u64,
     pub out_amount: u64,
     pub route_plan: Option<Vec<PlanItem>>,
+    // the amounts valued at the oracle prices, left out when unknown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_usd_value: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub out_usd_value: Option<f64>,
+}
+
+/// What a single source came up with, racing the others for [`Target::All`].
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceQuote {
+    pub source: Target,
+    pub quote: Option<IntQuoteResponse>,
+    // why there's no quote, i.e. the source's deadline elapsed
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// The winning quote, along with every source's result.
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BestQuoteResponse {
+    #[serde(flatten)]
+    pub best: IntQuoteResponse,
+    pub sources: Vec<SourceQuote>,
 }
 
 // todo: implement the ToSchema
@@ -89,8 +141,27 @@ pub struct IntSwapResponse {
     pub input_mint: String,
     pub output_mint: String,
     pub in_amount: u64,
+    pub out_amount: u64,
     pub signature: String, //Signature,
     pub route_plan: Option<Vec<PlanItem>>,
+    // why the swap never went out, i.e. no leg could be routed or the tx was rejected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The swap of the quote, yet to land.
+impl From<IntQuoteResponse> for IntSwapResponse {
+    fn from(quote: IntQuoteResponse) -> Self {
+        IntSwapResponse {
+            source: quote.source,
+            input_mint: quote.input_mint,
+            output_mint: quote.output_mint,
+            in_amount: quote.in_amount,
+            out_amount: quote.out_amount,
+            route_plan: quote.route_plan,
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Serialize, ToSchema)]
@@ -1,10 +1,370 @@
 pub mod dflow;
 pub mod jupiter;
+pub mod okx;
+pub mod raydium;
 
-use crate::adapters::Adapter;
+use std::{fs, time::Duration};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::VersionedTransaction};
+
+use crate::adapters::{
+    Adapter, IntSwapResponse,
+    aggregators::{
+        dflow::DFlow,
+        jupiter::Jupiter,
+        okx::{Okx, OkxCfg},
+        raydium::Raydium,
+    },
+    amms::Target,
+};
 
 #[async_trait::async_trait]
 pub trait Aggregator: Adapter + Send + Sync {
     async fn quote(&self, _params: &crate::adapters::QuoteParams) -> eyre::Result<crate::adapters::IntQuoteResponse>;
-    async fn swap(&self, _params: &crate::adapters::SwapParams) -> eyre::Result<crate::adapters::SwapAndAccountMetas>;
+    /// Requests the swap transaction for the signer and signs it, leaving the submission to the executor.
+    async fn swap(&self, _params: &crate::adapters::SwapParams, _signer: &Keypair) -> eyre::Result<SignedSwap>;
+}
+
+/// The config of every aggregator, the ones left out go by the defaults.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AggregatorsCfg {
+    pub jupiter: AggregatorCfg,
+    pub dflow: AggregatorCfg,
+    pub okx: OkxCfg,
+    pub raydium: AggregatorCfg,
+}
+
+impl AggregatorsCfg {
+    pub fn load(file: &str) -> eyre::Result<Self> {
+        let json = fs::read_to_string(file)?;
+
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct AggregatorCfg {
+    /// The aggregator's public endpoint if unset.
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    /// The header the api key is sent under.
+    pub api_key_header: String,
+    pub timeout_ms: u64,
+    pub retry: RetryPolicy,
+    // passed through with every quote/swap, left to the aggregator if unset
+    pub slippage_bps: Option<u16>,
+    pub only_direct_routes: Option<bool>,
+    pub exclude_dexes: Vec<String>,
+}
+
+impl Default for AggregatorCfg {
+    fn default() -> Self {
+        AggregatorCfg {
+            base_url: None,
+            api_key: None,
+            api_key_header: "x-api-key".to_string(),
+            timeout_ms: 2_000,
+            retry: RetryPolicy::default(),
+            slippage_bps: None,
+            only_direct_routes: None,
+            exclude_dexes: vec![],
+        }
+    }
+}
+
+/// How the requests failing for a transient reason (i.e. timeouts, 429s & 5xxs) are retried.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    // the backoff grows linearly with each retry
+    pub backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy { max_retries: 2, backoff_ms: 100 }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AggregatorError {
+    #[error("{url} responded with {status}: {body}")]
+    Status { url: String, status: StatusCode, body: String },
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+}
+
+impl AggregatorError {
+    pub fn is_transient(&self) -> bool {
+        match self {
+            AggregatorError::Status { status, .. } => *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+            AggregatorError::Request(e) => e.is_timeout() || e.is_connect(),
+        }
+    }
+}
+
+/// The http client an aggregator talks to its API through.
+#[derive(Clone, Debug)]
+pub struct AggregatorClient {
+    base_url: String,
+    cfg: AggregatorCfg,
+    // shared between the aggregators, along with its connection pool
+    client: reqwest::Client,
+}
+
+impl AggregatorClient {
+    pub fn new(cfg: AggregatorCfg, default_url: &str, client: reqwest::Client) -> Self {
+        let base_url = cfg.base_url.clone().unwrap_or_else(|| default_url.to_string()).trim_end_matches('/').to_string();
+
+        AggregatorClient { base_url, cfg, client }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn cfg(&self) -> &AggregatorCfg {
+        &self.cfg
+    }
+
+    pub fn http(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// The pair and amount to quote, along with the configured routing options.
+    pub fn quote_query(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount: u64) -> Vec<(&'static str, String)> {
+        let mut params = vec![("inputMint", input_mint.to_string()), ("outputMint", output_mint.to_string()), ("amount", amount.to_string())];
+        if let Some(slippage_bps) = self.cfg.slippage_bps {
+            params.push(("slippageBps", slippage_bps.to_string()));
+        }
+        if let Some(only_direct_routes) = self.cfg.only_direct_routes {
+            params.push(("onlyDirectRoutes", only_direct_routes.to_string()));
+        }
+        if !self.cfg.exclude_dexes.is_empty() {
+            params.push(("excludeDexes", self.cfg.exclude_dexes.join(",")));
+        }
+
+        params
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T, AggregatorError> {
+        let url = format!("{}{}", self.base_url, path);
+        self.send(|| self.client.get(&url).query(query)).await
+    }
+
+    pub async fn post<T: DeserializeOwned, B: Serialize + ?Sized>(&self, path: &str, body: &B) -> Result<T, AggregatorError> {
+        let url = format!("{}{}", self.base_url, path);
+        self.send(|| self.client.post(&url).json(body)).await
+    }
+
+    /// Sends the request, rebuilt on each retry, with the api key attached.
+    pub async fn send<T: DeserializeOwned>(&self, request: impl Fn() -> RequestBuilder) -> Result<T, AggregatorError> {
+        let mut retries = 0;
+        loop {
+            let mut request = request().timeout(Duration::from_millis(self.cfg.timeout_ms));
+            if let Some(api_key) = &self.cfg.api_key {
+                request = request.header(&self.cfg.api_key_header, api_key);
+            }
+
+            let error = match request.send().await {
+                Ok(response) if response.status().is_success() => return Ok(response.json().await?),
+                Ok(response) => {
+                    let (url, status) = (response.url().to_string(), response.status());
+                    AggregatorError::Status { url, status, body: response.text().await.unwrap_or_default() }
+                }
+                Err(e) => AggregatorError::Request(e),
+            };
+
+            if retries >= self.cfg.retry.max_retries || !error.is_transient() {
+                return Err(error);
+            }
+            retries += 1;
+            tokio::time::sleep(Duration::from_millis(self.cfg.retry.backoff_ms * retries as u64)).await;
+        }
+    }
+}
+
+/// The aggregators we quote and swap through, sharing a single http client.
+pub struct Aggregators {
+    pub jupiter: Jupiter,
+    pub dflow: DFlow,
+    pub okx: Okx,
+    pub raydium: Raydium,
+}
+
+impl Aggregators {
+    pub fn new(cfg: AggregatorsCfg) -> Self {
+        let client = reqwest::Client::new();
+
+        Aggregators {
+            jupiter: Jupiter::new(cfg.jupiter, client.clone()),
+            dflow: DFlow::new(cfg.dflow, client.clone()),
+            okx: Okx::new(cfg.okx, client.clone()),
+            raydium: Raydium::new(cfg.raydium, client),
+        }
+    }
+
+    pub fn get(&self, target: Target) -> Option<&dyn Aggregator> {
+        match target {
+            Target::Jupiter => Some(&self.jupiter),
+            Target::DFlow => Some(&self.dflow),
+            Target::Okx => Some(&self.okx),
+            Target::Raydium => Some(&self.raydium),
+            _ => None,
+        }
+    }
+
+    /// Every aggregator, along with the target it's picked by.
+    pub fn all(&self) -> Vec<(Target, &dyn Aggregator)> {
+        vec![(Target::Jupiter, &self.jupiter), (Target::DFlow, &self.dflow), (Target::Okx, &self.okx), (Target::Raydium, &self.raydium)]
+    }
+}
+
+impl Default for Aggregators {
+    fn default() -> Self {
+        Aggregators::new(AggregatorsCfg::default())
+    }
+}
+
+/// An aggregator's swap transaction, signed by the user and ready to be submitted.
+#[derive(Clone, Debug)]
+pub struct SignedSwap {
+    pub transaction: VersionedTransaction,
+    // the signature is filled in once the transaction lands
+    pub response: IntSwapResponse,
+}
+
+/// Decodes the base64 encoded transaction as served by the aggregators' APIs.
+pub fn decode_transaction(encoded: &str) -> eyre::Result<VersionedTransaction> {
+    deserialize_transaction(&STANDARD.decode(encoded)?)
+}
+
+/// Deserializes the transaction off its wire format.
+pub fn deserialize_transaction(bytes: &[u8]) -> eyre::Result<VersionedTransaction> {
+    let (transaction, _) = bincode::serde::decode_from_slice(bytes, bincode::config::legacy())?;
+
+    Ok(transaction)
+}
+
+/// Signs the transaction in the signer's slot, the signatures of the other signers (if any) are kept as they are.
+pub fn sign(transaction: &mut VersionedTransaction, signer: &Keypair) -> eyre::Result<()> {
+    let signers = transaction.message.header().num_required_signatures as usize;
+    let position = transaction
+        .message
+        .static_account_keys()
+        .iter()
+        .take(signers)
+        .position(|key| *key == signer.pubkey())
+        .ok_or_else(|| eyre::eyre!("{} is not a signer of the transaction", signer.pubkey()))?;
+
+    transaction.signatures.resize(signers, Default::default());
+    transaction.signatures[position] = signer.sign_message(&transaction.message.serialize());
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    };
+
+    use actix_web::{App, HttpRequest, HttpResponse, HttpServer, web};
+    use solana_sdk::{
+        hash::Hash,
+        message::{Message, VersionedMessage},
+        pubkey::Pubkey,
+    };
+
+    use super::*;
+
+    /// Points at the local stub standing in for the aggregator's API.
+    pub(crate) fn stub_cfg(url: String) -> AggregatorCfg {
+        AggregatorCfg { base_url: Some(url), retry: RetryPolicy { max_retries: 2, backoff_ms: 1 }, ..AggregatorCfg::default() }
+    }
+
+    /// An unsigned, instruction-less transaction, paid for by the payer, as an aggregator would serve it.
+    pub(crate) fn unsigned_transaction(payer: &Pubkey) -> String {
+        let message = Message::new_with_blockhash(&[], Some(payer), &Hash::new_unique());
+        let transaction = VersionedTransaction { signatures: vec![Default::default()], message: VersionedMessage::Legacy(message) };
+
+        STANDARD.encode(bincode::serde::encode_to_vec(&transaction, bincode::config::legacy()).unwrap())
+    }
+
+    #[test]
+    fn signs_in_the_signers_slot() {
+        let signer = Keypair::new();
+        let mut transaction = decode_transaction(&unsigned_transaction(&signer.pubkey())).unwrap();
+
+        sign(&mut transaction, &signer).unwrap();
+        assert_eq!(transaction.verify_with_results(), vec![true]);
+
+        // not the payer, not a signer
+        assert!(sign(&mut transaction, &Keypair::new()).is_err());
+        assert!(decode_transaction("not base64").is_err());
+    }
+
+    #[tokio::test]
+    async fn passes_the_routing_options_and_the_api_key() {
+        async fn echo(request: HttpRequest) -> HttpResponse {
+            let api_key = request.headers().get("x-api-key").map(|key| key.to_str().unwrap().to_string());
+            HttpResponse::Ok().json(serde_json::json!({ "query": request.query_string(), "apiKey": api_key }))
+        }
+
+        let server = HttpServer::new(|| App::new().route("/echo", web::get().to(echo))).workers(1).bind(("127.0.0.1", 0)).unwrap();
+        let cfg = AggregatorCfg {
+            api_key: Some("secret".to_string()),
+            slippage_bps: Some(50),
+            only_direct_routes: Some(true),
+            exclude_dexes: vec!["Whirlpool".to_string(), "Phoenix".to_string()],
+            ..stub_cfg(format!("http://{}/", server.addrs()[0]))
+        };
+        tokio::spawn(server.run());
+
+        let client = AggregatorClient::new(cfg, "https://unused", reqwest::Client::new());
+        let (input_mint, output_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let echoed: serde_json::Value = client.get("/echo", &client.quote_query(&input_mint, &output_mint, 1)).await.unwrap();
+
+        let query = format!("inputMint={}&outputMint={}&amount=1&slippageBps=50&onlyDirectRoutes=true&excludeDexes=Whirlpool%2CPhoenix", input_mint, output_mint);
+        assert_eq!(echoed["query"], query);
+        assert_eq!(echoed["apiKey"], "secret");
+    }
+
+    #[tokio::test]
+    async fn retries_the_transient_failures_only() {
+        async fn flaky(request: HttpRequest, hits: web::Data<AtomicU32>) -> HttpResponse {
+            hits.fetch_add(1, Ordering::SeqCst);
+            match request.path() {
+                "/unavailable" => HttpResponse::ServiceUnavailable().body("try later"),
+                _ => HttpResponse::BadRequest().body("bad mint"),
+            }
+        }
+
+        let hits = web::Data::from(Arc::new(AtomicU32::new(0)));
+        let server = {
+            let hits = hits.clone();
+            HttpServer::new(move || App::new().app_data(hits.clone()).default_service(web::to(flaky))).workers(1).bind(("127.0.0.1", 0)).unwrap()
+        };
+        let client = AggregatorClient::new(stub_cfg(format!("http://{}", server.addrs()[0])), "https://unused", reqwest::Client::new());
+        tokio::spawn(server.run());
+
+        match client.get::<serde_json::Value>("/unavailable", &[]).await {
+            Err(AggregatorError::Status { status, body, .. }) => assert_eq!((status, body.as_str()), (StatusCode::SERVICE_UNAVAILABLE, "try later")),
+            res => panic!("unexpected result {:?}", res),
+        }
+        assert_eq!(hits.swap(0, Ordering::SeqCst), 3);
+
+        match client.get::<serde_json::Value>("/invalid", &[]).await {
+            Err(AggregatorError::Status { status, .. }) => assert_eq!(status, StatusCode::BAD_REQUEST),
+            res => panic!("unexpected result {:?}", res),
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
 }
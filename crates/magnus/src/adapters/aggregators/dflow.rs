@@ -1,29 +1,55 @@
 use serde::{Deserialize, Serialize};
+use solana_sdk::{signature::Keypair, signer::Signer};
 
 use crate::{
-    adapters::{Adapter, IntQuoteResponse, PlanItem, QuoteParams, aggregators::Aggregator, amms::Target},
+    adapters::{
+        Adapter, IntQuoteResponse, PlanItem, QuoteParams, SwapParams,
+        aggregators::{Aggregator, AggregatorCfg, AggregatorClient, SignedSwap, decode_transaction, sign},
+        amms::Target,
+    },
     helpers::parse_amount,
 };
 
 const API_URL: &str = "https://quote-api.dflow.net";
 
-pub struct DFlow;
+pub struct DFlow {
+    client: AggregatorClient,
+}
+
+impl DFlow {
+    pub fn new(cfg: AggregatorCfg, client: reqwest::Client) -> Self {
+        DFlow { client: AggregatorClient::new(cfg, API_URL, client) }
+    }
+}
+
+impl Default for DFlow {
+    fn default() -> Self {
+        DFlow::new(AggregatorCfg::default(), reqwest::Client::new())
+    }
+}
 
 impl Adapter for DFlow {}
 
 #[async_trait::async_trait]
 impl Aggregator for DFlow {
     async fn quote(&self, params: &QuoteParams) -> eyre::Result<crate::adapters::IntQuoteResponse> {
-        let url = format!("{}/quote?inputMint={}&outputMint={}&amount={}", API_URL, params.input_mint, params.output_mint, params.amount);
-
-        let resp: DFlowQuoteResponse = reqwest::get(&url).await?.json().await?;
+        let resp: DFlowQuoteResponse = self.client.get("/quote", &self.client.quote_query(&params.input_mint, &params.output_mint, params.amount)).await?;
         let quote = IntQuoteResponse::from(resp);
 
         Ok(quote)
     }
 
-    async fn swap(&self, _params: &crate::adapters::SwapParams) -> eyre::Result<crate::adapters::SwapAndAccountMetas> {
-        unimplemented!("not yet implemented")
+    async fn swap(&self, params: &SwapParams, signer: &Keypair) -> eyre::Result<SignedSwap> {
+        // handed back as is, the swap expects the quote it was served
+        let quote: serde_json::Value = self.client.get("/quote", &self.client.quote_query(&params.input_mint, &params.output_mint, params.amount)).await?;
+        let parsed: DFlowQuoteResponse = serde_json::from_value(quote.clone())?;
+
+        let request = DFlowSwapRequest { user_public_key: signer.pubkey().to_string(), quote_response: quote };
+        let resp: DFlowSwapResponse = self.client.post("/swap", &request).await?;
+        let mut transaction = decode_transaction(&resp.swap_transaction)?;
+        sign(&mut transaction, signer)?;
+
+        Ok(SignedSwap { transaction, response: IntQuoteResponse::from(parsed).into() })
     }
 }
 
@@ -48,6 +74,19 @@ pub struct DFlowQuoteResponse {
     pub route_plan: Vec<DFlowRoutePlanItem>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DFlowSwapRequest {
+    pub user_public_key: String,
+    pub quote_response: serde_json::Value,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DFlowSwapResponse {
+    pub swap_transaction: String,
+}
+
 impl From<DFlowQuoteResponse> for IntQuoteResponse {
     fn from(dflow: DFlowQuoteResponse) -> Self {
         let route_plan = Some(
@@ -72,6 +111,55 @@ impl From<DFlowQuoteResponse> for IntQuoteResponse {
             in_amount: parse_amount(&dflow.in_amount).unwrap_or(0),
             out_amount: parse_amount(&dflow.out_amount).unwrap_or(0),
             route_plan,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use actix_web::{App, HttpResponse, HttpServer, web};
+    use solana_sdk::pubkey::Pubkey;
+
+    use super::*;
+    use crate::adapters::aggregators::tests::{stub_cfg, unsigned_transaction};
+
+    async fn quote(query: web::Query<HashMap<String, String>>) -> HttpResponse {
+        HttpResponse::Ok().json(serde_json::json!({
+            "inputMint": query["inputMint"],
+            "inAmount": query["amount"],
+            "outputMint": query["outputMint"],
+            "outAmount": "990",
+            "routePlan": [{ "venue": "Phoenix", "marketKey": "market", "inputMint": query["inputMint"], "outputMint": query["outputMint"], "inAmount": query["amount"], "outAmount": "990" }],
+            "contextSlot": 42,
+        }))
+    }
+
+    async fn swap(request: web::Json<DFlowSwapRequest>) -> HttpResponse {
+        // the quote makes it back untouched
+        if request.quote_response["contextSlot"] != 42 {
+            return HttpResponse::BadRequest().finish();
         }
+
+        HttpResponse::Ok().json(DFlowSwapResponse { swap_transaction: unsigned_transaction(&request.user_public_key.parse().unwrap()) })
+    }
+
+    #[tokio::test]
+    async fn swaps_through_the_quoted_transaction() {
+        let server = HttpServer::new(|| App::new().route("/quote", web::get().to(quote)).route("/swap", web::post().to(swap))).workers(1).bind(("127.0.0.1", 0)).unwrap();
+        let dflow = DFlow::new(stub_cfg(format!("http://{}", server.addrs()[0])), reqwest::Client::new());
+        tokio::spawn(server.run());
+
+        let signer = Keypair::new();
+        let params = SwapParams { input_mint: Pubkey::new_unique(), output_mint: Pubkey::new_unique(), amount: 1_000, ..SwapParams::default() };
+        let swap = dflow.swap(&params, &signer).await.unwrap();
+
+        assert_eq!(swap.transaction.verify_with_results(), vec![true]);
+        assert!(matches!(swap.response.source, Target::DFlow));
+        assert_eq!(swap.response.input_mint, params.input_mint.to_string());
+        let route_plan = swap.response.route_plan.unwrap();
+        assert_eq!((route_plan[0].venue.as_str(), route_plan[0].out_amount), ("Phoenix", 990));
     }
 }
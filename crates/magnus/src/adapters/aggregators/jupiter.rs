@@ -1,29 +1,57 @@
 use serde::{Deserialize, Serialize};
+use solana_sdk::{signature::Keypair, signer::Signer};
 
 use crate::{
-    adapters::{Adapter, IntQuoteResponse, PlanItem, aggregators::Aggregator, amms::Target},
+    adapters::{
+        Adapter, IntQuoteResponse, PlanItem, SwapParams,
+        aggregators::{Aggregator, AggregatorCfg, AggregatorClient, SignedSwap, decode_transaction, sign},
+        amms::Target,
+    },
     helpers::parse_amount,
 };
 
 const API_URL: &str = "https://lite-api.jup.ag";
 
-pub struct Jupiter;
+pub struct Jupiter {
+    client: AggregatorClient,
+}
+
+impl Jupiter {
+    pub fn new(cfg: AggregatorCfg, client: reqwest::Client) -> Self {
+        Jupiter { client: AggregatorClient::new(cfg, API_URL, client) }
+    }
+}
+
+impl Default for Jupiter {
+    fn default() -> Self {
+        Jupiter::new(AggregatorCfg::default(), reqwest::Client::new())
+    }
+}
 
 impl Adapter for Jupiter {}
 
 #[async_trait::async_trait]
 impl Aggregator for Jupiter {
     async fn quote(&self, params: &crate::adapters::QuoteParams) -> eyre::Result<crate::adapters::IntQuoteResponse> {
-        let url = format!("{}/ultra/v1/order?inputMint={}&outputMint={}&amount={}", API_URL, params.input_mint, params.output_mint, params.amount);
-
-        let resp: JupQuoteResp = reqwest::get(&url).await?.json().await?;
+        let resp: JupQuoteResp = self.client.get("/swap/v1/quote", &self.client.quote_query(&params.input_mint, &params.output_mint, params.amount)).await?;
         let quote = crate::adapters::IntQuoteResponse::from(resp);
 
         Ok(quote)
     }
 
-    async fn swap(&self, _params: &crate::adapters::SwapParams) -> eyre::Result<crate::adapters::SwapAndAccountMetas> {
-        unimplemented!("not yet implemented")
+    /// Goes through the swap API rather than Ultra, whose orders only land through its own `/execute`
+    /// while the executor submits the transaction to our RPC.
+    async fn swap(&self, params: &SwapParams, signer: &Keypair) -> eyre::Result<SignedSwap> {
+        // handed back as is, the swap expects the quote it was served
+        let quote: serde_json::Value = self.client.get("/swap/v1/quote", &self.client.quote_query(&params.input_mint, &params.output_mint, params.amount)).await?;
+        let parsed: JupQuoteResp = serde_json::from_value(quote.clone())?;
+
+        let request = JupSwapRequest { user_public_key: signer.pubkey().to_string(), quote_response: quote };
+        let resp: JupSwapResp = self.client.post("/swap/v1/swap", &request).await?;
+        let mut transaction = decode_transaction(&resp.swap_transaction)?;
+        sign(&mut transaction, signer)?;
+
+        Ok(SignedSwap { transaction, response: IntQuoteResponse::from(parsed).into() })
     }
 }
 
@@ -44,7 +72,8 @@ pub struct JupRoutePlanItem {
     pub swap_info: JupSwapInfo,
     pub percent: Option<u8>,
     pub bps: Option<u16>,
-    pub usd_value: f64,
+    // only served by Ultra
+    pub usd_value: Option<f64>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -58,6 +87,19 @@ pub struct JupQuoteResp {
     pub route_plan: Vec<JupRoutePlanItem>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JupSwapRequest {
+    pub user_public_key: String,
+    pub quote_response: serde_json::Value,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JupSwapResp {
+    pub swap_transaction: String,
+}
+
 impl From<JupQuoteResp> for IntQuoteResponse {
     fn from(jup: JupQuoteResp) -> Self {
         let route_plan = jup
@@ -80,6 +122,59 @@ impl From<JupQuoteResp> for IntQuoteResponse {
             in_amount: parse_amount(&jup.in_amount).unwrap_or(0),
             out_amount: parse_amount(&jup.out_amount).unwrap_or(0),
             route_plan: Some(route_plan),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use actix_web::{App, HttpResponse, HttpServer, web};
+    use solana_sdk::pubkey::Pubkey;
+
+    use super::*;
+    use crate::adapters::aggregators::tests::{stub_cfg, unsigned_transaction};
+
+    async fn quote(query: web::Query<HashMap<String, String>>) -> HttpResponse {
+        HttpResponse::Ok().json(serde_json::json!({
+            "inputMint": query["inputMint"],
+            "outputMint": query["outputMint"],
+            "inAmount": query["amount"],
+            "outAmount": "990",
+            "routePlan": [{
+                "swapInfo": { "ammKey": "amm", "label": "Whirlpool", "inputMint": query["inputMint"], "outputMint": query["outputMint"], "inAmount": query["amount"], "outAmount": "990" },
+                "percent": 100,
+            }],
+        }))
+    }
+
+    async fn swap(request: web::Json<JupSwapRequest>) -> HttpResponse {
+        // the quote makes it back untouched
+        if request.quote_response["outAmount"] != "990" {
+            return HttpResponse::BadRequest().finish();
         }
+
+        HttpResponse::Ok().json(JupSwapResp { swap_transaction: unsigned_transaction(&request.user_public_key.parse().unwrap()) })
+    }
+
+    #[tokio::test]
+    async fn swaps_through_the_swap_api_transaction() {
+        let server =
+            HttpServer::new(|| App::new().route("/swap/v1/quote", web::get().to(quote)).route("/swap/v1/swap", web::post().to(swap))).workers(1).bind(("127.0.0.1", 0)).unwrap();
+        let jupiter = Jupiter::new(stub_cfg(format!("http://{}", server.addrs()[0])), reqwest::Client::new());
+        tokio::spawn(server.run());
+
+        let signer = Keypair::new();
+        let params = SwapParams { input_mint: Pubkey::new_unique(), output_mint: Pubkey::new_unique(), amount: 1_000, ..SwapParams::default() };
+        let swap = jupiter.swap(&params, &signer).await.unwrap();
+
+        assert_eq!(swap.transaction.message.static_account_keys()[0], signer.pubkey());
+        assert_eq!(swap.transaction.verify_with_results(), vec![true]);
+        assert!(matches!(swap.response.source, Target::Jupiter));
+        assert_eq!(swap.response.in_amount, 1_000);
+        let route_plan = swap.response.route_plan.unwrap();
+        assert_eq!((route_plan[0].venue.as_str(), route_plan[0].out_amount), ("Whirlpool", 990));
     }
 }
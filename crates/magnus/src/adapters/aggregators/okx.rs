@@ -0,0 +1,322 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use hmac::{Hmac, Mac};
+use reqwest::{RequestBuilder, Url};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sha2::Sha256;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+use crate::{
+    adapters::{
+        Adapter, IntQuoteResponse, PlanItem, QuoteParams, SwapParams,
+        aggregators::{Aggregator, AggregatorCfg, AggregatorClient, SignedSwap, deserialize_transaction, sign},
+        amms::Target,
+    },
+    helpers::parse_amount,
+};
+
+const API_URL: &str = "https://web3.okx.com";
+const API_KEY_HEADER: &str = "OK-ACCESS-KEY";
+// okx's index of the solana chain
+const CHAIN_INDEX: &str = "501";
+// the swap needs a slippage, unlike the quote
+const DEFAULT_SLIPPAGE_BPS: u16 = 50;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct OkxCfg {
+    #[serde(flatten)]
+    pub aggregator: AggregatorCfg,
+    // the requests are signed with the api key's secret, left unsigned without it
+    pub secret_key: Option<String>,
+    pub passphrase: Option<String>,
+    pub project_id: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+struct Credentials {
+    secret_key: String,
+    passphrase: String,
+    project_id: Option<String>,
+}
+
+/// OKX DEX's aggregator.
+///
+/// Takes no list of dexes to exclude, only one of the dexes to route through, so `excludeDexes` isn't passed on.
+pub struct Okx {
+    client: AggregatorClient,
+    credentials: Option<Credentials>,
+}
+
+impl Okx {
+    pub fn new(cfg: OkxCfg, client: reqwest::Client) -> Self {
+        let credentials = match (cfg.secret_key, cfg.passphrase) {
+            (Some(secret_key), Some(passphrase)) => Some(Credentials { secret_key, passphrase, project_id: cfg.project_id }),
+            _ => None,
+        };
+        let cfg = AggregatorCfg { api_key_header: API_KEY_HEADER.to_string(), ..cfg.aggregator };
+
+        Okx { client: AggregatorClient::new(cfg, API_URL, client), credentials }
+    }
+
+    fn query(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount: u64) -> Vec<(&'static str, String)> {
+        let mut query = vec![
+            ("chainIndex", CHAIN_INDEX.to_string()),
+            ("amount", amount.to_string()),
+            ("fromTokenAddress", input_mint.to_string()),
+            ("toTokenAddress", output_mint.to_string()),
+        ];
+        if let Some(only_direct_routes) = self.client.cfg().only_direct_routes {
+            query.push(("directRoute", only_direct_routes.to_string()));
+        }
+
+        query
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> eyre::Result<T> {
+        let url = Url::parse_with_params(&format!("{}{}", self.client.base_url(), path), query)?;
+        let resp: OkxResponse<T> = self.client.send(|| self.authenticate(self.client.http().get(url.clone()), &url)).await?;
+
+        resp.into_data()
+    }
+
+    /// Signs the request with the timestamp it's sent at, the signature goes stale past 30 seconds.
+    fn authenticate(&self, request: RequestBuilder, url: &Url) -> RequestBuilder {
+        let Some(credentials) = &self.credentials else { return request };
+        let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+
+        let request = request
+            .header("OK-ACCESS-SIGN", signature(&credentials.secret_key, &timestamp, "GET", &path))
+            .header("OK-ACCESS-TIMESTAMP", timestamp)
+            .header("OK-ACCESS-PASSPHRASE", &credentials.passphrase);
+        match &credentials.project_id {
+            Some(project_id) => request.header("OK-ACCESS-PROJECT", project_id),
+            None => request,
+        }
+    }
+}
+
+impl Adapter for Okx {}
+
+#[async_trait::async_trait]
+impl Aggregator for Okx {
+    async fn quote(&self, params: &QuoteParams) -> eyre::Result<IntQuoteResponse> {
+        let resp: OkxQuoteResponse = self.get("/api/v5/dex/aggregator/quote", &self.query(&params.input_mint, &params.output_mint, params.amount)).await?;
+
+        Ok(IntQuoteResponse::from(resp))
+    }
+
+    async fn swap(&self, params: &SwapParams, signer: &Keypair) -> eyre::Result<SignedSwap> {
+        let slippage_bps = self.client.cfg().slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS);
+        let mut query = self.query(&params.input_mint, &params.output_mint, params.amount);
+        query.push(("slippage", (slippage_bps as f64 / 10_000.0).to_string()));
+        query.push(("userWalletAddress", signer.pubkey().to_string()));
+
+        let resp: OkxSwapResponse = self.get("/api/v5/dex/aggregator/swap", &query).await?;
+        // base58 encoded, as opposed to the other aggregators
+        let mut transaction = deserialize_transaction(&bs58::decode(&resp.tx.data).into_vec()?)?;
+        sign(&mut transaction, signer)?;
+
+        Ok(SignedSwap { transaction, response: IntQuoteResponse::from(resp.router_result).into() })
+    }
+}
+
+/// The base64 encoded HMAC-SHA256 of the timestamp, method and request path (query included).
+pub fn signature(secret_key: &str, timestamp: &str, method: &str, request_path: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret_key.as_bytes()).expect("hmac takes keys of any size");
+    mac.update(format!("{}{}{}", timestamp, method, request_path).as_bytes());
+
+    STANDARD.encode(mac.finalize().into_bytes())
+}
+
+/// The envelope of every response, a non-"0" code being an error despite the 200.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkxResponse<T> {
+    pub code: String,
+    #[serde(default)]
+    pub msg: String,
+    #[serde(default = "Vec::new")]
+    pub data: Vec<T>,
+}
+
+impl<T> OkxResponse<T> {
+    pub fn into_data(self) -> eyre::Result<T> {
+        if self.code != "0" {
+            eyre::bail!("okx responded with {}: {}", self.code, self.msg);
+        }
+
+        self.data.into_iter().next().ok_or_else(|| eyre::eyre!("okx responded with no data"))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkxToken {
+    pub token_contract_address: String,
+    pub token_symbol: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkxDexProtocol {
+    pub dex_name: String,
+    pub percent: String,
+}
+
+/// A single hop, split between the dexes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkxSubRouter {
+    pub dex_protocol: Vec<OkxDexProtocol>,
+    pub from_token: OkxToken,
+    pub to_token: OkxToken,
+}
+
+/// A split of the whole amount, routed through the hops.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkxRouter {
+    pub router_percent: String,
+    pub sub_router_list: Vec<OkxSubRouter>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkxQuoteResponse {
+    pub from_token: OkxToken,
+    pub to_token: OkxToken,
+    pub from_token_amount: String,
+    pub to_token_amount: String,
+    pub dex_router_list: Vec<OkxRouter>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkxTx {
+    pub data: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkxSwapResponse {
+    pub router_result: OkxQuoteResponse,
+    pub tx: OkxTx,
+}
+
+impl From<OkxQuoteResponse> for IntQuoteResponse {
+    fn from(okx: OkxQuoteResponse) -> Self {
+        let (in_amount, out_amount) = (parse_amount(&okx.from_token_amount).unwrap_or(0), parse_amount(&okx.to_token_amount).unwrap_or(0));
+        let percent = |percent: &str| percent.parse::<f64>().unwrap_or(0.0) / 100.0;
+
+        // okx neither names the pools nor breaks the amounts down per hop, the first hop's in and the last hop's out
+        // amounts are apportioned off the totals by the split percentages
+        let route_plan = okx
+            .dex_router_list
+            .iter()
+            .flat_map(|router| {
+                let hops = router.sub_router_list.len();
+                router.sub_router_list.iter().enumerate().flat_map(move |(hop, sub_router)| {
+                    sub_router.dex_protocol.iter().map(move |dex| {
+                        let share = percent(&router.router_percent) * percent(&dex.percent);
+                        PlanItem {
+                            venue: dex.dex_name.clone(),
+                            market_key: String::new(),
+                            input_mint: sub_router.from_token.token_contract_address.clone(),
+                            output_mint: sub_router.to_token.token_contract_address.clone(),
+                            in_amount: if hop == 0 { (in_amount as f64 * share) as u64 } else { 0 },
+                            out_amount: if hop == hops - 1 { (out_amount as f64 * share) as u64 } else { 0 },
+                        }
+                    })
+                })
+            })
+            .collect();
+
+        IntQuoteResponse {
+            source: Target::Okx,
+            input_mint: okx.from_token.token_contract_address,
+            output_mint: okx.to_token.token_contract_address,
+            in_amount,
+            out_amount,
+            route_plan: Some(route_plan),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use actix_web::{App, HttpRequest, HttpResponse, HttpServer, web};
+
+    use super::*;
+    use crate::adapters::aggregators::tests::{stub_cfg, unsigned_transaction};
+
+    const QUOTE: &str = include_str!("../../../tests/fixtures/okx/quote.json");
+    const NO_LIQUIDITY: &str = include_str!("../../../tests/fixtures/okx/no-liquidity.json");
+
+    #[test]
+    fn parses_the_quote() {
+        let quote = IntQuoteResponse::from(serde_json::from_str::<OkxResponse<OkxQuoteResponse>>(QUOTE).unwrap().into_data().unwrap());
+
+        assert!(matches!(quote.source, Target::Okx));
+        assert_eq!((quote.input_mint.as_str(), quote.output_mint.as_str()), ("So11111111111111111111111111111111111111112", "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB"));
+        assert_eq!((quote.in_amount, quote.out_amount), (1_000_000_000, 149_950_000));
+
+        let route_plan = quote.route_plan.unwrap();
+        let hops: Vec<_> = route_plan.iter().map(|item| (item.venue.as_str(), item.in_amount, item.out_amount)).collect();
+        assert_eq!(hops, vec![("Raydium CL", 600_000_000, 0), ("Whirlpool", 400_000_000, 0), ("Meteora DLMM", 0, 149_950_000)]);
+        assert_eq!(route_plan[2].input_mint, "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+    }
+
+    #[test]
+    fn surfaces_the_error_code() {
+        let err = serde_json::from_str::<OkxResponse<OkxQuoteResponse>>(NO_LIQUIDITY).unwrap().into_data().unwrap_err();
+        assert_eq!(err.to_string(), "okx responded with 82000: Insufficient liquidity");
+    }
+
+    #[test]
+    fn signs_the_request_path() {
+        let signature = signature("22582BD0CFF14C41EDBF1AB98506286D", "2020-12-08T09:08:57.715Z", "GET", "/api/v5/dex/aggregator/quote?chainIndex=501&amount=1000000000");
+        assert_eq!(signature, "b7d2+E0sUQJ/r0IRBRJWH6RpkUKwHPkKAqKFo/bFw1Q=");
+    }
+
+    async fn swap(request: HttpRequest, query: web::Query<HashMap<String, String>>) -> HttpResponse {
+        if ["OK-ACCESS-KEY", "OK-ACCESS-SIGN", "OK-ACCESS-TIMESTAMP", "OK-ACCESS-PASSPHRASE"].iter().any(|header| !request.headers().contains_key(*header)) {
+            return HttpResponse::Unauthorized().finish();
+        }
+
+        let quote: serde_json::Value = serde_json::from_str(QUOTE).unwrap();
+        let transaction = STANDARD.decode(unsigned_transaction(&query["userWalletAddress"].parse().unwrap())).unwrap();
+        HttpResponse::Ok().json(serde_json::json!({
+            "code": "0",
+            "msg": "",
+            "data": [{ "routerResult": quote["data"][0], "tx": { "data": bs58::encode(transaction).into_string(), "slippage": query["slippage"] } }],
+        }))
+    }
+
+    #[tokio::test]
+    async fn swaps_through_the_signed_request() {
+        let server = HttpServer::new(|| App::new().route("/api/v5/dex/aggregator/swap", web::get().to(swap))).workers(1).bind(("127.0.0.1", 0)).unwrap();
+        let cfg = OkxCfg {
+            aggregator: AggregatorCfg { api_key: Some("key".to_string()), ..stub_cfg(format!("http://{}", server.addrs()[0])) },
+            secret_key: Some("secret".to_string()),
+            passphrase: Some("passphrase".to_string()),
+            project_id: None,
+        };
+        let okx = Okx::new(cfg, reqwest::Client::new());
+        tokio::spawn(server.run());
+
+        let signer = Keypair::new();
+        let params = SwapParams { input_mint: Pubkey::new_unique(), output_mint: Pubkey::new_unique(), amount: 1_000_000_000, ..SwapParams::default() };
+        let swap = okx.swap(&params, &signer).await.unwrap();
+
+        assert_eq!(swap.transaction.verify_with_results(), vec![true]);
+        assert!(matches!(swap.response.source, Target::Okx));
+        assert_eq!(swap.response.route_plan.unwrap().len(), 3);
+    }
+}
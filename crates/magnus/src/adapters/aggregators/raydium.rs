@@ -0,0 +1,235 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+use crate::{
+    adapters::{
+        Adapter, IntQuoteResponse, PlanItem, QuoteParams, SwapParams,
+        aggregators::{Aggregator, AggregatorCfg, AggregatorClient, SignedSwap, decode_transaction, sign},
+        amms::Target,
+    },
+    helpers::parse_amount,
+};
+
+const API_URL: &str = "https://transaction-v1.raydium.io";
+// the quote's min out is computed off a slippage, so it's always passed
+const DEFAULT_SLIPPAGE_BPS: u16 = 50;
+// the priority fee the swap transaction is built with
+const COMPUTE_UNIT_PRICE_MICRO_LAMPORTS: u64 = 100_000;
+
+/// Raydium's trade API, routing through the Raydium pools only.
+///
+/// There's no picking the routes, so neither `onlyDirectRoutes` nor `excludeDexes` is passed on.
+pub struct Raydium {
+    client: AggregatorClient,
+}
+
+impl Raydium {
+    pub fn new(cfg: AggregatorCfg, client: reqwest::Client) -> Self {
+        Raydium { client: AggregatorClient::new(cfg, API_URL, client) }
+    }
+
+    async fn compute(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount: u64) -> eyre::Result<serde_json::Value> {
+        let query = vec![
+            ("inputMint", input_mint.to_string()),
+            ("outputMint", output_mint.to_string()),
+            ("amount", amount.to_string()),
+            ("slippageBps", self.client.cfg().slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS).to_string()),
+            ("txVersion", "V0".to_string()),
+        ];
+
+        Ok(self.client.get("/compute/swap-base-in", &query).await?)
+    }
+}
+
+impl Adapter for Raydium {}
+
+#[async_trait::async_trait]
+impl Aggregator for Raydium {
+    async fn quote(&self, params: &QuoteParams) -> eyre::Result<IntQuoteResponse> {
+        let resp: RaydiumResponse<RaydiumQuoteResponse> = serde_json::from_value(self.compute(&params.input_mint, &params.output_mint, params.amount).await?)?;
+
+        Ok(IntQuoteResponse::from(resp.into_data()?))
+    }
+
+    async fn swap(&self, params: &SwapParams, signer: &Keypair) -> eyre::Result<SignedSwap> {
+        // handed back as is, the swap is built off the computed one
+        let compute = self.compute(&params.input_mint, &params.output_mint, params.amount).await?;
+        let quote = serde_json::from_value::<RaydiumResponse<RaydiumQuoteResponse>>(compute.clone())?.into_data()?;
+
+        let request = RaydiumSwapRequest {
+            compute_unit_price_micro_lamports: COMPUTE_UNIT_PRICE_MICRO_LAMPORTS.to_string(),
+            swap_response: compute,
+            tx_version: "V0".to_string(),
+            wallet: signer.pubkey().to_string(),
+            // the native SOL is (un)wrapped within the transaction
+            wrap_sol: params.input_mint == spl_token::native_mint::id(),
+            unwrap_sol: params.output_mint == spl_token::native_mint::id(),
+        };
+        let resp: RaydiumResponse<Vec<RaydiumTransaction>> = self.client.post("/transaction/swap-base-in", &request).await?;
+        let transactions = resp.into_data()?;
+        // i.e. the setup of the token accounts, split into a transaction of its own
+        let [encoded] = transactions.as_slice() else { eyre::bail!("raydium built {} transactions, a single one is supported", transactions.len()) };
+        let mut transaction = decode_transaction(&encoded.transaction)?;
+        sign(&mut transaction, signer)?;
+
+        Ok(SignedSwap { transaction, response: IntQuoteResponse::from(quote).into() })
+    }
+}
+
+/// The envelope of every response, an unsuccessful one being an error despite the 200.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaydiumResponse<T> {
+    pub id: String,
+    pub success: bool,
+    pub msg: Option<String>,
+    pub data: Option<T>,
+}
+
+impl<T> RaydiumResponse<T> {
+    pub fn into_data(self) -> eyre::Result<T> {
+        match (self.success, self.data) {
+            (true, Some(data)) => Ok(data),
+            _ => eyre::bail!("raydium responded with {}", self.msg.as_deref().unwrap_or("no data")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaydiumRoutePlanItem {
+    pub pool_id: String,
+    pub input_mint: String,
+    pub output_mint: String,
+    pub fee_mint: String,
+    pub fee_amount: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaydiumQuoteResponse {
+    pub input_mint: String,
+    pub input_amount: String,
+    pub output_mint: String,
+    pub output_amount: String,
+    pub other_amount_threshold: String,
+    pub route_plan: Vec<RaydiumRoutePlanItem>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaydiumSwapRequest {
+    pub compute_unit_price_micro_lamports: String,
+    pub swap_response: serde_json::Value,
+    pub tx_version: String,
+    pub wallet: String,
+    pub wrap_sol: bool,
+    pub unwrap_sol: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaydiumTransaction {
+    pub transaction: String,
+}
+
+impl From<RaydiumQuoteResponse> for IntQuoteResponse {
+    fn from(raydium: RaydiumQuoteResponse) -> Self {
+        let (in_amount, out_amount) = (parse_amount(&raydium.input_amount).unwrap_or(0), parse_amount(&raydium.output_amount).unwrap_or(0));
+
+        // the amounts in between the hops aren't known, only the first one's in and the last one's out
+        let hops = raydium.route_plan.len();
+        let route_plan = raydium
+            .route_plan
+            .iter()
+            .enumerate()
+            .map(|(hop, v)| PlanItem {
+                venue: "Raydium".to_string(),
+                market_key: v.pool_id.clone(),
+                input_mint: v.input_mint.clone(),
+                output_mint: v.output_mint.clone(),
+                in_amount: if hop == 0 { in_amount } else { 0 },
+                out_amount: if hop == hops - 1 { out_amount } else { 0 },
+            })
+            .collect();
+
+        IntQuoteResponse {
+            source: Target::Raydium,
+            input_mint: raydium.input_mint,
+            output_mint: raydium.output_mint,
+            in_amount,
+            out_amount,
+            route_plan: Some(route_plan),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{App, HttpResponse, HttpServer, web};
+
+    use super::*;
+    use crate::adapters::aggregators::tests::{stub_cfg, unsigned_transaction};
+
+    const QUOTE: &str = include_str!("../../../tests/fixtures/raydium/quote.json");
+    const ROUTE_NOT_FOUND: &str = include_str!("../../../tests/fixtures/raydium/route-not-found.json");
+
+    #[test]
+    fn parses_the_quote() {
+        let quote = IntQuoteResponse::from(serde_json::from_str::<RaydiumResponse<RaydiumQuoteResponse>>(QUOTE).unwrap().into_data().unwrap());
+
+        assert!(matches!(quote.source, Target::Raydium));
+        assert_eq!((quote.in_amount, quote.out_amount), (1_000_000_000, 149_900_000));
+
+        let route_plan = quote.route_plan.unwrap();
+        let hops: Vec<_> = route_plan.iter().map(|item| (item.market_key.as_str(), item.in_amount, item.out_amount)).collect();
+        assert_eq!(hops, vec![("58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2", 1_000_000_000, 0), ("BZtgQEyS6eXUXicYPHecYQ7PybqodXQMvkjUbP4R8mUU", 0, 149_900_000)]);
+        assert_eq!(
+            (route_plan[1].input_mint.as_str(), route_plan[1].output_mint.as_str()),
+            ("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB")
+        );
+    }
+
+    #[test]
+    fn surfaces_the_unsuccessful_response() {
+        let err = serde_json::from_str::<RaydiumResponse<RaydiumQuoteResponse>>(ROUTE_NOT_FOUND).unwrap().into_data().unwrap_err();
+        assert_eq!(err.to_string(), "raydium responded with ROUTE_NOT_FOUND");
+    }
+
+    async fn compute() -> HttpResponse {
+        HttpResponse::Ok().content_type("application/json").body(QUOTE)
+    }
+
+    async fn swap(request: web::Json<RaydiumSwapRequest>) -> HttpResponse {
+        // the computed swap makes it back untouched
+        if request.swap_response["id"] != "0b5c4a8e-3c6f-4f0e-9d0a-6a1f0c2e7b11" || !request.wrap_sol {
+            return HttpResponse::BadRequest().finish();
+        }
+
+        HttpResponse::Ok().json(serde_json::json!({
+            "id": "1",
+            "version": "V1",
+            "success": true,
+            "data": [{ "transaction": unsigned_transaction(&request.wallet.parse().unwrap()) }],
+        }))
+    }
+
+    #[tokio::test]
+    async fn swaps_through_the_computed_transaction() {
+        let server = HttpServer::new(|| App::new().route("/compute/swap-base-in", web::get().to(compute)).route("/transaction/swap-base-in", web::post().to(swap)))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let raydium = Raydium::new(stub_cfg(format!("http://{}", server.addrs()[0])), reqwest::Client::new());
+        tokio::spawn(server.run());
+
+        let signer = Keypair::new();
+        let params = SwapParams { input_mint: spl_token::native_mint::id(), output_mint: Pubkey::new_unique(), amount: 1_000_000_000, ..SwapParams::default() };
+        let swap = raydium.swap(&params, &signer).await.unwrap();
+
+        assert_eq!(swap.transaction.verify_with_results(), vec![true]);
+        assert!(matches!(swap.response.source, Target::Raydium));
+        assert_eq!(swap.response.route_plan.unwrap().len(), 2);
+    }
+}
@@ -1,9 +1,12 @@
 use std::{fmt::Debug, path::Path, time::SystemTime};
 
-use litesvm::{LiteSVM, types::TransactionMetadata};
+use eyre::eyre;
+use litesvm::LiteSVM;
 use serde::{Deserialize, Serialize};
 use solana_compute_budget::compute_budget::ComputeBudget;
-use solana_sdk::{account::Account, program_pack::Pack, pubkey::Pubkey, rent::Rent, signature::Keypair, signer::Signer};
+use solana_sdk::{
+    account::Account, clock::Clock, instruction::Instruction, program_pack::Pack, pubkey::Pubkey, rent::Rent, signature::Keypair, signer::Signer, transaction::Transaction,
+};
 use spl_associated_token_account::get_associated_token_address;
 
 use crate::{
@@ -12,9 +15,16 @@ use crate::{
 };
 
 pub mod humidifi;
+pub mod meteora_dlmm;
 pub mod obric_v2;
+pub mod phoenix;
+pub mod raydium_amm_v4;
 pub mod raydium_cp;
+pub mod registry;
+pub mod spl_stake_pool;
+pub mod spl_token_swap;
 pub mod swap_state;
+pub mod whirlpool;
 
 /// ..
 pub trait Amm: Adapter + Send + Sync + Debug {
@@ -58,6 +68,17 @@ pub trait Amm: Adapter + Send + Sync + Debug {
     fn is_active(&self) -> bool {
         true
     }
+
+    /// Indicates whether the state is pushed by the market maker on its own (i.e. a pmm), rather than only moved by the swaps.
+    /// Such a market can't be trusted once it stops updating.
+    fn expects_regular_updates(&self) -> bool {
+        false
+    }
+
+    /// The params it was constructed from (see [`KeyedAccount::params`]), for the venues that can't tell everything from the market account.
+    fn params(&self) -> Option<serde_json::Value> {
+        None
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -76,12 +97,18 @@ pub enum Target {
     // poke a particular aggregator for quote/swap
     Jupiter,
     DFlow,
+    Okx,
+    Raydium,
 
     // get the best pricing from any of the integrated AMMs
     // perhaps we can get even more granular here and segment into (prop|public) AMMs
     #[serde(rename = "amms")]
     #[default]
     AMMs,
+
+    // race the AMMs against the aggregators, the best pricing of them all wins
+    #[serde(alias = "best")]
+    All,
 }
 
 pub struct Chroot {
@@ -135,6 +162,10 @@ impl Chroot {
         self.svm.warp_to_slot(slot);
     }
 
+    pub fn slot(&self) -> u64 {
+        self.svm.get_sysvar::<Clock>().slot
+    }
+
     /// Creates fully initialised mint account suitable for use in LiteSVM simulations.
     fn mk_mint_acc(decimals: u8) -> Account {
         let mint = spl_token::state::Mint {
@@ -183,20 +214,20 @@ impl Chroot {
         spl_token::state::Account::unpack(&acc.data).map(|a| a.amount).unwrap_or(0)
     }
 
-    fn get_event_amount_out(&self, metadata: &TransactionMetadata) -> u64 {
-        let amount_out: u64 = metadata
-            .logs
+    /// Simulates a router `swap` instruction and reads its amount out off the `SwapEvent` it logs.
+    /// Nothing is committed to the svm, so the loaded balances stay as they were.
+    /// Stands in for the router's `quote_route` until the bundled magnus-router.so is rebuilt with it.
+    pub fn swap_amount_out(&self, ix: Instruction) -> eyre::Result<u64> {
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&self.wallet_pubkey()), &[&self.wallet], self.svm.latest_blockhash());
+        let meta = self.svm.simulate_transaction(tx).map_err(|failed| eyre!("swap failed: {:?} | logs: {:?}", failed.err, failed.meta.logs))?.meta;
+
+        meta.logs
             .iter()
             .find_map(|log| {
-                if log.contains("SwapEvent") {
-                    // i.e.: "Program log: SwapEvent { dex: Humidifi, amount_in: 1000000000, amount_out: 121518066 }"
-                    log.split("amount_out: ").nth(1)?.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()
-                } else {
-                    None
-                }
+                // i.e.: "Program log: SwapEvent { dex: Humidifi, amount_in: 1000000000, amount_out: 121518066 }"
+                let log = log.split_once("SwapEvent")?.1;
+                log.split("amount_out: ").nth(1)?.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()
             })
-            .expect("couldn't find amount_out in logs");
-
-        amount_out
+            .ok_or_else(|| eyre!("no SwapEvent in the logs: {:?}", meta.logs))
     }
 }
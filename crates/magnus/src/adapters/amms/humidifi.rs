@@ -5,13 +5,13 @@ use magnus_router_client::{instructions::SwapBuilder, programs::ROUTER_ID};
 use magnus_shared::{Dex, Route, pmm_humidifi};
 use rust_decimal::dec;
 use serde::{Deserialize, Serialize};
-use solana_client::rpc_client::RpcClient;
 use solana_instruction::AccountMeta;
-use solana_sdk::{pubkey::Pubkey, sysvar, transaction::Transaction};
+use solana_sdk::{pubkey::Pubkey, sysvar};
 
 use crate::adapters::{
     Adapter, AmmKind, SwapParams,
     amms::{Amm, Chroot},
+    token_meta::TokenMetas,
 };
 
 /*
@@ -24,6 +24,7 @@ pub struct Humidifi {
     key: Pubkey,
     cfg: HumidifiCfg,
     chroot: Chroot,
+    token_metas: TokenMetas,
 }
 
 impl Humidifi {
@@ -77,24 +78,86 @@ impl TryFrom<&serde_json::Value> for HumidifiCfg {
     }
 }
 
+impl From<&HumidifiCfg> for serde_json::Value {
+    /// The inverse of `TryFrom<&serde_json::Value>`, i.e. the shape of the bootstrap file.
+    fn from(cfg: &HumidifiCfg) -> Self {
+        serde_json::json!({
+            "pubkey": cfg.pubkey.to_string(),
+            "accounts": [{
+                "market": cfg.market.to_string(),
+                "base_ta": cfg.base_ta.to_string(),
+                "quote_ta": cfg.quote_ta.to_string(),
+            }],
+            "reserve_mints": cfg.reserve_mints.iter().map(|(mint, decimals)| serde_json::json!([mint.to_string(), decimals])).collect::<Vec<_>>(),
+        })
+    }
+}
+
 impl Adapter for Humidifi {}
 
 impl Humidifi {
-    pub fn new(cfg: HumidifiCfg, client: &RpcClient) -> eyre::Result<Humidifi> {
+    /// Sets up the chroot without any of the market's accounts, they're loaded on `update`.
+    fn from_cfg(cfg: HumidifiCfg) -> eyre::Result<Humidifi> {
         let mut chroot = Chroot::new(cfg.reserve_mints);
         chroot.load_program(ROUTER_ID, "./cfg/programs/magnus-router.so")?;
         chroot.load_program(Pubkey::from_str_const(&pmm_humidifi::id().to_string()), "./cfg/programs/humidifi.so")?;
 
-        let accs = client.get_multiple_accounts(&[cfg.market, cfg.base_ta, cfg.quote_ta])?;
-        chroot.load_accounts(vec![(cfg.market, accs[0].clone().unwrap()), (cfg.base_ta, accs[1].clone().unwrap()), (cfg.quote_ta, accs[2].clone().unwrap())])?;
-
         cfg.reserve_mints.iter().try_for_each(|(mint_addr, _)| -> eyre::Result<()> {
             let ata = Chroot::mk_ata(mint_addr, &chroot.wallet_pubkey(), 0);
             let addr = chroot.wallet_ata(mint_addr);
             chroot.load_accounts(vec![(addr, ata)])
         })?;
 
-        Ok(Humidifi { key: cfg.pubkey, cfg, chroot })
+        Ok(Humidifi { key: cfg.pubkey, cfg, chroot, token_metas: TokenMetas::default() })
+    }
+
+    fn quote_chroot(&mut self, params: &crate::adapters::QuoteParams) -> eyre::Result<crate::adapters::Quote> {
+        let src_ta = Chroot::get_ta(params.input_mint, self.chroot.wallet_pubkey());
+        let dst_ta = Chroot::get_ta(params.output_mint, self.chroot.wallet_pubkey());
+
+        let ata = Chroot::mk_ata(&params.input_mint, &self.chroot.wallet_pubkey(), params.amount);
+        let addr = self.chroot.wallet_ata(&params.input_mint);
+        self.chroot.load_accounts(vec![(addr, ata)])?;
+
+        let routes: Vec<Vec<magnus_router_client::types::Route>> = vec![vec![Route { dexes: vec![Dex::HumidiFi], weights: vec![100] }.into()]];
+        let swap_params = SwapParams {
+            swap_mode: params.swap_mode,
+            amount: params.amount,
+            input_mint: params.input_mint,
+            output_mint: params.output_mint,
+            src_ta,
+            dst_ta,
+            token_transfer_authority: self.chroot.wallet_pubkey(),
+        };
+        let order_id = Chroot::gen_order_id();
+        let construct = self.get_swap_and_account_metas(&swap_params)?;
+
+        // the bundled router has no `quote_route` yet, so the swap is simulated with the least min return it takes
+        let mut swap_builder = SwapBuilder::new();
+        let swap = swap_builder
+            .payer(self.chroot.wallet_pubkey())
+            .source_token_account(src_ta)
+            .destination_token_account(dst_ta)
+            .source_mint(params.input_mint)
+            .destination_mint(params.output_mint)
+            .amount_in(params.amount)
+            .expect_amount_out(1)
+            .min_return(1)
+            .amounts(vec![params.amount])
+            .routes(routes)
+            .order_id(order_id)
+            .add_remaining_accounts(&construct.account_metas);
+
+        let amount_out = self.chroot.swap_amount_out(swap.instruction())?;
+
+        Ok(crate::adapters::Quote {
+            in_amount: params.amount,
+            out_amount: amount_out,
+            fee_amount: 0,
+            fee_pct: dec!(0.0),
+            fee_mint: Pubkey::new_unique(),
+            context_slot: self.chroot.slot(),
+        })
     }
 }
 
@@ -123,6 +186,14 @@ impl Amm for Humidifi {
         pmm_humidifi::ACCOUNTS_LEN
     }
 
+    fn expects_regular_updates(&self) -> bool {
+        true
+    }
+
+    fn params(&self) -> Option<serde_json::Value> {
+        Some((&self.cfg).into())
+    }
+
     fn key(&self) -> solana_sdk::pubkey::Pubkey {
         self.key
     }
@@ -132,11 +203,16 @@ impl Amm for Humidifi {
     }
 
     fn get_accounts_to_update(&self) -> Vec<solana_sdk::pubkey::Pubkey> {
-        [self.key, self.cfg.market, self.cfg.base_ta, self.cfg.quote_ta].to_vec()
+        let [(mint_0, _), (mint_1, _)] = self.cfg.reserve_mints;
+        [self.key, self.cfg.market, self.cfg.base_ta, self.cfg.quote_ta, mint_0, mint_1].to_vec()
     }
 
     fn update(&mut self, account_map: &super::AccountMap, slot: Option<u64>) -> eyre::Result<()> {
-        let accs = account_map.iter().map(|(key, account)| (*key, account.clone())).collect();
+        let mints = self.get_reserve_mints();
+        self.token_metas.update(account_map, &mints)?;
+
+        // the chroot keeps its own plain spl token mints (see `Chroot::new`), the real ones are only parsed for their transfer fees
+        let accs = account_map.iter().filter(|(key, _)| !mints.contains(key)).map(|(key, account)| (*key, account.clone())).collect();
 
         self.chroot.update_accounts(accs)?;
 
@@ -148,50 +224,12 @@ impl Amm for Humidifi {
     }
 
     fn quote(&mut self, params: &crate::adapters::QuoteParams) -> eyre::Result<crate::adapters::Quote> {
-        let src_ta = Chroot::get_ta(params.input_mint, self.chroot.wallet_pubkey());
-        let dst_ta = Chroot::get_ta(params.output_mint, self.chroot.wallet_pubkey());
-
-        let ata = Chroot::mk_ata(&params.input_mint, &self.chroot.wallet_pubkey(), params.amount);
-        let addr = self.chroot.wallet_ata(&params.input_mint);
-        self.chroot.load_accounts(vec![(addr, ata)])?;
-
-        let routes: Vec<Vec<magnus_router_client::types::Route>> = vec![vec![Route { dexes: vec![Dex::HumidiFi], weights: vec![100] }.into()]];
-        let swap_params = SwapParams {
-            swap_mode: params.swap_mode,
-            amount: params.amount,
-            input_mint: params.input_mint,
-            output_mint: params.output_mint,
-            src_ta,
-            dst_ta,
-            token_transfer_authority: self.chroot.wallet_pubkey(),
-        };
-        let order_id = Chroot::gen_order_id();
-        let construct = self.get_swap_and_account_metas(&swap_params)?;
+        // taken out for the call, the chroot being quoted through mutably
+        let token_metas = std::mem::take(&mut self.token_metas);
+        let quote = token_metas.quote(params, self.chroot.slot(), |params| self.quote_chroot(params));
+        self.token_metas = token_metas;
 
-        let mut swap_builder = SwapBuilder::new();
-        let swap = swap_builder
-            .payer(self.chroot.wallet_pubkey())
-            .source_token_account(src_ta)
-            .destination_token_account(dst_ta)
-            .source_mint(params.input_mint)
-            .destination_mint(params.output_mint)
-            .amount_in(params.amount)
-            .expect_amount_out(0)
-            .min_return(0)
-            .amounts(vec![params.amount])
-            .routes(routes)
-            .order_id(order_id)
-            .add_remaining_accounts(&construct.account_metas);
-
-        let ix = swap.instruction();
-        let tx = Transaction::new_signed_with_payer(&[ix], Some(&self.chroot.wallet_pubkey()), &[&self.chroot.wallet], self.chroot.svm.latest_blockhash());
-        let res = self.chroot.svm.send_transaction(tx).map_err(|e| eyre!("{:?}", e))?;
-
-        let amount_out = self.chroot.get_event_amount_out(&res);
-
-        // then: reset the env amounts by nullifying the amounts of the input and output tokens
-
-        Ok(crate::adapters::Quote { in_amount: params.amount, out_amount: amount_out, fee_amount: 0, fee_pct: dec!(0.0), fee_mint: Pubkey::new_unique() })
+        quote
     }
 
     fn get_swap_and_account_metas(&self, params: &crate::adapters::SwapParams) -> eyre::Result<crate::adapters::SwapAndAccountMetas> {
@@ -217,11 +255,20 @@ impl Amm for Humidifi {
         Ok(res)
     }
 
-    fn from_keyed_account(_keyed_account: &super::KeyedAccount) -> eyre::Result<Self>
+    /// There's no telling the market's token accounts and mints from its data, they come in the params.
+    fn from_keyed_account(keyed_account: &super::KeyedAccount) -> eyre::Result<Self>
     where
         Self: Sized,
     {
-        unimplemented!()
+        let params = keyed_account.params.as_ref().ok_or_else(|| eyre!("humidifi market {} requires its accounts in the params", keyed_account.key))?;
+        let cfg = HumidifiCfg::try_from(params).map_err(|e| eyre!(e))?;
+
+        let mut humidifi = Humidifi::from_cfg(HumidifiCfg { pubkey: keyed_account.key, ..cfg })?;
+        if humidifi.cfg.market == keyed_account.key {
+            humidifi.chroot.load_accounts(vec![(keyed_account.key, keyed_account.account.clone())])?;
+        }
+
+        Ok(humidifi)
     }
 }
 
@@ -254,6 +301,12 @@ mod tests {
         assert_eq!(cfg.reserve_mints[1].1, 9);
     }
 
+    #[test]
+    fn humidifi_cfg_round_trips_through_json() {
+        let cfg = HumidifiCfg::try_from(&valid_json()).unwrap();
+        assert_eq!(serde_json::Value::from(&cfg), valid_json());
+    }
+
     #[test]
     fn humidifi_cfg_try_from_missing_pubkey() {
         let mut json = valid_json();
@@ -0,0 +1,6 @@
+pub mod integration;
+pub mod math;
+pub mod state;
+
+pub use integration::MeteoraDlmm;
+pub use state::{Bin, BinArray, LbPair};
@@ -0,0 +1,317 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use borsh::BorshDeserialize;
+use eyre::eyre;
+use magnus_shared::amm_meteora_dlmm;
+use solana_instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::adapters::{
+    Adapter, AmmKind,
+    amms::{
+        AccountMap, Amm, KeyedAccount, Quote, QuoteParams, SwapAndAccountMetas, SwapParams,
+        meteora_dlmm::{
+            math,
+            state::{self, BinArray, LbPair},
+        },
+    },
+    token_meta::TokenMetas,
+};
+
+/// The number of bin arrays a swap passes, the one holding the active bin and the next two in the swap's direction.
+const BIN_ARRAYS_PER_SWAP: i64 = 3;
+
+const STATUS_ENABLED: u8 = 0;
+
+#[derive(Clone, Debug, Default)]
+pub struct MeteoraDlmm {
+    key: Pubkey,
+    pub state: LbPair,
+    bitmap_extension_key: Pubkey,
+    has_bitmap_extension: bool,
+    /// The tracked bin arrays, keyed by their index.
+    bin_arrays: HashMap<i64, BinArray>,
+    slot: u64,
+    token_metas: TokenMetas,
+}
+
+impl MeteoraDlmm {
+    fn program_id() -> Pubkey {
+        Pubkey::from_str_const(&amm_meteora_dlmm::id().to_string())
+    }
+
+    /// The indexes of the bin arrays a swap from the active bin passes, in the swap's direction.
+    fn swap_bin_array_indexes(&self, swap_for_y: bool) -> Vec<i64> {
+        let start = state::bin_id_to_bin_array_index(self.state.active_id);
+        let (min, max) = (state::bin_id_to_bin_array_index(self.state.parameters.min_bin_id), state::bin_id_to_bin_array_index(self.state.parameters.max_bin_id));
+        let step = if swap_for_y { -1 } else { 1 };
+
+        (0..BIN_ARRAYS_PER_SWAP).map(|i| start + i * step).filter(|index| (min..=max).contains(index)).collect()
+    }
+
+    /// The indexes of the bin arrays a swap in either direction passes.
+    fn tracked_bin_array_indexes(&self) -> Vec<i64> {
+        let mut indexes = self.swap_bin_array_indexes(true);
+        indexes.extend(self.swap_bin_array_indexes(false).into_iter().skip(1));
+        indexes
+    }
+
+    fn bin_array_address(&self, index: i64) -> Pubkey {
+        state::bin_array_address(&Self::program_id(), &self.key, index)
+    }
+
+    /// Walks the bins from the active one, draining each at its price and the fee for the volatility crossing it adds.
+    fn quote_pool(&self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        let swap_for_y = if quote_params.input_mint == self.state.token_x_mint {
+            true
+        } else if quote_params.input_mint == self.state.token_y_mint {
+            false
+        } else {
+            return Err(eyre!("mint {} isn't traded by lb pair {}", quote_params.input_mint, self.key));
+        };
+        if self.state.status != STATUS_ENABLED {
+            return Err(eyre!("lb pair {} is disabled", self.key));
+        }
+
+        let mut lb_pair = self.state.clone();
+        let now = SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64;
+        math::update_references(&mut lb_pair, now);
+
+        // bins outside of the reachable bin arrays can't be swapped against
+        let indexes = self.swap_bin_array_indexes(swap_for_y);
+        let last = *indexes.last().ok_or_else(|| eyre!("no reachable bin arrays"))?;
+        let bound_bin_id = if swap_for_y {
+            (last * state::MAX_BIN_PER_ARRAY as i64) as i32
+        } else {
+            ((last + 1) * state::MAX_BIN_PER_ARRAY as i64 - 1) as i32
+        }
+        .clamp(lb_pair.parameters.min_bin_id, lb_pair.parameters.max_bin_id);
+
+        let (mut amount_in_left, mut amount_out, mut fee_amount) = (quote_params.amount, 0u64, 0u64);
+        let mut fee_rate = math::total_fee(&lb_pair);
+
+        loop {
+            // missing bin arrays are uninitialized, i.e. hold no liquidity
+            let bin = self.bin_arrays.get(&state::bin_id_to_bin_array_index(lb_pair.active_id)).and_then(|bin_array| bin_array.bin(lb_pair.active_id));
+            if let Some(bin) = bin.filter(|bin| bin.price != 0 && if swap_for_y { bin.amount_y } else { bin.amount_x } != 0) {
+                math::update_volatility_accumulator(&mut lb_pair);
+                fee_rate = math::total_fee(&lb_pair);
+
+                let step = math::swap_bin(bin, amount_in_left, swap_for_y, fee_rate).ok_or_else(|| eyre!("bin swap overflow"))?;
+                amount_in_left -= step.amount_in_with_fees;
+                amount_out = amount_out.checked_add(step.amount_out).ok_or_else(|| eyre!("overflow"))?;
+                fee_amount += step.fee;
+            }
+
+            if amount_in_left == 0 {
+                break;
+            }
+            if lb_pair.active_id == bound_bin_id {
+                return Err(eyre!("amount exceeds the liquidity of the bin arrays a swap passes"));
+            }
+            lb_pair.active_id += if swap_for_y { -1 } else { 1 };
+        }
+
+        Ok(Quote {
+            in_amount: quote_params.amount,
+            out_amount: amount_out,
+            fee_amount,
+            fee_mint: quote_params.input_mint,
+            fee_pct: rust_decimal::Decimal::new(fee_rate as i64, 9),
+            context_slot: self.slot,
+        })
+    }
+}
+
+impl Adapter for MeteoraDlmm {}
+
+impl Amm for MeteoraDlmm {
+    fn program_id(&self) -> Pubkey {
+        Self::program_id()
+    }
+
+    fn label(&self) -> String {
+        "Meteora DLMM".to_string()
+    }
+
+    fn get_accounts_len(&self) -> usize {
+        amm_meteora_dlmm::ACCOUNTS_LEN
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![self.state.token_x_mint, self.state.token_y_mint]
+    }
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        let mut accounts = vec![self.key, self.bitmap_extension_key, self.state.token_x_mint, self.state.token_y_mint];
+        accounts.extend(self.tracked_bin_array_indexes().into_iter().map(|index| self.bin_array_address(index)));
+        accounts
+    }
+
+    // the tracked bin arrays follow the active bin
+    fn has_dynamic_accounts(&self) -> bool {
+        true
+    }
+
+    fn is_active(&self) -> bool {
+        self.state.status == STATUS_ENABLED
+    }
+
+    fn from_keyed_account(keyed_account: &KeyedAccount) -> eyre::Result<Self>
+    where
+        Self: Sized,
+    {
+        let state = LbPair::deserialize(&mut &keyed_account.account.data[8..])?;
+        let bitmap_extension_key = state::bin_array_bitmap_extension_address(&Self::program_id(), &keyed_account.key);
+
+        Ok(MeteoraDlmm { key: keyed_account.key, state, bitmap_extension_key, ..MeteoraDlmm::default() })
+    }
+
+    fn update(&mut self, account_map: &AccountMap, slot: Option<u64>) -> eyre::Result<()> {
+        let lb_pair = account_map.get(&self.key).ok_or_else(|| eyre!("lb pair not found"))?;
+        self.state = LbPair::deserialize(&mut &lb_pair.data[8..])?;
+
+        // only pairs with bin arrays past the pair's own bitmap have an extension
+        self.has_bitmap_extension = account_map.get(&self.bitmap_extension_key).is_some_and(|extension| !extension.data.is_empty());
+
+        let mut bin_arrays = HashMap::new();
+        for index in self.tracked_bin_array_indexes() {
+            if let Some(bin_array) = account_map.get(&self.bin_array_address(index)) {
+                bin_arrays.insert(index, BinArray::deserialize(&mut &bin_array.data[8..])?);
+            }
+        }
+        self.bin_arrays = bin_arrays;
+
+        self.token_metas.update(account_map, &[self.state.token_x_mint, self.state.token_y_mint])?;
+        if let Some(slot) = slot {
+            self.slot = slot;
+        }
+
+        Ok(())
+    }
+
+    fn quote(&mut self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        self.token_metas.quote(quote_params, self.slot, |quote_params| self.quote_pool(quote_params))
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> eyre::Result<SwapAndAccountMetas> {
+        let swap_for_y = swap_params.input_mint == self.state.token_x_mint;
+
+        // the program loads every bin array it's passed, so only initialized ones are passed and the last one is repeated
+        let mut indexes: Vec<i64> = self.swap_bin_array_indexes(swap_for_y).into_iter().filter(|index| self.bin_arrays.contains_key(index)).collect();
+        if indexes.is_empty() {
+            indexes.push(state::bin_id_to_bin_array_index(self.state.active_id));
+        }
+        let bin_arrays: Vec<Pubkey> = (0..BIN_ARRAYS_PER_SWAP as usize).map(|i| self.bin_array_address(indexes[i.min(indexes.len() - 1)])).collect();
+
+        let program_id = Self::program_id();
+        let bitmap_extension = if self.has_bitmap_extension { self.bitmap_extension_key } else { program_id };
+        let token_program = |mint: &Pubkey| self.token_metas.get(mint).map_or(spl_token::id(), |meta| meta.program);
+
+        let account_metas = vec![
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new(swap_params.token_transfer_authority, true),
+            AccountMeta::new(swap_params.src_ta, false),
+            AccountMeta::new(swap_params.dst_ta, false),
+            AccountMeta::new(self.key, false),
+            AccountMeta::new_readonly(bitmap_extension, false),
+            AccountMeta::new(self.state.reserve_x, false),
+            AccountMeta::new(self.state.reserve_y, false),
+            AccountMeta::new_readonly(self.state.token_x_mint, false),
+            AccountMeta::new_readonly(self.state.token_y_mint, false),
+            AccountMeta::new(self.state.oracle, false),
+            // no host fee
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new_readonly(token_program(&self.state.token_x_mint), false),
+            AccountMeta::new_readonly(token_program(&self.state.token_y_mint), false),
+            AccountMeta::new_readonly(state::event_authority_address(&program_id), false),
+            AccountMeta::new(bin_arrays[0], false),
+            AccountMeta::new(bin_arrays[1], false),
+            AccountMeta::new(bin_arrays[2], false),
+        ];
+
+        Ok(SwapAndAccountMetas { swap: AmmKind::MeteoraDlmm, account_metas })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{SwapMode, amms::meteora_dlmm::state::Bin};
+
+    const ONE: u128 = 1 << 64;
+
+    // 1_000 of each token in every bin from -70 to 69, all at a price of 1 to keep the numbers round
+    fn lb_pair() -> MeteoraDlmm {
+        let mut state = LbPair { active_id: 0, bin_step: 10, token_x_mint: Pubkey::new_unique(), token_y_mint: Pubkey::new_unique(), ..LbPair::default() };
+        state.parameters.base_factor = 10_000;
+        state.parameters.min_bin_id = -443_636;
+        state.parameters.max_bin_id = 443_636;
+        state.parameters.max_volatility_accumulator = 350_000;
+        // keep the references of the last swap in place
+        state.parameters.filter_period = u16::MAX;
+        state.v_parameters.last_update_timestamp = i64::MAX;
+
+        let bin_array = |index: i64| BinArray {
+            index,
+            version: 1,
+            padding: [0; 7],
+            lb_pair: Pubkey::default(),
+            bins: [Bin { amount_x: 1_000, amount_y: 1_000, price: ONE, ..Bin::default() }; state::MAX_BIN_PER_ARRAY as usize],
+        };
+
+        MeteoraDlmm { key: Pubkey::new_unique(), state, bin_arrays: HashMap::from([(-1, bin_array(-1)), (0, bin_array(0))]), ..MeteoraDlmm::default() }
+    }
+
+    fn params(lb_pair: &MeteoraDlmm, swap_for_y: bool, amount: u64) -> QuoteParams {
+        let (input_mint, output_mint) = if swap_for_y {
+            (lb_pair.state.token_x_mint, lb_pair.state.token_y_mint)
+        } else {
+            (lb_pair.state.token_y_mint, lb_pair.state.token_x_mint)
+        };
+        QuoteParams { swap_mode: SwapMode::ExactIn, amount, input_mint, output_mint }
+    }
+
+    #[test]
+    fn quotes_within_the_active_bin() {
+        let mut lb_pair = lb_pair();
+
+        for swap_for_y in [true, false] {
+            let quote = lb_pair.quote(&params(&lb_pair, swap_for_y, 500)).unwrap();
+            // 0.1% base fee
+            assert_eq!((quote.out_amount, quote.fee_amount), (499, 1), "{:?}", quote);
+        }
+    }
+
+    #[test]
+    fn walks_bins_with_the_variable_fee() {
+        let mut lb_pair = lb_pair();
+        lb_pair.state.parameters.variable_fee_control = 40_000;
+
+        // drains the active bin at the base fee, then bin -1 costs 0.1004% as it's one bin off the reference
+        let quote = lb_pair.quote(&params(&lb_pair, true, 1_500)).unwrap();
+        assert_eq!(quote.out_amount, 1_000 + 497);
+        assert_eq!(quote.fee_amount, 2 + 1);
+
+        // the 140 bins of the two tracked arrays hold 140_000 of token y
+        assert!(lb_pair.quote(&params(&lb_pair, true, 150_000)).is_err());
+        lb_pair.state.status = 1;
+        assert!(lb_pair.quote(&params(&lb_pair, true, 500)).is_err());
+    }
+
+    #[test]
+    fn tracks_the_bin_arrays_around_the_active_bin() {
+        let mut lb_pair = lb_pair();
+
+        assert_eq!(lb_pair.swap_bin_array_indexes(true), vec![0, -1, -2]);
+        assert_eq!(lb_pair.swap_bin_array_indexes(false), vec![0, 1, 2]);
+        assert_eq!(lb_pair.tracked_bin_array_indexes().len(), 5);
+
+        lb_pair.state.active_id = -71;
+        assert_eq!(lb_pair.swap_bin_array_indexes(false), vec![-2, -1, 0]);
+    }
+}
@@ -0,0 +1,159 @@
+//! DLMM's per bin constant sum math and dynamic fee, mirroring the on-chain rounding.
+
+use spl_math::uint::U256;
+
+use super::state::{Bin, LbPair};
+
+pub const FEE_PRECISION: u128 = 1_000_000_000;
+pub const MAX_FEE_RATE: u128 = 100_000_000;
+pub const BASIS_POINT_MAX: i64 = 10_000;
+
+const SCALE_OFFSET: u32 = 64;
+const VARIABLE_FEE_DENOMINATOR: u128 = 100_000_000_000;
+
+fn div_ceil(numerator: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    let (quotient, remainder) = numerator.div_mod(denominator);
+    Some(if remainder.is_zero() { quotient } else { quotient + 1 })
+}
+
+fn to_u64(value: U256) -> Option<u64> {
+    (value.bits() <= 64).then(|| value.as_u64())
+}
+
+/// `amount * price >> 64`
+fn mul_shr(amount: u64, price: u128, round_up: bool) -> Option<u64> {
+    let product = U256::from(amount) * U256::from(price);
+    let shifted = product >> SCALE_OFFSET;
+    let round = round_up && !(product & U256::from(u64::MAX)).is_zero();
+    to_u64(if round { shifted + 1 } else { shifted })
+}
+
+/// `amount << 64 / price`
+fn shl_div(amount: u64, price: u128, round_up: bool) -> Option<u64> {
+    let numerator = U256::from(amount) << SCALE_OFFSET;
+    let quotient = if round_up {
+        div_ceil(numerator, U256::from(price))?
+    } else {
+        numerator.checked_div(U256::from(price))?
+    };
+    to_u64(quotient)
+}
+
+/// Moves the volatility and index references as the on-chain program does at the start of a swap.
+pub fn update_references(lb_pair: &mut LbPair, now: i64) {
+    let (parameters, v_parameters) = (&lb_pair.parameters, &mut lb_pair.v_parameters);
+    let elapsed = now.saturating_sub(v_parameters.last_update_timestamp);
+
+    if elapsed >= parameters.filter_period as i64 {
+        v_parameters.index_reference = lb_pair.active_id;
+        v_parameters.volatility_reference = if elapsed < parameters.decay_period as i64 {
+            (v_parameters.volatility_accumulator as u64 * parameters.reduction_factor as u64 / BASIS_POINT_MAX as u64) as u32
+        } else {
+            0
+        };
+    }
+}
+
+/// Accumulates the volatility of crossing from the index reference to the active bin.
+pub fn update_volatility_accumulator(lb_pair: &mut LbPair) {
+    let delta_id = (lb_pair.v_parameters.index_reference as i64 - lb_pair.active_id as i64).unsigned_abs();
+    let volatility_accumulator = lb_pair.v_parameters.volatility_reference as u64 + delta_id * BASIS_POINT_MAX as u64;
+    lb_pair.v_parameters.volatility_accumulator = volatility_accumulator.min(lb_pair.parameters.max_volatility_accumulator as u64) as u32;
+}
+
+pub fn base_fee(lb_pair: &LbPair) -> u128 {
+    lb_pair.parameters.base_factor as u128 * lb_pair.bin_step as u128 * 10 * 10u128.pow(lb_pair.parameters.base_fee_power_factor as u32)
+}
+
+pub fn variable_fee(lb_pair: &LbPair) -> u128 {
+    if lb_pair.parameters.variable_fee_control == 0 {
+        return 0;
+    }
+    let volatility = lb_pair.v_parameters.volatility_accumulator as u128 * lb_pair.bin_step as u128;
+    (lb_pair.parameters.variable_fee_control as u128 * volatility * volatility).div_ceil(VARIABLE_FEE_DENOMINATOR)
+}
+
+/// The fee rate over `FEE_PRECISION`.
+pub fn total_fee(lb_pair: &LbPair) -> u128 {
+    (base_fee(lb_pair) + variable_fee(lb_pair)).min(MAX_FEE_RATE)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BinSwap {
+    pub amount_in_with_fees: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+}
+
+/// Swaps up to `amount_in` against a single bin at `fee_rate`.
+pub fn swap_bin(bin: &Bin, amount_in: u64, swap_for_y: bool, fee_rate: u128) -> Option<BinSwap> {
+    let (max_amount_out, max_amount_in) = if swap_for_y {
+        (bin.amount_y, shl_div(bin.amount_y, bin.price, true)?)
+    } else {
+        (bin.amount_x, mul_shr(bin.amount_x, bin.price, true)?)
+    };
+    let max_fee = u64::try_from((max_amount_in as u128 * fee_rate).div_ceil(FEE_PRECISION - fee_rate)).ok()?;
+    let max_amount_in = max_amount_in.checked_add(max_fee)?;
+
+    if amount_in >= max_amount_in {
+        return Some(BinSwap { amount_in_with_fees: max_amount_in, amount_out: max_amount_out, fee: max_fee });
+    }
+
+    let fee = u64::try_from((amount_in as u128 * fee_rate).div_ceil(FEE_PRECISION)).ok()?;
+    let amount_in_after_fee = amount_in - fee;
+    let amount_out = if swap_for_y {
+        mul_shr(amount_in_after_fee, bin.price, false)?
+    } else {
+        shl_div(amount_in_after_fee, bin.price, false)?
+    };
+
+    Some(BinSwap { amount_in_with_fees: amount_in, amount_out: amount_out.min(max_amount_out), fee })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: u128 = 1 << 64;
+
+    #[test]
+    fn fills_or_drains_a_bin() {
+        let bin = Bin { amount_x: 1_000, amount_y: 1_000, price: ONE, ..Bin::default() };
+        // 1% fee
+        let fee_rate = 10_000_000;
+
+        let partial = swap_bin(&bin, 500, true, fee_rate).unwrap();
+        assert_eq!(partial, BinSwap { amount_in_with_fees: 500, amount_out: 495, fee: 5 });
+
+        // the fee is charged on top of the 1000 it takes to drain the bin
+        let drained = swap_bin(&bin, 5_000, true, fee_rate).unwrap();
+        assert_eq!(drained, BinSwap { amount_in_with_fees: 1_011, amount_out: 1_000, fee: 11 });
+    }
+
+    #[test]
+    fn computes_the_dynamic_fee() {
+        let mut lb_pair = LbPair { active_id: 10, bin_step: 10, ..LbPair::default() };
+        lb_pair.parameters.base_factor = 10_000;
+        lb_pair.parameters.variable_fee_control = 40_000;
+        lb_pair.parameters.max_volatility_accumulator = 350_000;
+        lb_pair.parameters.filter_period = 30;
+        lb_pair.parameters.decay_period = 600;
+        lb_pair.parameters.reduction_factor = 5_000;
+        lb_pair.v_parameters.volatility_accumulator = 20_000;
+
+        // 0.1% base fee, no volatility before the swap moves away from the reference
+        update_references(&mut lb_pair, 100);
+        assert_eq!(lb_pair.v_parameters.volatility_reference, 10_000);
+        assert_eq!(base_fee(&lb_pair), 1_000_000);
+
+        lb_pair.active_id = 12;
+        update_volatility_accumulator(&mut lb_pair);
+        assert_eq!(lb_pair.v_parameters.volatility_accumulator, 30_000);
+        // 40_000 * (30_000 * 10)^2 / 1e11
+        assert_eq!(variable_fee(&lb_pair), 36_000);
+        assert_eq!(total_fee(&lb_pair), 1_036_000);
+    }
+}
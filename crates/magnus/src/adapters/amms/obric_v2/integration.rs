@@ -1,5 +1,5 @@
 use anchor_lang::AccountDeserialize;
-use anchor_spl::token::{Mint, TokenAccount};
+use anchor_spl::token_interface::TokenAccount;
 use borsh::BorshDeserialize;
 use eyre::Result;
 use magnus_shared::{pmm_obric_v2, spl_token};
@@ -12,6 +12,7 @@ use crate::adapters::{
         AccountMap, Amm, KeyedAccount, Quote, QuoteParams, SwapAndAccountMetas, SwapParams,
         obric_v2::state::{PriceFeed, SSTradingPair},
     },
+    token_meta::TokenMetas,
 };
 
 #[derive(thiserror::Error, Debug)]
@@ -28,12 +29,30 @@ pub struct ObricV2 {
     current_y: u64,
     pub x_decimals: u8,
     pub y_decimals: u8,
+    slot: u64,
+    token_metas: TokenMetas,
 }
 
 impl ObricV2 {
     pub fn new() -> ObricV2 {
         ObricV2::default()
     }
+
+    /// Quotes the pool itself, i.e. the amount that reaches and leaves the reserves.
+    fn quote_pool(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        let (output_after_fee, protocol_fee, lp_fee) = if quote_params.input_mint.eq(&self.state.mint_x) {
+            self.state.quote_x_to_y(quote_params.amount, self.current_x, self.current_y)?
+        } else if quote_params.input_mint.eq(&self.state.mint_y) {
+            self.state.quote_y_to_x(quote_params.amount, self.current_x, self.current_y)?
+        } else {
+            (0u64, 0u64, 0u64)
+        };
+        if output_after_fee == 0 {
+            Ok(Quote::default())
+        } else {
+            Ok(Quote { out_amount: output_after_fee, fee_amount: protocol_fee + lp_fee, fee_mint: quote_params.output_mint, context_slot: self.slot, ..Quote::default() })
+        }
+    }
 }
 
 impl Adapter for ObricV2 {}
@@ -63,15 +82,17 @@ impl Amm for ObricV2 {
         true
     }
 
+    // priced off the oracle feeds, which move every few slots
+    fn expects_regular_updates(&self) -> bool {
+        true
+    }
+
     fn get_accounts_to_update(&self) -> Vec<Pubkey> {
-        if self.x_decimals == 0 && self.y_decimals == 0 {
-            [self.state.reserve_x, self.state.reserve_y, self.state.x_price_feed_id, self.state.y_price_feed_id, self.state.mint_x, self.state.mint_y].to_vec()
-        } else {
-            [self.state.reserve_x, self.state.reserve_y, self.state.x_price_feed_id, self.state.y_price_feed_id].to_vec()
-        }
+        // the mints are kept in, a transfer fee update takes effect at a later epoch
+        [self.state.reserve_x, self.state.reserve_y, self.state.x_price_feed_id, self.state.y_price_feed_id, self.state.mint_x, self.state.mint_y].to_vec()
     }
 
-    fn update(&mut self, accounts_map: &AccountMap, _: Option<u64>) -> Result<()> {
+    fn update(&mut self, accounts_map: &AccountMap, slot: Option<u64>) -> Result<()> {
         let reserve_x_data = &mut &accounts_map.get(&self.state.reserve_x).ok_or(AmmError::AccountNotFound)?.data[..];
         let reserve_y_data = &mut &accounts_map.get(&self.state.reserve_x).ok_or(AmmError::AccountNotFound)?.data[..];
         let reserve_x_token_account = &TokenAccount::try_deserialize(reserve_x_data)?;
@@ -80,14 +101,13 @@ impl Amm for ObricV2 {
         self.current_y = reserve_y_token_account.amount;
 
         if self.x_decimals == 0 && self.y_decimals == 0 {
-            let mint_x_data = &mut &accounts_map.get(&self.state.mint_x).ok_or(AmmError::AccountNotFound)?.data[..];
-            let min_x = &Mint::try_deserialize(mint_x_data)?;
-
-            let mint_y_data = &mut &accounts_map.get(&self.state.mint_y).ok_or(AmmError::AccountNotFound)?.data[..];
-            let min_y = &Mint::try_deserialize(mint_y_data)?;
-
-            self.x_decimals = min_x.decimals;
-            self.y_decimals = min_y.decimals;
+            accounts_map.get(&self.state.mint_x).ok_or(AmmError::AccountNotFound)?;
+            accounts_map.get(&self.state.mint_y).ok_or(AmmError::AccountNotFound)?;
+        }
+        self.token_metas.update(accounts_map, &[self.state.mint_x, self.state.mint_y])?;
+        if let (Some(mint_x), Some(mint_y)) = (self.token_metas.get(&self.state.mint_x), self.token_metas.get(&self.state.mint_y)) {
+            self.x_decimals = mint_x.decimals;
+            self.y_decimals = mint_y.decimals;
         }
 
         let price_x_data = &mut &accounts_map.get(&self.state.x_price_feed_id).ok_or(AmmError::AccountNotFound)?.data[8..];
@@ -97,22 +117,14 @@ impl Amm for ObricV2 {
         let price_x = price_x_fee.price_normalized()?.price as u64;
         let price_y = price_y_fee.price_normalized()?.price as u64;
         self.state.update_price(price_x, price_y, self.x_decimals, self.y_decimals)?;
+        if let Some(slot) = slot {
+            self.slot = slot;
+        }
         Ok(())
     }
 
     fn quote(&mut self, quote_params: &QuoteParams) -> Result<Quote> {
-        let (output_after_fee, protocol_fee, lp_fee) = if quote_params.input_mint.eq(&self.state.mint_x) {
-            self.state.quote_x_to_y(quote_params.amount, self.current_x, self.current_y)?
-        } else if quote_params.input_mint.eq(&self.state.mint_y) {
-            self.state.quote_y_to_x(quote_params.amount, self.current_x, self.current_y)?
-        } else {
-            (0u64, 0u64, 0u64)
-        };
-        if output_after_fee == 0 {
-            Ok(Quote::default())
-        } else {
-            Ok(Quote { out_amount: output_after_fee, fee_amount: protocol_fee + lp_fee, fee_mint: quote_params.output_mint, ..Quote::default() })
-        }
+        self.token_metas.quote(quote_params, self.slot, |quote_params| self.quote_pool(quote_params))
     }
 
     //fn clone_amm(&self) -> Box<dyn Amm + Send + Sync> {
@@ -160,7 +172,16 @@ impl Amm for ObricV2 {
     {
         let data = &mut &keyed_account.account.data.clone()[8..];
         let ss_trading_pair = SSTradingPair::deserialize(data)?;
-        Ok(Self { key: keyed_account.key, state: ss_trading_pair, current_x: 0u64, current_y: 0u64, x_decimals: 0u8, y_decimals: 0u8 })
+        Ok(Self {
+            key: keyed_account.key,
+            state: ss_trading_pair,
+            current_x: 0u64,
+            current_y: 0u64,
+            x_decimals: 0u8,
+            y_decimals: 0u8,
+            slot: 0u64,
+            token_metas: TokenMetas::default(),
+        })
     }
 
     fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas> {
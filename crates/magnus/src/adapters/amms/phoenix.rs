@@ -0,0 +1,5 @@
+pub mod integration;
+pub mod state;
+
+pub use integration::Phoenix;
+pub use state::{FifoMarketHeader, MarketHeader, Order};
@@ -0,0 +1,263 @@
+use std::time::SystemTime;
+
+use eyre::eyre;
+use magnus_shared::clob_phoenix;
+use solana_instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::adapters::{
+    Adapter, AmmKind,
+    amms::{
+        AccountMap, Amm, KeyedAccount, Quote, QuoteParams, SwapAndAccountMetas, SwapParams,
+        phoenix::state::{self, FifoMarketHeader, MARKET_STATUS_ACTIVE, MarketHeader, Order},
+    },
+};
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+#[derive(Clone, Debug, Default)]
+pub struct Phoenix {
+    key: Pubkey,
+    pub header: MarketHeader,
+    pub market: FifoMarketHeader,
+    /// The resting bids, the highest price first.
+    bids: Vec<Order>,
+    /// The resting asks, the lowest price first.
+    asks: Vec<Order>,
+    slot: u64,
+}
+
+impl Phoenix {
+    fn program_id() -> Pubkey {
+        Pubkey::from_str_const(&clob_phoenix::id().to_string())
+    }
+
+    fn load(&mut self, data: &[u8]) -> eyre::Result<()> {
+        let (header, market, bids, asks) = state::parse_market(data)?;
+        (self.header, self.market, self.bids, self.asks) = (header, market, bids, asks);
+        Ok(())
+    }
+
+    /// The quote lots of `base_lots` at `price_in_ticks`, times the base lots per base unit to keep them exact.
+    fn adjusted_quote_lots(&self, price_in_ticks: u64, base_lots: u64) -> u128 {
+        price_in_ticks as u128 * self.market.tick_size_in_quote_lots_per_base_unit as u128 * base_lots as u128
+    }
+
+    /// Walks the book as an immediate-or-cancel taker order, paying the taker fee in quote lots.
+    fn quote_book(&self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        let is_ask = if quote_params.input_mint == self.header.base_params.mint_key {
+            true
+        } else if quote_params.input_mint == self.header.quote_params.mint_key {
+            false
+        } else {
+            return Err(eyre!("mint {} isn't traded by market {}", quote_params.input_mint, self.key));
+        };
+        if self.header.status != MARKET_STATUS_ACTIVE {
+            return Err(eyre!("market {} doesn't take orders", self.key));
+        }
+        if self.header.base_lot_size == 0 || self.header.quote_lot_size == 0 || self.market.base_lots_per_base_unit == 0 {
+            return Err(eyre!("market {} isn't initialized", self.key));
+        }
+
+        let now = SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        let base_lots_per_base_unit = self.market.base_lots_per_base_unit as u128;
+        let taker_fee_bps = self.market.taker_fee_bps as u128;
+        let book = if is_ask { &self.bids } else { &self.asks };
+        let mut orders = book.iter().filter(|order| !order.is_expired(self.slot, now));
+
+        let (out_amount, fee_lots) = if is_ask {
+            // sells its base lots into the bids, the fee comes out of the quote lots received
+            let mut base_lots_left = quote_params.amount / self.header.base_lot_size;
+            let mut adjusted_quote_lots = 0u128;
+            while base_lots_left > 0 {
+                let order = orders.next().ok_or_else(|| eyre!("amount exceeds the bids of market {}", self.key))?;
+                let fill = base_lots_left.min(order.num_base_lots);
+                adjusted_quote_lots += self.adjusted_quote_lots(order.price_in_ticks, fill);
+                base_lots_left -= fill;
+            }
+
+            let quote_lots = adjusted_quote_lots / base_lots_per_base_unit;
+            let fee_lots = (quote_lots * taker_fee_bps).div_ceil(BPS_DENOMINATOR);
+            ((quote_lots - fee_lots) * self.header.quote_lot_size as u128, fee_lots)
+        } else {
+            // buys base lots from the asks with the quote lots left once the fee is set aside
+            let budget = (quote_params.amount / self.header.quote_lot_size) as u128;
+            let mut adjusted_budget_left = budget * BPS_DENOMINATOR / (BPS_DENOMINATOR + taker_fee_bps) * base_lots_per_base_unit;
+            let (mut base_lots, mut adjusted_quote_lots) = (0u64, 0u128);
+            for order in orders {
+                let price = self.adjusted_quote_lots(order.price_in_ticks, 1);
+                let fill = (adjusted_budget_left / price).min(order.num_base_lots as u128) as u64;
+                if fill == 0 {
+                    break;
+                }
+                base_lots += fill;
+                adjusted_quote_lots += price * fill as u128;
+                adjusted_budget_left -= price * fill as u128;
+            }
+            if base_lots == 0 {
+                return Err(eyre!("amount buys no base lots of market {}", self.key));
+            }
+
+            let quote_lots = adjusted_quote_lots.div_ceil(base_lots_per_base_unit);
+            let fee_lots = (quote_lots * taker_fee_bps).div_ceil(BPS_DENOMINATOR);
+            (base_lots as u128 * self.header.base_lot_size as u128, fee_lots)
+        };
+
+        Ok(Quote {
+            in_amount: quote_params.amount,
+            out_amount: u64::try_from(out_amount)?,
+            fee_amount: u64::try_from(fee_lots * self.header.quote_lot_size as u128)?,
+            fee_mint: self.header.quote_params.mint_key,
+            fee_pct: rust_decimal::Decimal::new(self.market.taker_fee_bps as i64, 4),
+            context_slot: self.slot,
+        })
+    }
+}
+
+impl Adapter for Phoenix {}
+
+impl Amm for Phoenix {
+    fn program_id(&self) -> Pubkey {
+        Self::program_id()
+    }
+
+    fn label(&self) -> String {
+        "Phoenix".to_string()
+    }
+
+    fn get_accounts_len(&self) -> usize {
+        clob_phoenix::ACCOUNTS_LEN
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![self.header.base_params.mint_key, self.header.quote_params.mint_key]
+    }
+
+    // the book lives in the market account
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        vec![self.key]
+    }
+
+    fn is_active(&self) -> bool {
+        self.header.status == MARKET_STATUS_ACTIVE
+    }
+
+    fn from_keyed_account(keyed_account: &KeyedAccount) -> eyre::Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut phoenix = Phoenix { key: keyed_account.key, ..Phoenix::default() };
+        phoenix.load(&keyed_account.account.data)?;
+
+        Ok(phoenix)
+    }
+
+    fn update(&mut self, account_map: &AccountMap, slot: Option<u64>) -> eyre::Result<()> {
+        let market = account_map.get(&self.key).ok_or_else(|| eyre!("market not found"))?;
+        self.load(&market.data)?;
+        if let Some(slot) = slot {
+            self.slot = slot;
+        }
+
+        Ok(())
+    }
+
+    fn quote(&mut self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        // phoenix only moves classic spl tokens, so there's no transfer fee or hook to account for
+        self.quote_book(quote_params)
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> eyre::Result<SwapAndAccountMetas> {
+        let program_id = Self::program_id();
+
+        let account_metas = vec![
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new(swap_params.token_transfer_authority, true),
+            AccountMeta::new(swap_params.src_ta, false),
+            AccountMeta::new(swap_params.dst_ta, false),
+            AccountMeta::new(self.key, false),
+            AccountMeta::new_readonly(state::log_authority_address(&program_id), false),
+            AccountMeta::new(self.header.base_params.vault_key, false),
+            AccountMeta::new(self.header.quote_params.vault_key, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ];
+
+        Ok(SwapAndAccountMetas { swap: AmmKind::Phoenix, account_metas })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::SwapMode;
+
+    // a SOL/USDC like market, 0.001 SOL base lots, 1e-6 USDC quote lots and 0.001 USDC ticks, 5 bps taker fee
+    fn market() -> Phoenix {
+        let mut header = MarketHeader { status: MARKET_STATUS_ACTIVE, base_lot_size: 1_000_000, quote_lot_size: 1, ..MarketHeader::default() };
+        header.base_params.mint_key = Pubkey::new_unique();
+        header.quote_params.mint_key = Pubkey::new_unique();
+        let market = FifoMarketHeader { base_lots_per_base_unit: 1_000, tick_size_in_quote_lots_per_base_unit: 1_000, taker_fee_bps: 5, ..FifoMarketHeader::default() };
+
+        let order = |price_in_ticks, num_base_lots| Order { price_in_ticks, num_base_lots, ..Order::default() };
+        // bids at 149.9 and 149.8, asks at 150.1 and 150.2, 1 SOL each
+        let bids = vec![order(149_900, 1_000), order(149_800, 1_000)];
+        let asks = vec![order(150_100, 1_000), order(150_200, 1_000)];
+
+        Phoenix { key: Pubkey::new_unique(), header, market, bids, asks, slot: 100 }
+    }
+
+    fn params(market: &Phoenix, is_ask: bool, amount: u64) -> QuoteParams {
+        let (base, quote) = (market.header.base_params.mint_key, market.header.quote_params.mint_key);
+        let (input_mint, output_mint) = if is_ask { (base, quote) } else { (quote, base) };
+        QuoteParams { swap_mode: SwapMode::ExactIn, amount, input_mint, output_mint }
+    }
+
+    #[test]
+    fn sells_into_the_bids() {
+        let mut market = market();
+
+        // 1.5 SOL takes all of the 149.9 bid and half of the 149.8 one, 224.8 USDC less 5 bps
+        let quote = market.quote(&params(&market, true, 1_500_000_000)).unwrap();
+        assert_eq!(quote.fee_amount, 112_400);
+        assert_eq!(quote.out_amount, 224_800_000 - 112_400);
+
+        assert!(market.quote(&params(&market, true, 2_500_000_000)).is_err());
+    }
+
+    #[test]
+    fn buys_from_the_asks() {
+        let mut market = market();
+
+        // 200 USDC leaves 199.9 USDC after the fee, 1 SOL at 150.1 and 0.331 SOL at 150.2
+        let quote = market.quote(&params(&market, false, 200_000_000)).unwrap();
+        assert_eq!(quote.out_amount, 1_331_000_000);
+        assert!(quote.fee_amount > 0);
+
+        // expired orders are skipped
+        market.asks[0].last_valid_slot = 99;
+        let quote = market.quote(&params(&market, false, 200_000_000)).unwrap();
+        assert_eq!(quote.out_amount, 1_000_000_000);
+    }
+
+    #[test]
+    fn parses_an_order_tree() {
+        // a root with a left and a right child and a freed fourth node
+        let mut data = vec![0u8; 32 + 4 * 64];
+        data[0..4].copy_from_slice(&2u32.to_le_bytes());
+        for (index, left, right, price) in [(1u32, 0u32, 0u32, 10u64), (2, 1, 3, 20), (3, 0, 0, 30), (4, 0, 0, 40)] {
+            let node = 32 + (index as usize - 1) * 64;
+            data[node..node + 4].copy_from_slice(&left.to_le_bytes());
+            data[node + 4..node + 8].copy_from_slice(&right.to_le_bytes());
+            data[node + 16..node + 24].copy_from_slice(&price.to_le_bytes());
+            data[node + 40..node + 48].copy_from_slice(&1u64.to_le_bytes());
+        }
+
+        let mut prices: Vec<u64> = state::parse_orders(&data, 0, 4).unwrap().iter().map(|order| order.price_in_ticks).collect();
+        prices.sort();
+        assert_eq!(prices, vec![10, 20, 30]);
+    }
+}
@@ -0,0 +1,137 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use eyre::eyre;
+use solana_sdk::pubkey::Pubkey;
+
+pub const MARKET_HEADER_LEN: usize = 576;
+/// The FIFO market's padding ahead of its parameters.
+const FIFO_MARKET_PADDING_LEN: usize = 256;
+const FIFO_MARKET_HEADER_LEN: usize = 48;
+/// The root and padding of a red-black tree and the size, bump index and free list head of its node allocator.
+const TREE_HEADER_LEN: usize = 32;
+/// The left, right, parent and color registers of a node, a FIFOOrderId key and a FIFORestingOrder value.
+const ORDER_NODE_LEN: usize = 64;
+const SENTINEL: u32 = 0;
+
+const LOG_AUTHORITY_SEED: &[u8] = b"log";
+
+pub const MARKET_STATUS_ACTIVE: u64 = 1;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default)]
+pub struct MarketSizeParams {
+    pub bids_size: u64,
+    pub asks_size: u64,
+    pub num_seats: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default)]
+pub struct TokenParams {
+    pub decimals: u32,
+    pub vault_bump: u32,
+    pub mint_key: Pubkey,
+    pub vault_key: Pubkey,
+}
+
+/// The leading fields of a market's header.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct MarketHeader {
+    pub discriminant: u64,
+    pub status: u64,
+    pub market_size_params: MarketSizeParams,
+    pub base_params: TokenParams,
+    pub base_lot_size: u64,
+    pub quote_params: TokenParams,
+    pub quote_lot_size: u64,
+    pub tick_size_in_quote_atoms_per_base_unit: u64,
+    pub authority: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub market_sequence_number: u64,
+    pub successor: Pubkey,
+    pub raw_base_units_per_base_unit: u32,
+}
+
+/// The parameters of the FIFO market following the header, ahead of its bids, asks and seats.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default)]
+pub struct FifoMarketHeader {
+    pub base_lots_per_base_unit: u64,
+    pub tick_size_in_quote_lots_per_base_unit: u64,
+    pub order_sequence_number: u64,
+    pub taker_fee_bps: u64,
+    pub collected_quote_lot_fees: u64,
+    pub unclaimed_quote_lot_fees: u64,
+}
+
+/// A resting order, a FIFOOrderId and FIFORestingOrder less the trader and sequence number.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Order {
+    pub price_in_ticks: u64,
+    pub num_base_lots: u64,
+    /// Zero if the order doesn't expire.
+    pub last_valid_slot: u64,
+    /// Zero if the order doesn't expire.
+    pub last_valid_unix_timestamp_in_seconds: u64,
+}
+
+impl Order {
+    pub fn is_expired(&self, slot: u64, now: u64) -> bool {
+        (self.last_valid_slot != 0 && self.last_valid_slot < slot) || (self.last_valid_unix_timestamp_in_seconds != 0 && self.last_valid_unix_timestamp_in_seconds < now)
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> eyre::Result<u32> {
+    data.get(offset..offset + 4).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap())).ok_or_else(|| eyre!("market data too short"))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> eyre::Result<u64> {
+    data.get(offset..offset + 8).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap())).ok_or_else(|| eyre!("market data too short"))
+}
+
+/// Reads the orders of the red-black tree at `offset`, walking it from the root so freed nodes are left out.
+pub fn parse_orders(data: &[u8], offset: usize, capacity: usize) -> eyre::Result<Vec<Order>> {
+    let node = |index: u32| offset + TREE_HEADER_LEN + (index as usize - 1) * ORDER_NODE_LEN;
+
+    let mut orders = Vec::new();
+    let mut stack = vec![read_u32(data, offset)?];
+    while let Some(index) = stack.pop() {
+        if index == SENTINEL {
+            continue;
+        }
+        if index as usize > capacity || orders.len() >= capacity {
+            return Err(eyre!("malformed order tree"));
+        }
+
+        let node = node(index);
+        stack.push(read_u32(data, node)?);
+        stack.push(read_u32(data, node + 4)?);
+        orders.push(Order {
+            price_in_ticks: read_u64(data, node + 16)?,
+            num_base_lots: read_u64(data, node + 40)?,
+            last_valid_slot: read_u64(data, node + 48)?,
+            last_valid_unix_timestamp_in_seconds: read_u64(data, node + 56)?,
+        });
+    }
+
+    Ok(orders)
+}
+
+/// Reads a market account into its header, its FIFO market parameters and its bids and asks in matching order.
+pub fn parse_market(data: &[u8]) -> eyre::Result<(MarketHeader, FifoMarketHeader, Vec<Order>, Vec<Order>)> {
+    let header = MarketHeader::deserialize(&mut &data[..])?;
+    let fifo_offset = MARKET_HEADER_LEN + FIFO_MARKET_PADDING_LEN;
+    let fifo = FifoMarketHeader::deserialize(&mut data.get(fifo_offset..).ok_or_else(|| eyre!("market data too short"))?)?;
+
+    let (bids_size, asks_size) = (header.market_size_params.bids_size as usize, header.market_size_params.asks_size as usize);
+    let bids_offset = fifo_offset + FIFO_MARKET_HEADER_LEN;
+    let asks_offset = bids_offset + TREE_HEADER_LEN + bids_size * ORDER_NODE_LEN;
+
+    // the best price first, the time priority of orders at a price doesn't change a quote
+    let mut bids = parse_orders(data, bids_offset, bids_size)?;
+    bids.sort_by_key(|order| std::cmp::Reverse(order.price_in_ticks));
+    let mut asks = parse_orders(data, asks_offset, asks_size)?;
+    asks.sort_by_key(|order| order.price_in_ticks);
+
+    Ok((header, fifo, bids, asks))
+}
+
+pub fn log_authority_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[LOG_AUTHORITY_SEED], program_id).0
+}
@@ -0,0 +1,5 @@
+pub mod integration;
+pub mod state;
+
+pub use integration::RaydiumAmmV4;
+pub use state::{AmmInfo, MarketState};
@@ -0,0 +1,232 @@
+use std::time::SystemTime;
+
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::TokenAccount;
+use borsh::BorshDeserialize;
+use eyre::eyre;
+use magnus_shared::amm_raydium_amm_v4;
+use solana_instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    adapters::{
+        Adapter, AmmKind,
+        amms::{
+            AccountMap, Amm, KeyedAccount, Quote, QuoteParams, SwapAndAccountMetas, SwapParams,
+            raydium_amm_v4::state::{self, AmmInfo, MarketState, STATUS_INITIALIZED, STATUS_SWAP_ONLY, STATUS_WAITING_TRADE},
+        },
+    },
+    curves::{constant_product, fees::Fees},
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct RaydiumAmmV4 {
+    key: Pubkey,
+    pub state: AmmInfo,
+    market: Option<MarketState>,
+    coin_amount: u64,
+    pc_amount: u64,
+    slot: u64,
+}
+
+impl RaydiumAmmV4 {
+    fn program_id() -> Pubkey {
+        Pubkey::from_str_const(&amm_raydium_amm_v4::id().to_string())
+    }
+
+    fn fees(&self) -> Fees {
+        Fees::new(self.state.fees.swap_fee_numerator, self.state.fees.swap_fee_denominator, 0, 0)
+    }
+
+    fn can_swap(&self, now: u64) -> bool {
+        match self.state.status {
+            STATUS_INITIALIZED | STATUS_SWAP_ONLY => true,
+            STATUS_WAITING_TRADE => self.state.state_data.pool_open_time <= now,
+            _ => false,
+        }
+    }
+
+    /// Quotes swap base in, the fee comes off the input ahead of the constant product.
+    fn quote_pool(&self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        let (input_reserve, output_reserve) = if quote_params.input_mint == self.state.coin_vault_mint {
+            (self.coin_amount, self.pc_amount)
+        } else if quote_params.input_mint == self.state.pc_vault_mint {
+            (self.pc_amount, self.coin_amount)
+        } else {
+            return Err(eyre!("mint {} isn't traded by pool {}", quote_params.input_mint, self.key));
+        };
+        if !self.can_swap(SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs()) {
+            return Err(eyre!("pool {} doesn't swap", self.key));
+        }
+        if input_reserve == 0 || output_reserve == 0 {
+            return Err(eyre!("insufficient liquidity"));
+        }
+
+        let fees = self.fees();
+        let fee_amount = fees.trading_fee_ceil(quote_params.amount as u128).ok_or_else(|| eyre!("overflow"))?;
+        let swapped =
+            constant_product::swap(quote_params.amount as u128 - fee_amount, input_reserve as u128, output_reserve as u128).ok_or_else(|| eyre!("insufficient liquidity"))?;
+
+        Ok(Quote {
+            in_amount: quote_params.amount,
+            out_amount: u64::try_from(swapped.destination_amount_swapped)?,
+            fee_amount: fee_amount as u64,
+            fee_mint: quote_params.input_mint,
+            fee_pct: fees.fee_pct().unwrap_or_default(),
+            context_slot: self.slot,
+        })
+    }
+}
+
+impl Adapter for RaydiumAmmV4 {}
+
+impl Amm for RaydiumAmmV4 {
+    fn program_id(&self) -> Pubkey {
+        Self::program_id()
+    }
+
+    fn label(&self) -> String {
+        "RaydiumAmmV4".to_string()
+    }
+
+    fn get_accounts_len(&self) -> usize {
+        amm_raydium_amm_v4::ACCOUNTS_LEN
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![self.state.coin_vault_mint, self.state.pc_vault_mint]
+    }
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        vec![self.key, self.state.coin_vault, self.state.pc_vault, self.state.market]
+    }
+
+    fn is_active(&self) -> bool {
+        matches!(self.state.status, STATUS_INITIALIZED | STATUS_SWAP_ONLY | STATUS_WAITING_TRADE)
+    }
+
+    fn from_keyed_account(keyed_account: &KeyedAccount) -> eyre::Result<Self>
+    where
+        Self: Sized,
+    {
+        let state = AmmInfo::deserialize(&mut &keyed_account.account.data[..])?;
+        Ok(RaydiumAmmV4 { key: keyed_account.key, state, ..RaydiumAmmV4::default() })
+    }
+
+    fn update(&mut self, account_map: &AccountMap, slot: Option<u64>) -> eyre::Result<()> {
+        let amm = account_map.get(&self.key).ok_or_else(|| eyre!("amm not found"))?;
+        self.state = AmmInfo::deserialize(&mut &amm.data[..])?;
+
+        let coin_vault = account_map.get(&self.state.coin_vault).ok_or_else(|| eyre!("coin_vault not found"))?;
+        let pc_vault = account_map.get(&self.state.pc_vault).ok_or_else(|| eyre!("pc_vault not found"))?;
+        let coin_vault = TokenAccount::try_deserialize(&mut &coin_vault.data[..])?;
+        let pc_vault = TokenAccount::try_deserialize(&mut &pc_vault.data[..])?;
+
+        // the orderbook is disabled, the reserves are the vaults less the pnl owed to the protocol
+        self.coin_amount = coin_vault.amount.saturating_sub(self.state.state_data.need_take_pnl_coin);
+        self.pc_amount = pc_vault.amount.saturating_sub(self.state.state_data.need_take_pnl_pc);
+
+        if let Some(market) = account_map.get(&self.state.market) {
+            self.market = Some(MarketState::parse(&market.data)?);
+        }
+        if let Some(slot) = slot {
+            self.slot = slot;
+        }
+
+        Ok(())
+    }
+
+    fn quote(&mut self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        // amm v4 only trades classic spl mints, so there's no transfer fee or hook to account for
+        self.quote_pool(quote_params)
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> eyre::Result<SwapAndAccountMetas> {
+        let market = self.market.as_ref().ok_or_else(|| eyre!("market {} of pool {} not loaded", self.state.market, self.key))?;
+        let program_id = Self::program_id();
+
+        let account_metas = vec![
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new(swap_params.token_transfer_authority, true),
+            AccountMeta::new(swap_params.src_ta, false),
+            AccountMeta::new(swap_params.dst_ta, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(self.key, false),
+            AccountMeta::new_readonly(state::amm_authority_address(&program_id), false),
+            AccountMeta::new(self.state.open_orders, false),
+            AccountMeta::new(self.state.coin_vault, false),
+            AccountMeta::new(self.state.pc_vault, false),
+            AccountMeta::new_readonly(self.state.market_program, false),
+            AccountMeta::new(self.state.market, false),
+            AccountMeta::new(market.bids, false),
+            AccountMeta::new(market.asks, false),
+            AccountMeta::new(market.event_queue, false),
+            AccountMeta::new(market.coin_vault, false),
+            AccountMeta::new(market.pc_vault, false),
+            AccountMeta::new_readonly(market.vault_signer(&self.state.market_program, &self.state.market)?, false),
+        ];
+
+        Ok(SwapAndAccountMetas { swap: AmmKind::RaydiumAmmV4, account_metas })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::SwapMode;
+
+    fn pool() -> RaydiumAmmV4 {
+        let mut state = AmmInfo { status: STATUS_SWAP_ONLY, coin_vault_mint: Pubkey::new_unique(), pc_vault_mint: Pubkey::new_unique(), ..AmmInfo::default() };
+        state.fees.swap_fee_numerator = 25;
+        state.fees.swap_fee_denominator = 10_000;
+
+        RaydiumAmmV4 { key: Pubkey::new_unique(), state, coin_amount: 1_000_000_000, pc_amount: 2_000_000_000, ..RaydiumAmmV4::default() }
+    }
+
+    #[test]
+    fn quotes_the_constant_product_less_the_swap_fee() {
+        let mut pool = pool();
+        let params = QuoteParams { swap_mode: SwapMode::ExactIn, amount: 1_000_001, input_mint: pool.state.coin_vault_mint, output_mint: pool.state.pc_vault_mint };
+
+        let quote = pool.quote(&params).unwrap();
+        // 25 bps rounded up, then 2e9 * 997_500 / (1e9 + 997_500)
+        assert_eq!(quote.fee_amount, 2_501);
+        assert_eq!(quote.out_amount, 1_993_011);
+        assert_eq!(quote.fee_pct, rust_decimal::Decimal::new(25, 4));
+    }
+
+    #[test]
+    fn rejects_pools_that_dont_swap() {
+        let mut pool = pool();
+        let params = QuoteParams { swap_mode: SwapMode::ExactIn, amount: 1_000, input_mint: pool.state.pc_vault_mint, output_mint: pool.state.coin_vault_mint };
+        assert!(pool.quote(&params).is_ok());
+
+        // waiting for its open time
+        pool.state.status = STATUS_WAITING_TRADE;
+        pool.state.state_data.pool_open_time = u64::MAX;
+        assert!(pool.quote(&params).is_err());
+
+        pool.state.status = 4;
+        assert!(pool.quote(&params).is_err());
+    }
+
+    #[test]
+    fn parses_the_pool_and_market_accounts() {
+        // the size of a v4 pool account
+        assert_eq!(borsh::to_vec(&AmmInfo::default()).unwrap().len(), 752);
+
+        let mut data = vec![0u8; 388];
+        data[45..53].copy_from_slice(&7u64.to_le_bytes());
+        let bids = Pubkey::new_unique();
+        data[285..317].copy_from_slice(bids.as_ref());
+
+        let market = MarketState::parse(&data).unwrap();
+        assert_eq!(market.vault_signer_nonce, 7);
+        assert_eq!(market.bids, bids);
+        assert!(MarketState::parse(&data[..300]).is_err());
+    }
+}
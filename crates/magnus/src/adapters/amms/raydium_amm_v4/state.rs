@@ -0,0 +1,123 @@
+use arrayref::array_ref;
+use borsh::{BorshDeserialize, BorshSerialize};
+use eyre::eyre;
+use solana_sdk::pubkey::Pubkey;
+
+const AMM_AUTHORITY_SEED: &[u8] = b"amm authority";
+
+// the statuses a pool swaps in
+pub const STATUS_INITIALIZED: u64 = 1;
+pub const STATUS_SWAP_ONLY: u64 = 6;
+pub const STATUS_WAITING_TRADE: u64 = 7;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default)]
+pub struct Fees {
+    pub min_separate_numerator: u64,
+    pub min_separate_denominator: u64,
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+    pub pnl_numerator: u64,
+    pub pnl_denominator: u64,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default)]
+pub struct StateData {
+    pub need_take_pnl_coin: u64,
+    pub need_take_pnl_pc: u64,
+    pub total_pnl_pc: u64,
+    pub total_pnl_coin: u64,
+    pub pool_open_time: u64,
+    pub padding: [u64; 2],
+    pub orderbook_to_init_time: u64,
+    pub swap_coin_in_amount: u128,
+    pub swap_pc_out_amount: u128,
+    pub swap_acc_pc_fee: u64,
+    pub swap_pc_in_amount: u128,
+    pub swap_coin_out_amount: u128,
+    pub swap_acc_coin_fee: u64,
+}
+
+/// A v4 pool, a plain (discriminator-less) account.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct AmmInfo {
+    pub status: u64,
+    pub nonce: u64,
+    pub order_num: u64,
+    pub depth: u64,
+    pub coin_decimals: u64,
+    pub pc_decimals: u64,
+    pub state: u64,
+    pub reset_flag: u64,
+    pub min_size: u64,
+    pub vol_max_cut_ratio: u64,
+    pub amount_wave: u64,
+    pub coin_lot_size: u64,
+    pub pc_lot_size: u64,
+    pub min_price_multiplier: u64,
+    pub max_price_multiplier: u64,
+    pub sys_decimal_value: u64,
+    pub fees: Fees,
+    pub state_data: StateData,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub coin_vault_mint: Pubkey,
+    pub pc_vault_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+    pub market_program: Pubkey,
+    pub target_orders: Pubkey,
+    pub padding1: [u64; 8],
+    pub amm_owner: Pubkey,
+    pub lp_amount: u64,
+    pub client_order_id: u64,
+    pub recent_epoch: u64,
+    pub padding2: u64,
+}
+
+/// The accounts of the OpenBook market a pool is paired with, which the swap instruction still takes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MarketState {
+    pub vault_signer_nonce: u64,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub event_queue: Pubkey,
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+}
+
+impl MarketState {
+    const ASKS_OFFSET: usize = 317;
+    const BIDS_OFFSET: usize = 285;
+    const COIN_VAULT_OFFSET: usize = 117;
+    const EVENT_QUEUE_OFFSET: usize = 253;
+    const PC_VAULT_OFFSET: usize = 165;
+    // the offsets past the 5 bytes of "serum" padding
+    const VAULT_SIGNER_NONCE_OFFSET: usize = 45;
+
+    pub fn parse(data: &[u8]) -> eyre::Result<Self> {
+        if data.len() < Self::ASKS_OFFSET + 32 {
+            return Err(eyre!("market data too short"));
+        }
+
+        let pubkey = |offset| Pubkey::new_from_array(*array_ref![data, offset, 32]);
+        Ok(MarketState {
+            vault_signer_nonce: u64::from_le_bytes(*array_ref![data, Self::VAULT_SIGNER_NONCE_OFFSET, 8]),
+            coin_vault: pubkey(Self::COIN_VAULT_OFFSET),
+            pc_vault: pubkey(Self::PC_VAULT_OFFSET),
+            event_queue: pubkey(Self::EVENT_QUEUE_OFFSET),
+            bids: pubkey(Self::BIDS_OFFSET),
+            asks: pubkey(Self::ASKS_OFFSET),
+        })
+    }
+
+    pub fn vault_signer(&self, market_program: &Pubkey, market: &Pubkey) -> eyre::Result<Pubkey> {
+        Pubkey::create_program_address(&[market.as_ref(), &self.vault_signer_nonce.to_le_bytes()], market_program).map_err(|e| eyre!("invalid vault signer nonce: {}", e))
+    }
+}
+
+pub fn amm_authority_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[AMM_AUTHORITY_SEED], program_id).0
+}
@@ -1,5 +1,5 @@
 use anchor_lang::AccountDeserialize;
-use anchor_spl::token::TokenAccount;
+use anchor_spl::token_interface::TokenAccount;
 use borsh::BorshDeserialize;
 use magnus_shared::amm_raydium_cp;
 use solana_instruction::AccountMeta;
@@ -8,6 +8,7 @@ use solana_sdk::pubkey::Pubkey;
 use crate::adapters::{
     Adapter, AmmKind,
     amms::{AccountMap, Amm, KeyedAccount, Quote, QuoteParams, SwapAndAccountMetas, SwapParams, raydium_cp},
+    token_meta::TokenMetas,
 };
 
 #[derive(Clone, Debug, Default)]
@@ -16,12 +17,44 @@ pub struct RaydiumCP {
     pub state: raydium_cp::state::State,
     current_x: u64,
     current_y: u64,
+    slot: u64,
+    token_metas: TokenMetas,
 }
 
 impl RaydiumCP {
     pub fn new() -> RaydiumCP {
         RaydiumCP::default()
     }
+
+    /// Quotes the pool itself, i.e. the amount that reaches and leaves the vaults.
+    fn quote_pool(&self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        let (input_reserve, output_reserve) = if quote_params.input_mint == self.state.token_0_mint {
+            (self.current_x, self.current_y)
+        } else {
+            (self.current_y, self.current_x)
+        };
+
+        if input_reserve == 0 || output_reserve == 0 {
+            return Err(eyre::eyre!("insufficient liquidity"));
+        }
+
+        let fee_bps = 30u64;
+        let amount_in_with_fee = quote_params.amount.checked_mul(10000 - fee_bps).ok_or_else(|| eyre::eyre!("overflow"))?;
+        let numerator = amount_in_with_fee.checked_mul(output_reserve).ok_or_else(|| eyre::eyre!("overflow"))?;
+        let denominator = input_reserve.checked_mul(10000).ok_or_else(|| eyre::eyre!("overflow"))?.checked_add(amount_in_with_fee).ok_or_else(|| eyre::eyre!("overflow"))?;
+
+        let out_amount = numerator / denominator;
+        let fee_amount = quote_params.amount * fee_bps / 10000;
+
+        Ok(Quote {
+            in_amount: quote_params.amount,
+            out_amount,
+            fee_amount,
+            fee_mint: quote_params.input_mint,
+            fee_pct: rust_decimal::Decimal::new(fee_bps as i64, 4),
+            context_slot: self.slot,
+        })
+    }
 }
 
 impl Adapter for RaydiumCP {}
@@ -48,7 +81,7 @@ impl Amm for RaydiumCP {
     }
 
     fn get_accounts_to_update(&self) -> Vec<Pubkey> {
-        vec![self.state.token_0_vault, self.state.token_1_vault]
+        vec![self.state.token_0_vault, self.state.token_1_vault, self.state.token_0_mint, self.state.token_1_mint]
     }
 
     //fn clone_amm(&self) -> Box<dyn Amm + Send + Sync> {
@@ -61,10 +94,10 @@ impl Amm for RaydiumCP {
     {
         let data = &mut &keyed_account.account.data.clone()[8..];
         let state = raydium_cp::state::State::deserialize(data)?;
-        Ok(RaydiumCP { key: keyed_account.key, state, current_x: 0, current_y: 0 })
+        Ok(RaydiumCP { key: keyed_account.key, state, current_x: 0, current_y: 0, slot: 0, token_metas: TokenMetas::default() })
     }
 
-    fn update(&mut self, account_map: &AccountMap, _: Option<u64>) -> eyre::Result<()> {
+    fn update(&mut self, account_map: &AccountMap, slot: Option<u64>) -> eyre::Result<()> {
         let vault_0 = account_map.get(&self.state.token_0_vault).ok_or_else(|| eyre::eyre!("token_0_vault not found"))?;
         let vault_1 = account_map.get(&self.state.token_1_vault).ok_or_else(|| eyre::eyre!("token_1_vault not found"))?;
 
@@ -73,30 +106,16 @@ impl Amm for RaydiumCP {
 
         self.current_x = vault_0_data.amount;
         self.current_y = vault_1_data.amount;
+        self.token_metas.update(account_map, &[self.state.token_0_mint, self.state.token_1_mint])?;
+        if let Some(slot) = slot {
+            self.slot = slot;
+        }
 
         Ok(())
     }
 
     fn quote(&mut self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
-        let (input_reserve, output_reserve) = if quote_params.input_mint == self.state.token_0_mint {
-            (self.current_x, self.current_y)
-        } else {
-            (self.current_y, self.current_x)
-        };
-
-        if input_reserve == 0 || output_reserve == 0 {
-            return Err(eyre::eyre!("insufficient liquidity"));
-        }
-
-        let fee_bps = 30u64;
-        let amount_in_with_fee = quote_params.amount.checked_mul(10000 - fee_bps).ok_or_else(|| eyre::eyre!("overflow"))?;
-        let numerator = amount_in_with_fee.checked_mul(output_reserve).ok_or_else(|| eyre::eyre!("overflow"))?;
-        let denominator = input_reserve.checked_mul(10000).ok_or_else(|| eyre::eyre!("overflow"))?.checked_add(amount_in_with_fee).ok_or_else(|| eyre::eyre!("overflow"))?;
-
-        let out_amount = numerator / denominator;
-        let fee_amount = quote_params.amount * fee_bps / 10000;
-
-        Ok(Quote { in_amount: quote_params.amount, out_amount, fee_amount, fee_mint: quote_params.input_mint, fee_pct: rust_decimal::Decimal::new(fee_bps as i64, 4) })
+        self.token_metas.quote(quote_params, self.slot, |quote_params| self.quote_pool(quote_params))
     }
 
     // https://solscan.io/tx/rUwLuvAuE5vKH48c3n7ZUbuUudPqdKsdcBy58gMUopYDg9yC5FbB1feg3xrEuvemBWwCbSjkmAVxqCCLthpBG1h
@@ -0,0 +1,180 @@
+//! Builds markets out of their on-chain accounts, picking the adapter by the account's owner
+
+use std::collections::HashMap;
+
+use eyre::eyre;
+use magnus_shared::{amm_meteora_dlmm, amm_raydium_amm_v4, amm_raydium_cp, amm_spl_token_swap, amm_whirlpool, clob_phoenix, lst_spl_stake_pool, pmm_humidifi, pmm_obric_v2};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use tracing::warn;
+
+use crate::{
+    AccountMap,
+    adapters::amms::{
+        Amm, KeyedAccount, humidifi::Humidifi, meteora_dlmm::MeteoraDlmm, obric_v2::ObricV2, phoenix::Phoenix, raydium_amm_v4::RaydiumAmmV4, raydium_cp::RaydiumCP,
+        spl_stake_pool::SplStakePool, spl_token_swap::SplTokenSwap, whirlpool::Whirlpool,
+    },
+};
+
+/// The most accounts `getMultipleAccounts` returns in a single request.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Constructs a market from its keyed account.
+pub type AmmFactory = fn(&KeyedAccount) -> eyre::Result<Box<dyn Amm>>;
+
+fn factory<T: Amm + 'static>(keyed_account: &KeyedAccount) -> eyre::Result<Box<dyn Amm>> {
+    Ok(Box::new(T::from_keyed_account(keyed_account)?))
+}
+
+/// Maps the program ids owning market accounts to the adapters able to make sense of them.
+pub struct AmmRegistry {
+    factories: HashMap<Pubkey, AmmFactory>,
+}
+
+impl Default for AmmRegistry {
+    /// All the venues with an `Amm` implementation.
+    fn default() -> Self {
+        let mut registry = AmmRegistry::empty();
+        registry.register_amm::<RaydiumCP>(amm_raydium_cp::id().to_bytes());
+        registry.register_amm::<RaydiumAmmV4>(amm_raydium_amm_v4::id().to_bytes());
+        registry.register_amm::<Whirlpool>(amm_whirlpool::id().to_bytes());
+        registry.register_amm::<MeteoraDlmm>(amm_meteora_dlmm::id().to_bytes());
+        registry.register_amm::<SplTokenSwap>(amm_spl_token_swap::id().to_bytes());
+        registry.register_amm::<ObricV2>(pmm_obric_v2::id().to_bytes());
+        registry.register_amm::<Humidifi>(pmm_humidifi::id().to_bytes());
+        registry.register_amm::<Phoenix>(clob_phoenix::id().to_bytes());
+        registry.register_amm::<SplStakePool>(lst_spl_stake_pool::id().to_bytes());
+
+        registry
+    }
+}
+
+impl AmmRegistry {
+    pub fn empty() -> Self {
+        AmmRegistry { factories: HashMap::new() }
+    }
+
+    /// Registers the factory for the accounts owned by `program_id`, replacing any previous one.
+    pub fn register(&mut self, program_id: Pubkey, factory: AmmFactory) {
+        self.factories.insert(program_id, factory);
+    }
+
+    /// Registers `T::from_keyed_account` for the accounts owned by `program_id`.
+    pub fn register_amm<T: Amm + 'static>(&mut self, program_id: impl Into<Pubkey>) {
+        self.register(program_id.into(), factory::<T>);
+    }
+
+    pub fn supports(&self, program_id: &Pubkey) -> bool {
+        self.factories.contains_key(program_id)
+    }
+
+    pub fn program_ids(&self) -> impl Iterator<Item = &Pubkey> {
+        self.factories.keys()
+    }
+
+    /// Constructs the market with the factory registered for the account's owner.
+    pub fn from_keyed_account(&self, keyed_account: &KeyedAccount) -> eyre::Result<Box<dyn Amm>> {
+        let owner = keyed_account.account.owner;
+        let factory = self.factories.get(&owner).ok_or_else(|| eyre!("no adapter for {} owned by {}", keyed_account.key, owner))?;
+
+        factory(keyed_account)
+    }
+
+    /// Fetches the market accounts, constructs their markets and primes them with their accounts to update.
+    ///
+    /// The params are handed over to the factories as is, for the venues that can't tell everything from the market account.
+    pub fn load(&self, client: &RpcClient, markets: &[(Pubkey, Option<serde_json::Value>)]) -> eyre::Result<Vec<Box<dyn Amm>>> {
+        self.load_with_account_map(client, markets).map(|(amms, _)| amms)
+    }
+
+    /// Same as [`AmmRegistry::load`], also handing back the accounts the markets were primed with.
+    pub fn load_with_account_map(&self, client: &RpcClient, markets: &[(Pubkey, Option<serde_json::Value>)]) -> eyre::Result<(Vec<Box<dyn Amm>>, AccountMap)> {
+        let keys: Vec<Pubkey> = markets.iter().map(|(key, _)| *key).collect();
+        let accounts = get_multiple_accounts(client, &keys)?;
+
+        let mut amms = markets
+            .iter()
+            .map(|(key, params)| {
+                let account = accounts.get(key).ok_or_else(|| eyre!("market {} not found", key))?;
+                self.from_keyed_account(&KeyedAccount { key: *key, account: account.clone(), params: params.clone() })
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        // one round of updates, the dynamic accounts (i.e. tick arrays) a market moves onto later are fetched by the ingest
+        let keys: Vec<Pubkey> = amms.iter().flat_map(|amm| amm.get_accounts_to_update()).collect();
+        let account_map = get_multiple_accounts(client, &keys)?;
+        for amm in amms.iter_mut() {
+            let accounts: AccountMap = amm.get_accounts_to_update().into_iter().filter_map(|key| account_map.get(&key).map(|account| (key, account.clone()))).collect();
+            if let Err(e) = amm.update(&accounts, None) {
+                warn!("unable to prime market {}: {}", amm.key(), e);
+            }
+        }
+
+        Ok((amms, account_map))
+    }
+}
+
+/// `getMultipleAccounts` in chunks the rpc accepts, leaving out the accounts that don't exist.
+pub fn get_multiple_accounts(client: &RpcClient, keys: &[Pubkey]) -> eyre::Result<AccountMap> {
+    let mut account_map = AccountMap::default();
+    for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = client.get_multiple_accounts(chunk)?;
+        account_map.extend(chunk.iter().zip(accounts).filter_map(|(key, account)| account.map(|account| (*key, account))));
+    }
+
+    Ok(account_map)
+}
+
+/// Same as [`get_multiple_accounts`], over the nonblocking client.
+pub async fn get_multiple_accounts_live(client: &solana_client::nonblocking::rpc_client::RpcClient, keys: &[Pubkey]) -> eyre::Result<AccountMap> {
+    let mut account_map = AccountMap::default();
+    for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = client.get_multiple_accounts(chunk).await?;
+        account_map.extend(chunk.iter().zip(accounts).filter_map(|(key, account)| account.map(|account| (*key, account))));
+    }
+
+    Ok(account_map)
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use solana_sdk::account::Account;
+
+    use super::*;
+    use crate::adapters::amms::raydium_amm_v4::AmmInfo;
+
+    fn keyed_account(owner: Pubkey, data: Vec<u8>) -> KeyedAccount {
+        KeyedAccount { key: Pubkey::new_unique(), account: Account { data, owner, ..Account::default() }, params: None }
+    }
+
+    #[test]
+    fn picks_the_factory_by_owner() {
+        let registry = AmmRegistry::default();
+        let program_id = Pubkey::new_from_array(amm_raydium_amm_v4::id().to_bytes());
+        assert!(registry.supports(&program_id));
+
+        let mut data = vec![];
+        AmmInfo::default().serialize(&mut data).unwrap();
+        let keyed_account = keyed_account(program_id, data);
+
+        let amm = registry.from_keyed_account(&keyed_account).unwrap();
+        assert_eq!(amm.key(), keyed_account.key);
+        assert_eq!(amm.program_id(), program_id);
+        assert_eq!(amm.label(), "RaydiumAmmV4");
+    }
+
+    #[test]
+    fn rejects_unknown_owners() {
+        let mut registry = AmmRegistry::empty();
+        let program_id = Pubkey::new_unique();
+        let keyed_account = keyed_account(program_id, vec![]);
+        assert!(registry.from_keyed_account(&keyed_account).is_err());
+
+        // registering the owner is all it takes
+        registry.register(program_id, |keyed_account| Ok(Box::new(SplStakePool::from_keyed_account(keyed_account)?)));
+        assert!(registry.supports(&program_id));
+        // the factory runs, and fails on the empty account
+        assert!(registry.from_keyed_account(&keyed_account).is_err());
+    }
+}
@@ -0,0 +1,5 @@
+pub mod integration;
+pub mod state;
+
+pub use integration::SplStakePool;
+pub use state::{Fee, StakePool};
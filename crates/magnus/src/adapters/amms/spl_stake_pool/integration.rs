@@ -0,0 +1,259 @@
+use borsh::BorshDeserialize;
+use eyre::eyre;
+use magnus_shared::lst_spl_stake_pool;
+use solana_instruction::AccountMeta;
+use solana_sdk::{pubkey::Pubkey, rent::Rent};
+
+use crate::adapters::{
+    Adapter, AmmKind,
+    amms::{
+        AccountMap, Amm, KeyedAccount, Quote, QuoteParams, SwapAndAccountMetas, SwapParams,
+        spl_stake_pool::state::{self, ACCOUNT_TYPE_STAKE_POOL, StakePool},
+    },
+    token_meta::TokenMetas,
+};
+
+const STAKE_PROGRAM_ID: Pubkey = Pubkey::from_str_const("Stake11111111111111111111111111111111111111");
+const CLOCK_SYSVAR_ID: Pubkey = Pubkey::from_str_const("SysvarC1ock11111111111111111111111111111111");
+const STAKE_HISTORY_SYSVAR_ID: Pubkey = Pubkey::from_str_const("SysvarStakeHistory1111111111111111111111111");
+
+/// The size of a stake account, the reserve keeps its rent exemption.
+const STAKE_ACCOUNT_LEN: usize = 200;
+/// The offset of the epoch in the clock sysvar, past the slot and the epoch start timestamp.
+const CLOCK_EPOCH_OFFSET: usize = 16;
+
+/// A stake pool as a SOL <-> pool token venue, depositing and withdrawing SOL through its reserve.
+#[derive(Clone, Debug, Default)]
+pub struct SplStakePool {
+    key: Pubkey,
+    pub state: StakePool,
+    reserve_lamports: u64,
+    epoch: Option<u64>,
+    slot: u64,
+    token_metas: TokenMetas,
+}
+
+impl SplStakePool {
+    fn program_id() -> Pubkey {
+        Pubkey::from_str_const(&lst_spl_stake_pool::id().to_string())
+    }
+
+    fn native_mint() -> Pubkey {
+        spl_token::native_mint::id()
+    }
+
+    fn quote_pool(&self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        // the pool only takes deposits and withdrawals once it's been updated for the epoch
+        if self.epoch.is_some_and(|epoch| self.state.last_update_epoch < epoch) {
+            return Err(eyre!("stake pool {} isn't updated for the epoch", self.key));
+        }
+        let overflow = || eyre!("overflow");
+
+        if quote_params.input_mint == Self::native_mint() && quote_params.output_mint == self.state.pool_mint {
+            if self.state.sol_deposit_authority.is_some() {
+                return Err(eyre!("stake pool {} restricts SOL deposits", self.key));
+            }
+
+            let pool_tokens = self.state.calc_pool_tokens_for_deposit(quote_params.amount).ok_or_else(overflow)?;
+            let fee = self.state.sol_deposit_fee.apply(pool_tokens).ok_or_else(overflow)? as u64;
+            if pool_tokens == fee {
+                return Err(eyre!("deposit too small"));
+            }
+
+            Ok(Quote {
+                in_amount: quote_params.amount,
+                out_amount: pool_tokens - fee,
+                fee_amount: fee,
+                fee_mint: self.state.pool_mint,
+                fee_pct: fee_pct(&self.state.sol_deposit_fee),
+                context_slot: self.slot,
+            })
+        } else if quote_params.input_mint == self.state.pool_mint && quote_params.output_mint == Self::native_mint() {
+            if self.state.sol_withdraw_authority.is_some() {
+                return Err(eyre!("stake pool {} restricts SOL withdrawals", self.key));
+            }
+
+            let fee = self.state.sol_withdrawal_fee.apply(quote_params.amount).ok_or_else(overflow)? as u64;
+            let lamports = self.state.calc_lamports_withdraw_amount(quote_params.amount - fee).ok_or_else(overflow)?;
+            if lamports == 0 {
+                return Err(eyre!("withdrawal too small"));
+            }
+            // the reserve pays out the lamports above its rent exemption
+            if lamports > self.reserve_lamports.saturating_sub(Rent::default().minimum_balance(STAKE_ACCOUNT_LEN)) {
+                return Err(eyre!("withdrawal exceeds the reserve of stake pool {}", self.key));
+            }
+
+            Ok(Quote {
+                in_amount: quote_params.amount,
+                out_amount: lamports,
+                fee_amount: fee,
+                fee_mint: self.state.pool_mint,
+                fee_pct: fee_pct(&self.state.sol_withdrawal_fee),
+                context_slot: self.slot,
+            })
+        } else {
+            Err(eyre!("stake pool {} doesn't convert {} to {}", self.key, quote_params.input_mint, quote_params.output_mint))
+        }
+    }
+}
+
+fn fee_pct(fee: &state::Fee) -> rust_decimal::Decimal {
+    if fee.denominator == 0 {
+        return rust_decimal::Decimal::ZERO;
+    }
+    rust_decimal::Decimal::from(fee.numerator) / rust_decimal::Decimal::from(fee.denominator)
+}
+
+impl Adapter for SplStakePool {}
+
+impl Amm for SplStakePool {
+    fn program_id(&self) -> Pubkey {
+        Self::program_id()
+    }
+
+    fn label(&self) -> String {
+        "SplStakePool".to_string()
+    }
+
+    fn get_accounts_len(&self) -> usize {
+        lst_spl_stake_pool::ACCOUNTS_LEN
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![Self::native_mint(), self.state.pool_mint]
+    }
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        vec![self.key, self.state.reserve_stake, CLOCK_SYSVAR_ID, self.state.pool_mint]
+    }
+
+    fn from_keyed_account(keyed_account: &KeyedAccount) -> eyre::Result<Self>
+    where
+        Self: Sized,
+    {
+        let state = StakePool::deserialize(&mut &keyed_account.account.data[..])?;
+        if state.account_type != ACCOUNT_TYPE_STAKE_POOL {
+            return Err(eyre!("{} isn't a stake pool", keyed_account.key));
+        }
+
+        Ok(SplStakePool { key: keyed_account.key, state, ..SplStakePool::default() })
+    }
+
+    fn update(&mut self, account_map: &AccountMap, slot: Option<u64>) -> eyre::Result<()> {
+        let stake_pool = account_map.get(&self.key).ok_or_else(|| eyre!("stake pool not found"))?;
+        self.state = StakePool::deserialize(&mut &stake_pool.data[..])?;
+
+        let reserve_stake = account_map.get(&self.state.reserve_stake).ok_or_else(|| eyre!("reserve stake not found"))?;
+        self.reserve_lamports = reserve_stake.lamports;
+
+        // without the clock the pool is taken to be up to date
+        self.epoch = account_map
+            .get(&CLOCK_SYSVAR_ID)
+            .and_then(|clock| clock.data.get(CLOCK_EPOCH_OFFSET..CLOCK_EPOCH_OFFSET + 8))
+            .map(|epoch| u64::from_le_bytes(epoch.try_into().unwrap()));
+        // the pool mint may be a token-2022 one
+        self.token_metas.update(account_map, &[self.state.pool_mint])?;
+        if let Some(slot) = slot {
+            self.slot = slot;
+        }
+
+        Ok(())
+    }
+
+    fn quote(&mut self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        self.token_metas.quote(quote_params, self.slot, |quote_params| self.quote_pool(quote_params))
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> eyre::Result<SwapAndAccountMetas> {
+        let program_id = Self::program_id();
+        let withdraw_authority =
+            state::withdraw_authority_address(&program_id, &self.key, self.state.stake_withdraw_bump_seed).ok_or_else(|| eyre!("invalid withdraw bump seed"))?;
+        let unwrap_account = Pubkey::create_with_seed(&swap_params.token_transfer_authority, lst_spl_stake_pool::UNWRAP_SEED, &self.state.token_program_id)?;
+
+        let account_metas = vec![
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new(swap_params.token_transfer_authority, true),
+            AccountMeta::new(swap_params.src_ta, false),
+            AccountMeta::new(swap_params.dst_ta, false),
+            AccountMeta::new(self.key, false),
+            AccountMeta::new_readonly(withdraw_authority, false),
+            AccountMeta::new(self.state.reserve_stake, false),
+            AccountMeta::new(self.state.manager_fee_account, false),
+            AccountMeta::new(self.state.pool_mint, false),
+            AccountMeta::new_readonly(self.state.token_program_id, false),
+            AccountMeta::new_readonly(Pubkey::default(), false),
+            AccountMeta::new_readonly(CLOCK_SYSVAR_ID, false),
+            AccountMeta::new_readonly(STAKE_HISTORY_SYSVAR_ID, false),
+            AccountMeta::new_readonly(STAKE_PROGRAM_ID, false),
+            AccountMeta::new(unwrap_account, false),
+            AccountMeta::new_readonly(Self::native_mint(), false),
+        ];
+
+        Ok(SwapAndAccountMetas { swap: AmmKind::SplStakePool, account_metas })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{SwapMode, amms::spl_stake_pool::state::Fee};
+
+    // 1.05 SOL a pool token, with a 0.1% SOL deposit fee and a 0.3% SOL withdrawal fee
+    fn stake_pool() -> SplStakePool {
+        let state = StakePool {
+            account_type: ACCOUNT_TYPE_STAKE_POOL,
+            pool_mint: Pubkey::new_unique(),
+            total_lamports: 1_050_000_000_000,
+            pool_token_supply: 1_000_000_000_000,
+            last_update_epoch: 700,
+            sol_deposit_fee: Fee { denominator: 1_000, numerator: 1 },
+            sol_withdrawal_fee: Fee { denominator: 1_000, numerator: 3 },
+            ..StakePool::default()
+        };
+
+        SplStakePool { key: Pubkey::new_unique(), state, reserve_lamports: 100_000_000_000, epoch: Some(700), slot: 0, token_metas: TokenMetas::default() }
+    }
+
+    fn params(stake_pool: &SplStakePool, deposit: bool, amount: u64) -> QuoteParams {
+        let (input_mint, output_mint) = if deposit {
+            (SplStakePool::native_mint(), stake_pool.state.pool_mint)
+        } else {
+            (stake_pool.state.pool_mint, SplStakePool::native_mint())
+        };
+        QuoteParams { swap_mode: SwapMode::ExactIn, amount, input_mint, output_mint }
+    }
+
+    #[test]
+    fn deposits_and_withdraws_at_the_pool_rate() {
+        let mut stake_pool = stake_pool();
+
+        // 1.05 SOL mints 1 pool token, less the 0.1% fee
+        let deposit = stake_pool.quote(&params(&stake_pool, true, 1_050_000_000)).unwrap();
+        assert_eq!(deposit.fee_amount, 1_000_000);
+        assert_eq!(deposit.out_amount, 999_000_000);
+
+        // 1 pool token less the 0.3% fee burns into 1.05 * 0.997 SOL
+        let withdrawal = stake_pool.quote(&params(&stake_pool, false, 1_000_000_000)).unwrap();
+        assert_eq!(withdrawal.fee_amount, 3_000_000);
+        assert_eq!(withdrawal.out_amount, 1_046_850_000);
+    }
+
+    #[test]
+    fn rejects_what_the_pool_would() {
+        let mut stake_pool = stake_pool();
+
+        // more than the reserve holds
+        assert!(stake_pool.quote(&params(&stake_pool, false, 200_000_000_000)).is_err());
+
+        stake_pool.state.sol_deposit_authority = Some(Pubkey::new_unique());
+        assert!(stake_pool.quote(&params(&stake_pool, true, 1_000_000_000)).is_err());
+
+        // not updated for the current epoch
+        stake_pool.epoch = Some(701);
+        assert!(stake_pool.quote(&params(&stake_pool, false, 1_000_000_000)).is_err());
+    }
+}
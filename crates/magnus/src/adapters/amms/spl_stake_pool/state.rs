@@ -0,0 +1,95 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::pubkey::Pubkey;
+
+const WITHDRAW_AUTHORITY_SEED: &[u8] = b"withdraw";
+
+pub const ACCOUNT_TYPE_STAKE_POOL: u8 = 1;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Fee {
+    pub denominator: u64,
+    pub numerator: u64,
+}
+
+impl Fee {
+    /// The fee on `amount`, rounded down.
+    pub fn apply(&self, amount: u64) -> Option<u128> {
+        if self.denominator == 0 {
+            return Some(0);
+        }
+        (amount as u128).checked_mul(self.numerator as u128)?.checked_div(self.denominator as u128)
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default)]
+pub enum FutureEpoch<T> {
+    #[default]
+    None,
+    One(T),
+    Two(T),
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default)]
+pub struct Lockup {
+    pub unix_timestamp: i64,
+    pub epoch: u64,
+    pub custodian: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
+pub struct StakePool {
+    pub account_type: u8,
+    pub manager: Pubkey,
+    pub staker: Pubkey,
+    pub stake_deposit_authority: Pubkey,
+    pub stake_withdraw_bump_seed: u8,
+    pub validator_list: Pubkey,
+    pub reserve_stake: Pubkey,
+    pub pool_mint: Pubkey,
+    pub manager_fee_account: Pubkey,
+    pub token_program_id: Pubkey,
+    pub total_lamports: u64,
+    pub pool_token_supply: u64,
+    pub last_update_epoch: u64,
+    pub lockup: Lockup,
+    pub epoch_fee: Fee,
+    pub next_epoch_fee: FutureEpoch<Fee>,
+    pub preferred_deposit_validator_vote_address: Option<Pubkey>,
+    pub preferred_withdraw_validator_vote_address: Option<Pubkey>,
+    pub stake_deposit_fee: Fee,
+    pub stake_withdrawal_fee: Fee,
+    pub next_stake_withdrawal_fee: FutureEpoch<Fee>,
+    pub stake_referral_fee: u8,
+    pub sol_deposit_authority: Option<Pubkey>,
+    pub sol_deposit_fee: Fee,
+    pub sol_referral_fee: u8,
+    pub sol_withdraw_authority: Option<Pubkey>,
+    pub sol_withdrawal_fee: Fee,
+    pub next_sol_withdrawal_fee: FutureEpoch<Fee>,
+    pub last_epoch_pool_token_supply: u64,
+    pub last_epoch_total_lamports: u64,
+}
+
+impl StakePool {
+    /// The pool tokens minted for `lamports`, ahead of the deposit fee.
+    pub fn calc_pool_tokens_for_deposit(&self, lamports: u64) -> Option<u64> {
+        if self.total_lamports == 0 || self.pool_token_supply == 0 {
+            return Some(lamports);
+        }
+        u64::try_from((lamports as u128).checked_mul(self.pool_token_supply as u128)?.checked_div(self.total_lamports as u128)?).ok()
+    }
+
+    /// The lamports the burnt `pool_tokens` are worth.
+    pub fn calc_lamports_withdraw_amount(&self, pool_tokens: u64) -> Option<u64> {
+        let numerator = (pool_tokens as u128).checked_mul(self.total_lamports as u128)?;
+        let denominator = self.pool_token_supply as u128;
+        if numerator < denominator || denominator == 0 {
+            return Some(0);
+        }
+        u64::try_from(numerator.checked_div(denominator)?).ok()
+    }
+}
+
+pub fn withdraw_authority_address(program_id: &Pubkey, stake_pool: &Pubkey, bump: u8) -> Option<Pubkey> {
+    Pubkey::create_program_address(&[stake_pool.as_ref(), WITHDRAW_AUTHORITY_SEED, &[bump]], program_id).ok()
+}
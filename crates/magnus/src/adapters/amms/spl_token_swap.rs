@@ -0,0 +1,3 @@
+pub mod integration;
+
+pub use integration::SplTokenSwap;
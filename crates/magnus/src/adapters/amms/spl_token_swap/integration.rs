@@ -0,0 +1,273 @@
+use std::sync::Arc;
+
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::TokenAccount;
+use eyre::eyre;
+use magnus_shared::amm_spl_token_swap;
+use solana_instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    adapters::{
+        Adapter, AmmKind,
+        amms::{
+            AccountMap, Amm, KeyedAccount, Quote, QuoteParams, SwapAndAccountMetas, SwapParams,
+            swap_state::{SwapState, SwapVersion},
+        },
+        helpers::{TokenSwap, get_swap_curve_result},
+        token_meta::TokenMetas,
+    },
+    curves::calculator::TradeDirection,
+};
+
+/// A pool of the SPL token-swap program, quoted with the pool's own curve and fees.
+#[derive(Clone, Debug)]
+pub struct SplTokenSwap {
+    key: Pubkey,
+    pub state: Arc<dyn SwapState>,
+    token_a_amount: u64,
+    token_b_amount: u64,
+    slot: u64,
+    token_metas: TokenMetas,
+}
+
+impl SplTokenSwap {
+    fn program_id() -> Pubkey {
+        Pubkey::from_str_const(&amm_spl_token_swap::id().to_string())
+    }
+
+    /// The program address owning the pool's token accounts and pool mint.
+    fn authority(&self) -> eyre::Result<Pubkey> {
+        Pubkey::create_program_address(&[self.key.as_ref(), &[self.state.bump_seed()]], &Self::program_id()).map_err(|_| eyre!("invalid bump seed for swap {}", self.key))
+    }
+
+    fn quote_pool(&self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        let (trade_direction, swap_source_amount, swap_destination_amount) = if quote_params.input_mint == *self.state.token_a_mint() {
+            (TradeDirection::AtoB, self.token_a_amount, self.token_b_amount)
+        } else if quote_params.input_mint == *self.state.token_b_mint() {
+            (TradeDirection::BtoA, self.token_b_amount, self.token_a_amount)
+        } else {
+            return Err(eyre!("mint {} isn't traded by swap {}", quote_params.input_mint, self.key));
+        };
+        if swap_source_amount == 0 || swap_destination_amount == 0 {
+            return Err(eyre!("insufficient liquidity"));
+        }
+
+        let result =
+            get_swap_curve_result(self.state.swap_curve(), quote_params.amount, swap_source_amount as u128, swap_destination_amount as u128, trade_direction, self.state.fees())?;
+
+        Ok(Quote {
+            in_amount: u64::try_from(result.input_amount)?,
+            out_amount: u64::try_from(result.expected_output_amount)?,
+            fee_amount: u64::try_from(result.fees)?,
+            fee_mint: quote_params.input_mint,
+            fee_pct: result.fee_pct,
+            context_slot: self.slot,
+        })
+    }
+}
+
+impl Adapter for SplTokenSwap {}
+
+impl Amm for SplTokenSwap {
+    fn program_id(&self) -> Pubkey {
+        Self::program_id()
+    }
+
+    fn label(&self) -> String {
+        "SplTokenSwap".to_string()
+    }
+
+    fn get_accounts_len(&self) -> usize {
+        amm_spl_token_swap::ACCOUNTS_LEN
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![*self.state.token_a_mint(), *self.state.token_b_mint()]
+    }
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        vec![self.key, *self.state.token_a_account(), *self.state.token_b_account(), *self.state.token_a_mint(), *self.state.token_b_mint()]
+    }
+
+    fn is_active(&self) -> bool {
+        self.state.is_initialized()
+    }
+
+    fn from_keyed_account(keyed_account: &KeyedAccount) -> eyre::Result<Self>
+    where
+        Self: Sized,
+    {
+        let state = SwapVersion::unpack(&keyed_account.account.data)?;
+        Ok(SplTokenSwap { key: keyed_account.key, state, token_a_amount: 0, token_b_amount: 0, slot: 0, token_metas: TokenMetas::default() })
+    }
+
+    fn update(&mut self, account_map: &AccountMap, slot: Option<u64>) -> eyre::Result<()> {
+        let swap = account_map.get(&self.key).ok_or_else(|| eyre!("swap not found"))?;
+        self.state = SwapVersion::unpack(&swap.data)?;
+
+        let token_a = account_map.get(self.state.token_a_account()).ok_or_else(|| eyre!("token_a not found"))?;
+        let token_b = account_map.get(self.state.token_b_account()).ok_or_else(|| eyre!("token_b not found"))?;
+        self.token_a_amount = TokenAccount::try_deserialize(&mut &token_a.data[..])?.amount;
+        self.token_b_amount = TokenAccount::try_deserialize(&mut &token_b.data[..])?.amount;
+        self.token_metas.update(account_map, &[*self.state.token_a_mint(), *self.state.token_b_mint()])?;
+
+        if let Some(slot) = slot {
+            self.slot = slot;
+        }
+
+        Ok(())
+    }
+
+    fn quote(&mut self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        self.token_metas.quote(quote_params, self.slot, |quote_params| self.quote_pool(quote_params))
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> eyre::Result<SwapAndAccountMetas> {
+        let (swap_source, swap_destination) = if swap_params.input_mint == *self.state.token_a_mint() {
+            (*self.state.token_a_account(), *self.state.token_b_account())
+        } else {
+            (*self.state.token_b_account(), *self.state.token_a_account())
+        };
+
+        let token_swap = TokenSwap {
+            token_swap_program: Self::program_id(),
+            token_program: *self.state.token_program_id(),
+            swap: self.key,
+            authority: self.authority()?,
+            user_transfer_authority: swap_params.token_transfer_authority,
+            source: swap_params.src_ta,
+            swap_source,
+            swap_destination,
+            destination: swap_params.dst_ta,
+            pool_mint: *self.state.pool_mint(),
+            pool_fee: *self.state.pool_fee_account(),
+        };
+
+        Ok(SwapAndAccountMetas { swap: AmmKind::SplTokenSwap, account_metas: Vec::<AccountMeta>::from(token_swap) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{account::Account, program_pack::Pack};
+
+    use super::*;
+    use crate::{
+        adapters::{SwapMode, amms::swap_state::ConstantProductSwapV1, token_meta::TokenMeta},
+        curves::{
+            base::{CurveType, SwapCurve},
+            fees::Fees,
+            stable::StableCurve,
+        },
+    };
+
+    fn swap_account(swap_curve: SwapCurve) -> (KeyedAccount, ConstantProductSwapV1) {
+        let key = Pubkey::new_unique();
+        let (_, bump_seed) = Pubkey::find_program_address(&[key.as_ref()], &SplTokenSwap::program_id());
+        let swap_info = ConstantProductSwapV1 {
+            is_initialized: true,
+            bump_seed,
+            token_program_id: spl_token::id(),
+            token_a: Pubkey::new_unique(),
+            token_b: Pubkey::new_unique(),
+            pool_mint: Pubkey::new_unique(),
+            token_a_mint: Pubkey::new_unique(),
+            token_b_mint: Pubkey::new_unique(),
+            pool_fee_account: Pubkey::new_unique(),
+            // 0.25% to the pool and 0.05% to the owner
+            fees: Fees { trade_fee_numerator: 25, trade_fee_denominator: 10_000, owner_trade_fee_numerator: 5, owner_trade_fee_denominator: 10_000, ..Fees::default() },
+            swap_curve,
+        };
+
+        // the version byte, then the v1 state
+        let mut data = vec![1u8; SwapVersion::LATEST_LEN];
+        anchor_lang::solana_program::program_pack::Pack::pack_into_slice(&swap_info, &mut data[1..]);
+
+        let account = Account { data, owner: SplTokenSwap::program_id(), ..Account::default() };
+        (KeyedAccount { key, account, params: None }, swap_info)
+    }
+
+    fn token_account(mint: Pubkey, amount: u64) -> Account {
+        let token_account = spl_token::state::Account { mint, amount, state: spl_token::state::AccountState::Initialized, ..Default::default() };
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        token_account.pack_into_slice(&mut data);
+
+        Account { data, owner: spl_token::id(), ..Account::default() }
+    }
+
+    fn pool(swap_curve: SwapCurve, token_a_amount: u64, token_b_amount: u64) -> (SplTokenSwap, ConstantProductSwapV1) {
+        let (keyed_account, swap_info) = swap_account(swap_curve);
+        let mut pool = SplTokenSwap::from_keyed_account(&keyed_account).unwrap();
+
+        let account_map: AccountMap = [
+            (keyed_account.key, keyed_account.account.clone()),
+            (swap_info.token_a, token_account(swap_info.token_a_mint, token_a_amount)),
+            (swap_info.token_b, token_account(swap_info.token_b_mint, token_b_amount)),
+        ]
+        .into_iter()
+        .collect();
+        pool.update(&account_map, Some(42)).unwrap();
+
+        (pool, swap_info)
+    }
+
+    #[test]
+    fn quotes_the_pool_curve_less_fees() {
+        let (mut pool, swap_info) = pool(SwapCurve::default(), 1_000_000_000, 2_000_000_000);
+        assert_eq!(pool.get_accounts_to_update(), vec![pool.key, swap_info.token_a, swap_info.token_b, swap_info.token_a_mint, swap_info.token_b_mint]);
+
+        let params = QuoteParams { swap_mode: SwapMode::ExactIn, amount: 1_000_000, input_mint: swap_info.token_a_mint, output_mint: swap_info.token_b_mint };
+        let quote = pool.quote(&params).unwrap();
+        // 2_500 + 500 in fees, then 2e9 - ceil(2e18 / (1e9 + 997_000))
+        assert_eq!(quote.fee_amount, 3_000);
+        assert_eq!(quote.out_amount, 1_992_013);
+        assert_eq!(quote.fee_pct, rust_decimal::Decimal::new(30, 4));
+        assert_eq!(quote.context_slot, 42);
+    }
+
+    #[test]
+    fn quotes_stable_pools_near_parity() {
+        let swap_curve = SwapCurve { curve_type: CurveType::Stable, calculator: Arc::new(StableCurve { amp: 100 }) };
+        let (mut pool, swap_info) = pool(swap_curve, 1_000_000_000_000, 1_000_000_000_000);
+
+        let params = QuoteParams { swap_mode: SwapMode::ExactIn, amount: 1_000_000_000, input_mint: swap_info.token_b_mint, output_mint: swap_info.token_a_mint };
+        let quote = pool.quote(&params).unwrap();
+        assert_eq!(quote.fee_amount, 3_000_000);
+        assert!(quote.out_amount < 997_000_000 && quote.out_amount > 996_900_000);
+    }
+
+    #[test]
+    fn rejects_transfer_hook_mints() {
+        let (mut pool, swap_info) = pool(SwapCurve::default(), 1_000_000_000, 2_000_000_000);
+        pool.token_metas.insert(swap_info.token_b_mint, TokenMeta { transfer_hook: Some(Pubkey::new_unique()), ..TokenMeta::default() });
+
+        let params = QuoteParams { swap_mode: SwapMode::ExactIn, amount: 1_000_000, input_mint: swap_info.token_a_mint, output_mint: swap_info.token_b_mint };
+        assert!(pool.quote(&params).is_err());
+    }
+
+    #[test]
+    fn builds_the_token_swap_leg() {
+        let (pool, swap_info) = pool(SwapCurve::default(), 1, 1);
+        let swap_params = SwapParams {
+            input_mint: swap_info.token_b_mint,
+            output_mint: swap_info.token_a_mint,
+            src_ta: Pubkey::new_unique(),
+            dst_ta: Pubkey::new_unique(),
+            token_transfer_authority: Pubkey::new_unique(),
+            ..SwapParams::default()
+        };
+
+        let metas = pool.get_swap_and_account_metas(&swap_params).unwrap().account_metas;
+        assert_eq!(metas.len(), amm_spl_token_swap::ACCOUNTS_LEN);
+        assert_eq!(metas[0].pubkey, SplTokenSwap::program_id());
+        assert_eq!(metas[3].pubkey, pool.authority().unwrap());
+        assert_eq!(metas[4].pubkey, swap_params.token_transfer_authority);
+        assert_eq!(metas[6].pubkey, swap_info.token_b);
+        assert_eq!(metas[7].pubkey, swap_info.token_a);
+    }
+}
@@ -1,6 +1,6 @@
 //! State transition types
 
-use std::sync::Arc;
+use std::{fmt::Debug, sync::Arc};
 
 use anchor_lang::solana_program::{
     account_info::AccountInfo,
@@ -15,7 +15,7 @@ use crate::curves::{base::SwapCurve, fees::Fees};
 
 /// Trait representing access to program state across all versions
 #[enum_dispatch]
-pub trait SwapState {
+pub trait SwapState: Debug + Send + Sync {
     /// Is the swap initialized, with data written to it
     fn is_initialized(&self) -> bool;
     /// Bump seed used to generate the program address / authority
@@ -48,6 +48,7 @@ pub trait SwapState {
 
 /// All versions of SwapState
 #[enum_dispatch(SwapState)]
+#[derive(Debug)]
 pub enum SwapVersion {
     ConstantProductSwapV1,
 }
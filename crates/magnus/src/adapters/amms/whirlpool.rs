@@ -0,0 +1,6 @@
+pub mod integration;
+pub mod math;
+pub mod state;
+
+pub use integration::Whirlpool;
+pub use state::{Oracle, TickArray, WhirlpoolState};
@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+
+use borsh::BorshDeserialize;
+use eyre::eyre;
+use magnus_shared::amm_whirlpool;
+use solana_instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::adapters::{
+    Adapter, AmmKind,
+    amms::{
+        AccountMap, Amm, KeyedAccount, Quote, QuoteParams, SwapAndAccountMetas, SwapParams,
+        whirlpool::{
+            math,
+            state::{self, MAX_TICK_INDEX, MIN_TICK_INDEX, Oracle, TickArray, WhirlpoolState},
+        },
+    },
+    token_meta::TokenMetas,
+};
+
+const MEMO_PROGRAM_ID: Pubkey = Pubkey::from_str_const("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// The number of tick arrays a swap passes, the one holding the current tick and the next two in the swap's direction.
+const TICK_ARRAYS_PER_SWAP: i32 = 3;
+
+#[derive(Clone, Debug, Default)]
+pub struct Whirlpool {
+    key: Pubkey,
+    pub state: WhirlpoolState,
+    oracle_key: Pubkey,
+    oracle: Option<Oracle>,
+    /// The tracked tick arrays, keyed by their start tick index.
+    tick_arrays: HashMap<i32, TickArray>,
+    slot: u64,
+    token_metas: TokenMetas,
+}
+
+impl Whirlpool {
+    fn program_id() -> Pubkey {
+        Pubkey::from_str_const(&amm_whirlpool::id().to_string())
+    }
+
+    /// The start indexes of the tick arrays a swap from the current tick passes, in the swap's direction.
+    fn swap_tick_array_starts(&self, a_to_b: bool) -> Vec<i32> {
+        let span = self.state.ticks_in_array();
+        let start = self.state.tick_array_start_index(self.state.tick_current_index);
+        let step = if a_to_b { -span } else { span };
+
+        (0..TICK_ARRAYS_PER_SWAP).map(|i| start + i * step).filter(|start| *start + span > MIN_TICK_INDEX && *start <= MAX_TICK_INDEX).collect()
+    }
+
+    /// The start indexes of the tick arrays a swap in either direction passes.
+    fn tracked_tick_array_starts(&self) -> Vec<i32> {
+        let mut starts = self.swap_tick_array_starts(true);
+        starts.extend(self.swap_tick_array_starts(false).into_iter().skip(1));
+        starts
+    }
+
+    fn tick_array_address(&self, start_tick_index: i32) -> Pubkey {
+        state::tick_array_address(&Self::program_id(), &self.key, start_tick_index)
+    }
+
+    /// Walks the initialized ticks of the reachable tick arrays, crossing each one as the price reaches it.
+    fn quote_pool(&self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        let a_to_b = if quote_params.input_mint == self.state.token_mint_a {
+            true
+        } else if quote_params.input_mint == self.state.token_mint_b {
+            false
+        } else {
+            return Err(eyre!("mint {} isn't traded by whirlpool {}", quote_params.input_mint, self.key));
+        };
+
+        let span = self.state.ticks_in_array();
+        let starts = self.swap_tick_array_starts(a_to_b);
+        let last = *starts.last().ok_or_else(|| eyre!("no reachable tick arrays"))?;
+        let bound_tick = if a_to_b { last.max(MIN_TICK_INDEX) } else { (last + span).min(MAX_TICK_INDEX) };
+
+        let tick_current = self.state.tick_current_index;
+        let mut ticks: Vec<(i32, i128)> = starts
+            .iter()
+            .filter_map(|start| self.tick_arrays.get(start))
+            .flat_map(|tick_array| {
+                tick_array
+                    .ticks
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, tick)| tick.initialized)
+                    .map(|(i, tick)| (tick_array.start_tick_index + i as i32 * self.state.tick_spacing as i32, tick.liquidity_net))
+            })
+            .filter(|(tick_index, _)| if a_to_b { *tick_index <= tick_current } else { *tick_index > tick_current })
+            .collect();
+        ticks.sort_by_key(|(tick_index, _)| *tick_index);
+        if a_to_b {
+            ticks.reverse();
+        }
+
+        let fee_rate = math::fee_rate(self.state.fee_rate, self.oracle.as_ref());
+        let mut ticks = ticks.into_iter().peekable();
+        let (mut sqrt_price, mut liquidity) = (self.state.sqrt_price, self.state.liquidity);
+        let (mut amount_remaining, mut amount_out, mut fee_amount) = (quote_params.amount, 0u64, 0u64);
+
+        while amount_remaining > 0 {
+            let (target_tick, liquidity_net) = ticks.peek().map_or((bound_tick, None), |(tick_index, liquidity_net)| (*tick_index, Some(*liquidity_net)));
+            let target_sqrt_price = math::sqrt_price_from_tick_index(target_tick);
+
+            let step = math::compute_swap_step(amount_remaining, fee_rate, liquidity, sqrt_price, target_sqrt_price, a_to_b).ok_or_else(|| eyre!("swap step overflow"))?;
+            amount_remaining -= step.amount_in + step.fee_amount;
+            amount_out = amount_out.checked_add(step.amount_out).ok_or_else(|| eyre!("overflow"))?;
+            fee_amount += step.fee_amount;
+            sqrt_price = step.next_sqrt_price;
+
+            if step.next_sqrt_price != target_sqrt_price {
+                continue;
+            }
+            match liquidity_net {
+                Some(liquidity_net) => {
+                    ticks.next();
+                    let delta = if a_to_b { -liquidity_net } else { liquidity_net };
+                    liquidity = liquidity.checked_add_signed(delta).ok_or_else(|| eyre!("liquidity overflow"))?;
+                }
+                None if amount_remaining > 0 => return Err(eyre!("amount exceeds the liquidity of the tick arrays a swap passes")),
+                None => {}
+            }
+        }
+
+        Ok(Quote {
+            in_amount: quote_params.amount,
+            out_amount: amount_out,
+            fee_amount,
+            fee_mint: quote_params.input_mint,
+            fee_pct: rust_decimal::Decimal::new(fee_rate as i64, 6),
+            context_slot: self.slot,
+        })
+    }
+}
+
+impl Adapter for Whirlpool {}
+
+impl Amm for Whirlpool {
+    fn program_id(&self) -> Pubkey {
+        Self::program_id()
+    }
+
+    fn label(&self) -> String {
+        "Whirlpool".to_string()
+    }
+
+    fn get_accounts_len(&self) -> usize {
+        amm_whirlpool::ACCOUNTS_LEN
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![self.state.token_mint_a, self.state.token_mint_b]
+    }
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        let mut accounts = vec![self.key, self.oracle_key, self.state.token_mint_a, self.state.token_mint_b];
+        accounts.extend(self.tracked_tick_array_starts().into_iter().map(|start| self.tick_array_address(start)));
+        accounts
+    }
+
+    // the tracked tick arrays follow the current tick
+    fn has_dynamic_accounts(&self) -> bool {
+        true
+    }
+
+    fn from_keyed_account(keyed_account: &KeyedAccount) -> eyre::Result<Self>
+    where
+        Self: Sized,
+    {
+        let state = WhirlpoolState::deserialize(&mut &keyed_account.account.data[8..])?;
+        let oracle_key = state::oracle_address(&Self::program_id(), &keyed_account.key);
+
+        Ok(Whirlpool { key: keyed_account.key, state, oracle_key, ..Whirlpool::default() })
+    }
+
+    fn update(&mut self, account_map: &AccountMap, slot: Option<u64>) -> eyre::Result<()> {
+        let whirlpool = account_map.get(&self.key).ok_or_else(|| eyre!("whirlpool not found"))?;
+        self.state = WhirlpoolState::deserialize(&mut &whirlpool.data[8..])?;
+
+        // only adaptive fee pools have an oracle
+        self.oracle = account_map.get(&self.oracle_key).filter(|oracle| oracle.data.len() > 8).map(|oracle| Oracle::deserialize(&mut &oracle.data[8..])).transpose()?;
+
+        // missing tick arrays are uninitialized, i.e. hold no liquidity
+        let mut tick_arrays = HashMap::new();
+        for start in self.tracked_tick_array_starts() {
+            if let Some(tick_array) = account_map.get(&self.tick_array_address(start)) {
+                tick_arrays.insert(start, TickArray::deserialize(&mut &tick_array.data[8..])?);
+            }
+        }
+        self.tick_arrays = tick_arrays;
+
+        self.token_metas.update(account_map, &[self.state.token_mint_a, self.state.token_mint_b])?;
+        if let Some(slot) = slot {
+            self.slot = slot;
+        }
+
+        Ok(())
+    }
+
+    fn quote(&mut self, quote_params: &QuoteParams) -> eyre::Result<Quote> {
+        self.token_metas.quote(quote_params, self.slot, |quote_params| self.quote_pool(quote_params))
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> eyre::Result<SwapAndAccountMetas> {
+        let a_to_b = swap_params.input_mint == self.state.token_mint_a;
+
+        // near the tick bounds there are less than 3 arrays to pass, the last one is repeated
+        let starts = self.swap_tick_array_starts(a_to_b);
+        let tick_arrays: Vec<Pubkey> = (0..TICK_ARRAYS_PER_SWAP as usize).map(|i| self.tick_array_address(starts[i.min(starts.len() - 1)])).collect();
+
+        let token_program = |mint: &Pubkey| self.token_metas.get(mint).map_or(spl_token::id(), |meta| meta.program);
+
+        let account_metas = vec![
+            AccountMeta::new_readonly(Self::program_id(), false),
+            AccountMeta::new(swap_params.token_transfer_authority, true),
+            AccountMeta::new(swap_params.src_ta, false),
+            AccountMeta::new(swap_params.dst_ta, false),
+            AccountMeta::new(self.key, false),
+            AccountMeta::new_readonly(token_program(&self.state.token_mint_a), false),
+            AccountMeta::new_readonly(token_program(&self.state.token_mint_b), false),
+            AccountMeta::new_readonly(MEMO_PROGRAM_ID, false),
+            AccountMeta::new_readonly(self.state.token_mint_a, false),
+            AccountMeta::new_readonly(self.state.token_mint_b, false),
+            AccountMeta::new(self.state.token_vault_a, false),
+            AccountMeta::new(self.state.token_vault_b, false),
+            AccountMeta::new(tick_arrays[0], false),
+            AccountMeta::new(tick_arrays[1], false),
+            AccountMeta::new(tick_arrays[2], false),
+            AccountMeta::new(self.oracle_key, false),
+        ];
+
+        Ok(SwapAndAccountMetas { swap: AmmKind::Whirlpool, account_metas })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{SwapMode, amms::whirlpool::state::Tick};
+
+    const LIQUIDITY: u128 = 1_000_000_000_000;
+
+    // a single position around the current price, between ticks -1408 and 1408
+    fn whirlpool() -> Whirlpool {
+        let state = WhirlpoolState {
+            tick_spacing: 64,
+            fee_rate: 3_000,
+            liquidity: LIQUIDITY,
+            sqrt_price: 1 << 64,
+            tick_current_index: 0,
+            token_mint_a: Pubkey::new_unique(),
+            token_mint_b: Pubkey::new_unique(),
+            ..WhirlpoolState::default()
+        };
+
+        let mut lower = TickArray { start_tick_index: -5632, ticks: [Tick::default(); state::TICK_ARRAY_SIZE as usize], whirlpool: Pubkey::default() };
+        lower.ticks[66] = Tick { initialized: true, liquidity_net: LIQUIDITY as i128, liquidity_gross: LIQUIDITY, ..Tick::default() };
+        let mut upper = TickArray { start_tick_index: 0, ticks: [Tick::default(); state::TICK_ARRAY_SIZE as usize], whirlpool: Pubkey::default() };
+        upper.ticks[22] = Tick { initialized: true, liquidity_net: -(LIQUIDITY as i128), liquidity_gross: LIQUIDITY, ..Tick::default() };
+
+        Whirlpool { key: Pubkey::new_unique(), state, tick_arrays: HashMap::from([(-5632, lower), (0, upper)]), ..Whirlpool::default() }
+    }
+
+    fn params(whirlpool: &Whirlpool, a_to_b: bool, amount: u64) -> QuoteParams {
+        let (input_mint, output_mint) = if a_to_b {
+            (whirlpool.state.token_mint_a, whirlpool.state.token_mint_b)
+        } else {
+            (whirlpool.state.token_mint_b, whirlpool.state.token_mint_a)
+        };
+        QuoteParams { swap_mode: SwapMode::ExactIn, amount, input_mint, output_mint }
+    }
+
+    #[test]
+    fn quotes_within_the_position() {
+        let mut whirlpool = whirlpool();
+
+        for a_to_b in [true, false] {
+            let quote = whirlpool.quote(&params(&whirlpool, a_to_b, 1_000_000)).unwrap();
+
+            assert!((3_000..=3_001).contains(&quote.fee_amount), "{:?}", quote);
+            // 0.3% fee and a tiny price impact at a price of 1
+            assert!((996_000..997_000).contains(&quote.out_amount), "{:?}", quote);
+        }
+    }
+
+    #[test]
+    fn rejects_amounts_past_the_position() {
+        let mut whirlpool = whirlpool();
+
+        // ~ L * (sqrt(1.0001^1408) - 1) ~ 7.3e10 of token b fits in the position
+        assert!(whirlpool.quote(&params(&whirlpool, false, 50_000_000_000)).is_ok());
+        assert!(whirlpool.quote(&params(&whirlpool, false, 100_000_000_000)).is_err());
+    }
+
+    #[test]
+    fn tracks_the_tick_arrays_around_the_current_tick() {
+        let whirlpool = whirlpool();
+
+        assert_eq!(whirlpool.swap_tick_array_starts(true), vec![0, -5632, -11264]);
+        assert_eq!(whirlpool.swap_tick_array_starts(false), vec![0, 5632, 11264]);
+        assert_eq!(whirlpool.tracked_tick_array_starts().len(), 5);
+    }
+}
@@ -0,0 +1,289 @@
+//! Whirlpool's Q64.64 concentrated liquidity math, mirroring the on-chain rounding.
+
+use spl_math::uint::U256;
+
+use super::state::{MAX_TICK_INDEX, MIN_TICK_INDEX, Oracle};
+
+pub const MIN_SQRT_PRICE_X64: u128 = 4295048016;
+pub const MAX_SQRT_PRICE_X64: u128 = 79226673515401279992447579055;
+
+pub const FEE_RATE_DENOMINATOR: u32 = 1_000_000;
+pub const FEE_RATE_HARD_LIMIT: u32 = 100_000;
+
+const ADAPTIVE_FEE_CONTROL_FACTOR_DENOMINATOR: u128 = 100_000;
+const VOLATILITY_ACCUMULATOR_SCALE_FACTOR: u128 = 10_000;
+
+fn mul_shift(a: u128, b: u128, shift: u32) -> u128 {
+    ((U256::from(a) * U256::from(b)) >> shift).as_u128()
+}
+
+fn shl_64(value: U256) -> Option<U256> {
+    (value.bits() <= 192).then(|| value << 64)
+}
+
+fn sqrt_price_positive_tick(tick: i32) -> u128 {
+    // sqrt(1.0001)^(2^i) in Q32.96
+    let mut ratio: u128 = if tick & 1 != 0 { 79232123823359799118286999567 } else { 79228162514264337593543950336 };
+
+    for (bit, factor) in [
+        (2, 79236085330515764027303304731),
+        (4, 79244008939048815603706035061),
+        (8, 79259858533276714757314932305),
+        (16, 79291567232598584799939703904),
+        (32, 79355022692464371645785046466),
+        (64, 79482085999252804386437311141),
+        (128, 79736823300114093921829183326),
+        (256, 80248749790819932309965073892),
+        (512, 81282483887344747381513967011),
+        (1024, 83390072131320151908154831281),
+        (2048, 87770609709833776024991924138),
+        (4096, 97234110755111693312479820773),
+        (8192, 119332217159966728226237229890),
+        (16384, 179736315981702064433883588727),
+        (32768, 407748233172238350107850275304),
+        (65536, 2098478828474011932436660412517),
+        (131072, 55581415166113811149459800483533),
+        (262144, 38992368544603139932233054999993551u128),
+    ] {
+        if tick & bit != 0 {
+            ratio = mul_shift(ratio, factor, 96);
+        }
+    }
+
+    ratio >> 32
+}
+
+fn sqrt_price_negative_tick(tick: i32) -> u128 {
+    let abs_tick = tick.abs();
+
+    // sqrt(1.0001)^-(2^i) in Q64.64
+    let mut ratio: u128 = if abs_tick & 1 != 0 { 18445821805675392311 } else { 18446744073709551616 };
+
+    for (bit, factor) in [
+        (2, 18444899583751176498),
+        (4, 18443055278223354162),
+        (8, 18439367220385604838),
+        (16, 18431993317065449817),
+        (32, 18417254355718160513),
+        (64, 18387811781193591352),
+        (128, 18329067761203520168),
+        (256, 18212142134806087854),
+        (512, 17980523815641551639),
+        (1024, 17526086738831147013),
+        (2048, 16651378430235024244),
+        (4096, 15030750278693429944),
+        (8192, 12247334978882834399),
+        (16384, 8131365268884726200),
+        (32768, 3584323654723342297),
+        (65536, 696457651847595233),
+        (131072, 26294789957452057),
+        (262144, 37481735321082),
+    ] {
+        if abs_tick & bit != 0 {
+            ratio = mul_shift(ratio, factor, 64);
+        }
+    }
+
+    ratio
+}
+
+/// sqrt(1.0001^tick) in Q64.64.
+pub fn sqrt_price_from_tick_index(tick: i32) -> u128 {
+    let tick = tick.clamp(MIN_TICK_INDEX, MAX_TICK_INDEX);
+    if tick >= 0 { sqrt_price_positive_tick(tick) } else { sqrt_price_negative_tick(tick) }
+}
+
+/// The amount of token a between two sqrt prices, `None` if it doesn't fit a u64.
+pub fn amount_delta_a(sqrt_price_0: u128, sqrt_price_1: u128, liquidity: u128, round_up: bool) -> Option<u64> {
+    let (lower, upper) = if sqrt_price_0 < sqrt_price_1 {
+        (sqrt_price_0, sqrt_price_1)
+    } else {
+        (sqrt_price_1, sqrt_price_0)
+    };
+    if lower == 0 {
+        return None;
+    }
+
+    let numerator = U256::from(liquidity).checked_mul(U256::from(upper - lower)).and_then(shl_64)?;
+    let denominator = U256::from(upper) * U256::from(lower);
+    let (quotient, remainder) = numerator.div_mod(denominator);
+    let result = if round_up && !remainder.is_zero() { quotient + 1 } else { quotient };
+
+    (result <= U256::from(u64::MAX)).then(|| result.as_u64())
+}
+
+/// The amount of token b between two sqrt prices, `None` if it doesn't fit a u64.
+pub fn amount_delta_b(sqrt_price_0: u128, sqrt_price_1: u128, liquidity: u128, round_up: bool) -> Option<u64> {
+    let diff = sqrt_price_0.abs_diff(sqrt_price_1);
+
+    let product = U256::from(liquidity) * U256::from(diff);
+    let quotient = product >> 64;
+    let result = if round_up && !(product & U256::from(u64::MAX)).is_zero() {
+        quotient + 1
+    } else {
+        quotient
+    };
+
+    (result <= U256::from(u64::MAX)).then(|| result.as_u64())
+}
+
+/// The sqrt price after adding `amount` of token a, i.e. moving the price down.
+pub fn next_sqrt_price_from_a_round_up(sqrt_price: u128, liquidity: u128, amount: u64) -> Option<u128> {
+    if amount == 0 {
+        return Some(sqrt_price);
+    }
+
+    let numerator = U256::from(liquidity).checked_mul(U256::from(sqrt_price)).and_then(shl_64)?;
+    let denominator = (U256::from(liquidity) << 64).checked_add(U256::from(sqrt_price) * U256::from(amount))?;
+    let (quotient, remainder) = numerator.div_mod(denominator);
+    let result = if remainder.is_zero() { quotient } else { quotient + 1 };
+
+    (result >= U256::from(MIN_SQRT_PRICE_X64)).then(|| result.as_u128())
+}
+
+/// The sqrt price after adding `amount` of token b, i.e. moving the price up.
+pub fn next_sqrt_price_from_b_round_down(sqrt_price: u128, liquidity: u128, amount: u64) -> Option<u128> {
+    if liquidity == 0 {
+        return None;
+    }
+
+    let delta = (U256::from(amount) << 64) / U256::from(liquidity);
+    let result = U256::from(sqrt_price).checked_add(delta)?;
+
+    (result <= U256::from(MAX_SQRT_PRICE_X64)).then(|| result.as_u128())
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapStep {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub next_sqrt_price: u128,
+    pub fee_amount: u64,
+}
+
+/// An exact-in swap step from `sqrt_price_current` towards `sqrt_price_target` within constant `liquidity`.
+pub fn compute_swap_step(amount_remaining: u64, fee_rate: u32, liquidity: u128, sqrt_price_current: u128, sqrt_price_target: u128, a_to_b: bool) -> Option<SwapStep> {
+    let amount_less_fee = (amount_remaining as u128 * (FEE_RATE_DENOMINATOR - fee_rate) as u128 / FEE_RATE_DENOMINATOR as u128) as u64;
+
+    // more than a u64 is needed to reach the target, i.e. the step ends before it
+    let max_amount_in = if a_to_b {
+        amount_delta_a(sqrt_price_target, sqrt_price_current, liquidity, true)
+    } else {
+        amount_delta_b(sqrt_price_current, sqrt_price_target, liquidity, true)
+    }
+    .unwrap_or(u64::MAX);
+
+    let reaches_target = amount_less_fee >= max_amount_in;
+    let (next_sqrt_price, amount_in, fee_amount) = if reaches_target {
+        let fee_amount = (max_amount_in as u128 * fee_rate as u128).div_ceil((FEE_RATE_DENOMINATOR - fee_rate) as u128) as u64;
+        (sqrt_price_target, max_amount_in, fee_amount)
+    } else {
+        let next_sqrt_price = if a_to_b {
+            next_sqrt_price_from_a_round_up(sqrt_price_current, liquidity, amount_less_fee)?
+        } else {
+            next_sqrt_price_from_b_round_down(sqrt_price_current, liquidity, amount_less_fee)?
+        };
+        let amount_in = if a_to_b {
+            amount_delta_a(next_sqrt_price, sqrt_price_current, liquidity, true)?
+        } else {
+            amount_delta_b(sqrt_price_current, next_sqrt_price, liquidity, true)?
+        };
+        (next_sqrt_price, amount_in, amount_remaining - amount_in)
+    };
+
+    let amount_out = if a_to_b {
+        amount_delta_b(next_sqrt_price, sqrt_price_current, liquidity, false)?
+    } else {
+        amount_delta_a(sqrt_price_current, next_sqrt_price, liquidity, false)?
+    };
+
+    Some(SwapStep { amount_in, amount_out, next_sqrt_price, fee_amount })
+}
+
+/// The static fee rate raised by the oracle's adaptive fee, if the pool has one.
+///
+/// The volatility accumulator is taken as last stored: on-chain it decays with the time since
+/// the last reference update and grows with every tick group crossed during the swap, so this is
+/// the fee of a swap staying within the current tick group.
+pub fn fee_rate(static_fee_rate: u16, oracle: Option<&Oracle>) -> u32 {
+    let Some(oracle) = oracle else {
+        return static_fee_rate as u32;
+    };
+
+    let constants = &oracle.adaptive_fee_constants;
+    let crossed = oracle.adaptive_fee_variables.volatility_accumulator as u128 * constants.tick_group_size as u128;
+    let adaptive_fee_rate = (constants.adaptive_fee_control_factor as u128 * crossed * crossed)
+        .div_ceil(ADAPTIVE_FEE_CONTROL_FACTOR_DENOMINATOR * VOLATILITY_ACCUMULATOR_SCALE_FACTOR * VOLATILITY_ACCUMULATOR_SCALE_FACTOR);
+
+    (static_fee_rate as u128 + adaptive_fee_rate).min(FEE_RATE_HARD_LIMIT as u128) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::amms::whirlpool::state::{AdaptiveFeeConstants, AdaptiveFeeVariables};
+
+    const ONE_X64: u128 = 1 << 64;
+
+    #[test]
+    fn sqrt_price_from_tick_index_matches_the_bounds() {
+        assert_eq!(sqrt_price_from_tick_index(0), ONE_X64);
+        assert_eq!(sqrt_price_from_tick_index(1), 18447666387855959850);
+        assert_eq!(sqrt_price_from_tick_index(-1), 18445821805675392311);
+        assert_eq!(sqrt_price_from_tick_index(MIN_TICK_INDEX), MIN_SQRT_PRICE_X64);
+        assert_eq!(sqrt_price_from_tick_index(MAX_TICK_INDEX), MAX_SQRT_PRICE_X64);
+    }
+
+    #[test]
+    fn amount_deltas_round_as_requested() {
+        let (lower, upper) = (sqrt_price_from_tick_index(-100), sqrt_price_from_tick_index(100));
+        let liquidity = 1_000_000_000u128;
+
+        let a_down = amount_delta_a(lower, upper, liquidity, false).unwrap();
+        let a_up = amount_delta_a(upper, lower, liquidity, true).unwrap();
+        assert!(a_up == a_down || a_up == a_down + 1);
+        // ~ L * (1/sqrt(p_lower) - 1/sqrt(p_upper)) ~ 1e9 * 0.01
+        assert!((9_990_000..10_010_000).contains(&a_down));
+
+        let b_down = amount_delta_b(lower, upper, liquidity, false).unwrap();
+        let b_up = amount_delta_b(lower, upper, liquidity, true).unwrap();
+        assert!(b_up == b_down || b_up == b_down + 1);
+        assert!((9_990_000..10_010_000).contains(&b_down));
+    }
+
+    #[test]
+    fn swap_step_within_range_spends_the_whole_amount() {
+        let step = compute_swap_step(1_000_000, 3_000, 1_000_000_000_000, ONE_X64, MIN_SQRT_PRICE_X64, true).unwrap();
+
+        assert!(step.next_sqrt_price < ONE_X64);
+        assert_eq!(step.amount_in + step.fee_amount, 1_000_000);
+        // 0.3% fee, at a price of ~1
+        assert!((996_000..997_000).contains(&step.amount_out));
+    }
+
+    #[test]
+    fn swap_step_stops_at_the_target() {
+        let target = sqrt_price_from_tick_index(10);
+        let step = compute_swap_step(u64::MAX / 2, 3_000, 1_000_000_000, ONE_X64, target, false).unwrap();
+
+        assert_eq!(step.next_sqrt_price, target);
+        assert_eq!(step.amount_in, amount_delta_b(ONE_X64, target, 1_000_000_000, true).unwrap());
+        assert_eq!(step.fee_amount, (step.amount_in as u128 * 3_000).div_ceil(997_000) as u64);
+    }
+
+    #[test]
+    fn fee_rate_adds_the_adaptive_fee() {
+        assert_eq!(fee_rate(3_000, None), 3_000);
+
+        let oracle = Oracle {
+            adaptive_fee_constants: AdaptiveFeeConstants { adaptive_fee_control_factor: 1_500, tick_group_size: 64, ..Default::default() },
+            adaptive_fee_variables: AdaptiveFeeVariables { volatility_accumulator: 10_000, ..Default::default() },
+            ..Default::default()
+        };
+        // 1_500 * (10_000 * 64)^2 / (100_000 * 10_000^2)
+        assert_eq!(fee_rate(3_000, Some(&oracle)), 3_000 + 62);
+
+        let volatile = Oracle { adaptive_fee_variables: AdaptiveFeeVariables { volatility_accumulator: u32::MAX, ..Default::default() }, ..oracle };
+        assert_eq!(fee_rate(3_000, Some(&volatile)), FEE_RATE_HARD_LIMIT);
+    }
+}
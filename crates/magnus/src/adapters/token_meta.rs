@@ -0,0 +1,202 @@
+//! Token-2022 mint extensions relevant to quoting.
+//!
+//! Transfers of a mint carrying a `TransferFeeConfig` withhold part of the amount
+//! at the destination, so a pool receives less than the user sends and the user
+//! receives less than the pool sends. Mints carrying a `TransferHook` run an
+//! arbitrary program on every transfer, which the router doesn't pass accounts
+//! for, so routes touching them are rejected. `InterestBearingConfig` only changes
+//! the UI amount, raw amounts are unaffected.
+
+use std::collections::HashMap;
+
+use eyre::eyre;
+use solana_sdk::{account::Account, clock::DEFAULT_SLOTS_PER_EPOCH, pubkey::Pubkey};
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, StateWithExtensions, interest_bearing_mint::InterestBearingConfig, transfer_fee::TransferFeeConfig, transfer_hook::TransferHook},
+    state::Mint,
+};
+
+use crate::{
+    AccountMap,
+    adapters::{Quote, QuoteParams},
+};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokenMeta {
+    pub program: Pubkey,
+    pub decimals: u8,
+    pub transfer_fee: Option<TransferFeeConfig>,
+    /// The program invoked on every transfer, if any.
+    pub transfer_hook: Option<Pubkey>,
+    /// The current rate, in basis points, of an interest-bearing mint.
+    pub interest_rate_bps: Option<i16>,
+}
+
+impl TokenMeta {
+    pub fn from_mint_account(account: &Account) -> eyre::Result<TokenMeta> {
+        let mint = StateWithExtensions::<Mint>::unpack(&account.data).map_err(|e| eyre!("invalid mint: {}", e))?;
+
+        let transfer_fee = mint.get_extension::<TransferFeeConfig>().ok().copied();
+        let transfer_hook = mint
+            .get_extension::<TransferHook>()
+            .ok()
+            .map(|hook| Pubkey::new_from_array(hook.program_id.0.to_bytes()))
+            // a zeroed program id means the hook is unset
+            .filter(|program_id| *program_id != Pubkey::default());
+        let interest_rate_bps = mint.get_extension::<InterestBearingConfig>().ok().map(|config: &InterestBearingConfig| i16::from(config.current_rate));
+
+        Ok(TokenMeta { program: account.owner, decimals: mint.base.decimals, transfer_fee, transfer_hook, interest_rate_bps })
+    }
+
+    /// The fee withheld when transferring `amount` during `epoch`.
+    pub fn transfer_fee(&self, epoch: u64, amount: u64) -> u64 {
+        self.transfer_fee.and_then(|config| config.calculate_epoch_fee(epoch, amount)).unwrap_or(0)
+    }
+
+    /// What reaches the destination when transferring `amount` during `epoch`.
+    pub fn amount_after_transfer_fee(&self, epoch: u64, amount: u64) -> u64 {
+        amount.saturating_sub(self.transfer_fee(epoch, amount))
+    }
+}
+
+/// The [`TokenMeta`] of the mints an adapter trades, keyed by mint.
+#[derive(Clone, Debug, Default)]
+pub struct TokenMetas(HashMap<Pubkey, TokenMeta>);
+
+impl TokenMetas {
+    /// (Re)parses the `mints` found in `account_map`, keeping the previous meta of the missing ones.
+    pub fn update(&mut self, account_map: &AccountMap, mints: &[Pubkey]) -> eyre::Result<()> {
+        for mint in mints {
+            if let Some(account) = account_map.get(mint) {
+                self.0.insert(*mint, TokenMeta::from_mint_account(account)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn insert(&mut self, mint: Pubkey, meta: TokenMeta) {
+        self.0.insert(mint, meta);
+    }
+
+    pub fn get(&self, mint: &Pubkey) -> Option<&TokenMeta> {
+        self.0.get(mint)
+    }
+
+    /// Runs `quote` on what reaches the pool after the input mint's transfer fee,
+    /// then deducts the output mint's transfer fee from its out amount.
+    /// Routes touching a transfer-hook mint are rejected, as are the ones touching a transfer-fee mint
+    /// while the slot (i.e. the epoch picking the fee) is unknown.
+    pub fn quote(&self, params: &QuoteParams, slot: u64, quote: impl FnOnce(&QuoteParams) -> eyre::Result<Quote>) -> eyre::Result<Quote> {
+        let input = self.get(&params.input_mint);
+        let output = self.get(&params.output_mint);
+
+        for (mint, meta) in [(params.input_mint, input), (params.output_mint, output)] {
+            if let Some(hook) = meta.and_then(|meta| meta.transfer_hook) {
+                return Err(eyre!("mint {} has a transfer hook ({})", mint, hook));
+            }
+            if slot == 0 && meta.is_some_and(|meta| meta.transfer_fee.is_some()) {
+                return Err(eyre!("mint {} has a transfer fee, and the epoch it's charged at is unknown yet", mint));
+            }
+        }
+
+        let epoch = slot / DEFAULT_SLOTS_PER_EPOCH;
+        let amount = input.map_or(params.amount, |meta| meta.amount_after_transfer_fee(epoch, params.amount));
+        let pool_quote = quote(&QuoteParams { amount, ..*params })?;
+        let out_amount = output.map_or(pool_quote.out_amount, |meta| meta.amount_after_transfer_fee(epoch, pool_quote.out_amount));
+
+        Ok(Quote { in_amount: params.amount, out_amount, ..pool_quote })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::program_pack::Pack;
+    use spl_token_2022::extension::{BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut, transfer_fee::TransferFee};
+
+    use super::*;
+    use crate::adapters::SwapMode;
+
+    fn mk_mint(extensions: &[ExtensionType], init: impl FnOnce(&mut StateWithExtensionsMut<Mint>)) -> Account {
+        let len = ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        init(&mut state);
+        state.base = Mint { decimals: 6, is_initialized: true, ..Default::default() };
+        state.pack_base();
+        state.init_account_type().unwrap();
+
+        Account { lamports: 1, data, owner: Pubkey::new_from_array(spl_token_2022::id().to_bytes()), executable: false, rent_epoch: u64::MAX }
+    }
+
+    fn mk_fee_mint(bps: u16, maximum_fee: u64) -> Account {
+        mk_mint(&[ExtensionType::TransferFeeConfig], |state| {
+            let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+            let fee = TransferFee { epoch: 0.into(), maximum_fee: maximum_fee.into(), transfer_fee_basis_points: bps.into() };
+            config.older_transfer_fee = fee;
+            config.newer_transfer_fee = fee;
+        })
+    }
+
+    fn params(input_mint: Pubkey, output_mint: Pubkey, amount: u64) -> QuoteParams {
+        QuoteParams { swap_mode: SwapMode::ExactIn, amount, input_mint, output_mint }
+    }
+
+    #[test]
+    fn parses_transfer_fee_config() {
+        let meta = TokenMeta::from_mint_account(&mk_fee_mint(100, 5_000)).unwrap();
+
+        assert_eq!(meta.decimals, 6);
+        assert_eq!(meta.transfer_fee(0, 100_000), 1_000);
+        // capped at the maximum fee
+        assert_eq!(meta.transfer_fee(0, 10_000_000), 5_000);
+        assert_eq!(meta.transfer_hook, None);
+    }
+
+    #[test]
+    fn parses_plain_spl_mint() {
+        let mut data = vec![0u8; <spl_token::state::Mint as Pack>::LEN];
+        let mint = spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() };
+        Pack::pack(mint, &mut data).unwrap();
+        let account = Account { lamports: 1, data, owner: spl_token::id(), executable: false, rent_epoch: u64::MAX };
+
+        let meta = TokenMeta::from_mint_account(&account).unwrap();
+        assert_eq!(meta.decimals, 9);
+        assert_eq!(meta.transfer_fee(0, 1_000_000), 0);
+    }
+
+    #[test]
+    fn quote_applies_input_and_output_fees() {
+        let (input_mint, output_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut metas = TokenMetas::default();
+        metas.insert(input_mint, TokenMeta::from_mint_account(&mk_fee_mint(100, u64::MAX)).unwrap());
+        metas.insert(output_mint, TokenMeta::from_mint_account(&mk_fee_mint(50, u64::MAX)).unwrap());
+
+        let quote = metas.quote(&params(input_mint, output_mint, 10_000), 1, |p| Ok(Quote { in_amount: p.amount, out_amount: p.amount * 2, ..Quote::default() })).unwrap();
+
+        // 10_000 - 1% reaches the pool, 19_800 - 0.5% reaches the user
+        assert_eq!(quote.in_amount, 10_000);
+        assert_eq!(quote.out_amount, 19_701);
+    }
+
+    #[test]
+    fn quote_waits_for_the_slot_of_transfer_fee_mints() {
+        let (input_mint, output_mint, plain_mint) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut metas = TokenMetas::default();
+        metas.insert(input_mint, TokenMeta::from_mint_account(&mk_fee_mint(100, u64::MAX)).unwrap());
+        metas.insert(plain_mint, TokenMeta::default());
+
+        assert!(metas.quote(&params(input_mint, output_mint, 10_000), 0, |_| Ok(Quote::default())).is_err());
+        // the fee-less mints don't depend on the epoch
+        assert!(metas.quote(&params(plain_mint, output_mint, 10_000), 0, |_| Ok(Quote::default())).is_ok());
+    }
+
+    #[test]
+    fn quote_rejects_transfer_hook_mints() {
+        let (input_mint, output_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut metas = TokenMetas::default();
+        metas.insert(output_mint, TokenMeta { transfer_hook: Some(Pubkey::new_unique()), ..TokenMeta::default() });
+
+        assert!(metas.quote(&params(input_mint, output_mint, 10_000), 0, |_| Ok(Quote::default())).is_err());
+    }
+}
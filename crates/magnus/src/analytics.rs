@@ -0,0 +1,286 @@
+//! Per-market counters, fed by the ingest (state updates) and the strategy (quotes).
+//!
+//! They're kept apart from the markets themselves so reading them never contends
+//! with the quoting for longer than a copy.
+//!
+//! They also tell when a market can no longer be trusted (see [`StalenessCfg`]), i.e. a pmm
+//! that stopped updating would otherwise keep winning the quotes with an outdated price.
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use utoipa::ToSchema;
+
+use crate::adapters::{Quote, QuoteParams, token_meta::TokenMetas};
+
+#[derive(Clone, Debug, Default)]
+pub struct MarketStats {
+    pub first_update: Option<Instant>,
+    pub last_update: Option<Instant>,
+    pub last_update_slot: Option<u64>,
+    pub updates: u64,
+    pub quotes: u64,
+    pub failed_quotes: u64,
+    pub last_quote: Option<QuoteSample>,
+    // reset by the next success
+    pub consecutive_failed_quotes: u32,
+    pub consecutive_failed_updates: u32,
+    /// When the market was first checked for staleness, the reference until its first update.
+    pub tracked_since: Option<Instant>,
+    /// Restored from a snapshot and not yet caught up with the chain.
+    pub restored: bool,
+    /// The verdict of the last [`StalenessCfg::check`].
+    pub stale: Option<StaleReason>,
+}
+
+/// Why a market is left out of the routing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum StaleReason {
+    Restored,
+    UpdateAge,
+    UpdateSlotLag,
+    FailedUpdates,
+    FailedQuotes,
+}
+
+impl StaleReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StaleReason::Restored => "restored",
+            StaleReason::UpdateAge => "update_age",
+            StaleReason::UpdateSlotLag => "update_slot_lag",
+            StaleReason::FailedUpdates => "failed_updates",
+            StaleReason::FailedQuotes => "failed_quotes",
+        }
+    }
+}
+
+impl fmt::Display for StaleReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The thresholds past which a market is left out of the routing, `None` leaving the check out.
+///
+/// The update age and slot lag only apply to the markets expected to update on their own (see [`crate::adapters::amms::Amm::expects_regular_updates`]),
+/// a pool nobody swapped through is as current as it gets.
+#[derive(Clone, Debug, Default)]
+pub struct StalenessCfg {
+    pub max_update_age: Option<Duration>,
+    /// The slots the last update may lag behind the latest one seen.
+    pub max_update_slot_lag: Option<u64>,
+    pub max_failed_updates: Option<u32>,
+    pub max_failed_quotes: Option<u32>,
+}
+
+impl StalenessCfg {
+    /// The first threshold the market is past, as of `slot`.
+    pub fn check(&self, stats: &MarketStats, expects_regular_updates: bool, slot: u64) -> Option<StaleReason> {
+        let past = |value: u32, max: Option<u32>| max.is_some_and(|max| value >= max);
+
+        if past(stats.consecutive_failed_updates, self.max_failed_updates) {
+            return Some(StaleReason::FailedUpdates);
+        }
+        if past(stats.consecutive_failed_quotes, self.max_failed_quotes) {
+            return Some(StaleReason::FailedQuotes);
+        }
+        if !expects_regular_updates {
+            return None;
+        }
+
+        let since = stats.last_update.or(stats.tracked_since);
+        if let (Some(max), Some(since)) = (self.max_update_age, since)
+            && since.elapsed() > max
+        {
+            return Some(StaleReason::UpdateAge);
+        }
+        if let (Some(max), Some(last_update_slot)) = (self.max_update_slot_lag, stats.last_update_slot)
+            && slot.saturating_sub(last_update_slot) > max
+        {
+            return Some(StaleReason::UpdateSlotLag);
+        }
+
+        None
+    }
+}
+
+/// The raw amounts of the last successful quote, the fee taken out of them.
+#[derive(Clone, Copy, Debug)]
+pub struct QuoteSample {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub in_amount: u64,
+    pub out_amount: u64,
+}
+
+impl MarketStats {
+    /// The stats of a market restored from the snapshot taken at `slot`.
+    pub fn restored(slot: u64) -> Self {
+        MarketStats { last_update_slot: Some(slot), restored: true, ..MarketStats::default() }
+    }
+
+    pub fn record_update(&mut self, slot: Option<u64>) {
+        let now = Instant::now();
+        self.first_update.get_or_insert(now);
+        self.last_update = Some(now);
+        self.last_update_slot = slot.or(self.last_update_slot);
+        self.updates += 1;
+        self.consecutive_failed_updates = 0;
+        // the new state gets another go at quoting
+        self.consecutive_failed_quotes = 0;
+    }
+
+    pub fn record_failed_update(&mut self) {
+        self.consecutive_failed_updates += 1;
+    }
+
+    pub fn record_quote(&mut self, params: &QuoteParams, quote: &eyre::Result<Quote>) {
+        self.quotes += 1;
+
+        let quote = match quote {
+            Ok(quote) if quote.in_amount > 0 && quote.out_amount > 0 => quote,
+            _ => {
+                self.failed_quotes += 1;
+                self.consecutive_failed_quotes += 1;
+                return;
+            }
+        };
+        self.consecutive_failed_quotes = 0;
+
+        // the fee is added back on whichever side it was taken from, leaving the pool's own price
+        let (mut in_amount, mut out_amount) = (quote.in_amount, quote.out_amount);
+        if quote.fee_mint == params.input_mint {
+            in_amount = in_amount.saturating_sub(quote.fee_amount);
+        } else if quote.fee_mint == params.output_mint {
+            out_amount = out_amount.saturating_add(quote.fee_amount);
+        }
+
+        self.last_quote = Some(QuoteSample { input_mint: params.input_mint, output_mint: params.output_mint, in_amount, out_amount });
+    }
+
+    /// Why the market is left out of the routing, if it is.
+    pub fn stale_reason(&self) -> Option<StaleReason> {
+        self.restored.then_some(StaleReason::Restored).or(self.stale)
+    }
+
+    /// The time elapsed since the last update.
+    pub fn age(&self) -> Option<Duration> {
+        self.last_update.map(|last_update| last_update.elapsed())
+    }
+
+    /// The updates per second, between the first and the last one.
+    pub fn update_rate(&self) -> f64 {
+        match (self.first_update, self.last_update) {
+            (Some(first), Some(last)) if last > first => (self.updates - 1) as f64 / (last - first).as_secs_f64(),
+            _ => 0.0,
+        }
+    }
+
+    pub fn quote_success_rate(&self) -> Option<f64> {
+        (self.quotes > 0).then(|| (self.quotes - self.failed_quotes) as f64 / self.quotes as f64)
+    }
+
+    /// The price of `base_mint` in the other mint of the last quote, in ui amounts.
+    /// None until both mints' decimals are known.
+    pub fn mid_price(&self, base_mint: &Pubkey, token_metas: &TokenMetas) -> Option<f64> {
+        let sample = self.last_quote?;
+        let ui_amount = |mint: &Pubkey, amount: u64| token_metas.get(mint).map(|meta| amount as f64 / 10f64.powi(meta.decimals as i32));
+        let (ui_in, ui_out) = (ui_amount(&sample.input_mint, sample.in_amount)?, ui_amount(&sample.output_mint, sample.out_amount)?);
+
+        if *base_mint == sample.input_mint {
+            Some(ui_out / ui_in)
+        } else if *base_mint == sample.output_mint {
+            Some(ui_in / ui_out)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::eyre;
+
+    use super::*;
+    use crate::adapters::{SwapMode, token_meta::TokenMeta};
+
+    fn params(input_mint: Pubkey, output_mint: Pubkey) -> QuoteParams {
+        QuoteParams { swap_mode: SwapMode::ExactIn, amount: 1_000_000, input_mint, output_mint }
+    }
+
+    #[test]
+    fn counts_updates_and_quotes() {
+        let mut stats = MarketStats::default();
+        assert_eq!(stats.update_rate(), 0.0);
+        assert_eq!(stats.quote_success_rate(), None);
+
+        stats.record_update(Some(10));
+        stats.record_update(None);
+        assert_eq!(stats.updates, 2);
+        // the slot of the last update that came with one
+        assert_eq!(stats.last_update_slot, Some(10));
+
+        let (input_mint, output_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        stats.record_quote(&params(input_mint, output_mint), &Ok(Quote { in_amount: 1_000_000, out_amount: 2_000, ..Quote::default() }));
+        stats.record_quote(&params(input_mint, output_mint), &Err(eyre!("no liquidity")));
+        stats.record_quote(&params(input_mint, output_mint), &Ok(Quote::default()));
+        assert_eq!(stats.quote_success_rate(), Some(1.0 / 3.0));
+    }
+
+    #[test]
+    fn goes_stale_past_the_thresholds_and_recovers() {
+        let cfg = StalenessCfg { max_update_age: Some(Duration::from_secs(60)), max_update_slot_lag: Some(10), max_failed_updates: Some(2), max_failed_quotes: Some(2) };
+        let (input_mint, output_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let mut stats = MarketStats::default();
+        stats.record_update(Some(100));
+        assert_eq!(cfg.check(&stats, true, 110), None);
+        assert_eq!(cfg.check(&stats, true, 111), Some(StaleReason::UpdateSlotLag));
+        // a pool that only moves with the swaps is as current as it gets
+        assert_eq!(cfg.check(&stats, false, 111), None);
+
+        stats.last_update = Instant::now().checked_sub(Duration::from_secs(61));
+        assert_eq!(cfg.check(&stats, true, 100), Some(StaleReason::UpdateAge));
+
+        stats.record_update(Some(120));
+        stats.record_quote(&params(input_mint, output_mint), &Err(eyre!("no liquidity")));
+        assert_eq!(cfg.check(&stats, true, 120), None);
+        stats.record_quote(&params(input_mint, output_mint), &Err(eyre!("no liquidity")));
+        assert_eq!(cfg.check(&stats, false, 120), Some(StaleReason::FailedQuotes));
+
+        stats.record_failed_update();
+        stats.record_failed_update();
+        assert_eq!(cfg.check(&stats, false, 120), Some(StaleReason::FailedUpdates));
+
+        // the next update resets both
+        stats.record_update(Some(121));
+        assert_eq!(cfg.check(&stats, true, 121), None);
+        assert_eq!(StalenessCfg::default().check(&MarketStats::restored(1), true, 1_000), None);
+        assert_eq!(MarketStats::restored(1).stale_reason(), Some(StaleReason::Restored));
+    }
+
+    #[test]
+    fn mid_price_is_fee_less_and_decimals_adjusted() {
+        let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut token_metas = TokenMetas::default();
+        token_metas.insert(sol, TokenMeta { decimals: 9, ..TokenMeta::default() });
+        token_metas.insert(usdc, TokenMeta { decimals: 6, ..TokenMeta::default() });
+
+        let mut stats = MarketStats::default();
+        // 1.01 SOL in, 0.01 SOL of it to the fee, 150 USDC out
+        let quote = Quote { in_amount: 1_010_000_000, out_amount: 150_000_000, fee_amount: 10_000_000, fee_mint: sol, ..Quote::default() };
+        stats.record_quote(&params(sol, usdc), &Ok(quote));
+
+        assert_eq!(stats.mid_price(&sol, &token_metas), Some(150.0));
+        assert_eq!(stats.mid_price(&usdc, &token_metas), Some(1.0 / 150.0));
+        assert_eq!(stats.mid_price(&Pubkey::new_unique(), &token_metas), None);
+        // the decimals aren't known yet
+        assert_eq!(stats.mid_price(&sol, &TokenMetas::default()), None);
+    }
+}
@@ -1,25 +1,42 @@
 pub mod v1;
 
-use std::sync::mpsc;
+use std::{
+    sync::{Arc, Mutex, mpsc},
+    time::Duration,
+};
 
 use actix_web::{App, HttpResponse, HttpServer, dev::ServerHandle, middleware::Logger, web};
 #[cfg(feature = "metrics")]
 use metrics::counter;
+use solana_client::rpc_client::RpcClient;
+use tokio::sync::mpsc::UnboundedSender;
 use tracing_actix_web::TracingLogger;
 use utoipa::OpenApi;
 use utoipa_rapidoc::RapiDoc;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::{
-    api_server::v1::{quote, swap},
+    DisabledMarkets, Markets, MarketsStats, Oracle,
+    adapters::{aggregators::Aggregators, amms::registry::AmmRegistry, token_meta::TokenMetas},
+    api_server::v1::{markets, quote, swap},
+    executor::ExecutionParams,
+    ingest::SubscriptionUpdate,
     strategy::DispatchParams,
 };
 
-#[derive(Debug)]
 pub struct ApiServerCfg {
     pub host: String,
     pub workers: u16,
     pub request_tx: mpsc::Sender<DispatchParams>,
+    pub executor_tx: mpsc::Sender<ExecutionParams>,
+    pub markets: Markets,
+    pub disabled: DisabledMarkets,
+    pub stats: MarketsStats,
+    pub oracle: Oracle,
+    pub client: Arc<RpcClient>,
+    pub subscription_tx: UnboundedSender<SubscriptionUpdate>,
+    pub quote_deadline: Duration,
+    pub aggregators: Aggregators,
 }
 
 pub struct ApiServer {
@@ -30,16 +47,56 @@ pub struct ApiServer {
 #[derive(Clone)]
 pub struct ServerState {
     pub request_tx: mpsc::Sender<DispatchParams>,
+    // the aggregator swaps skip the strategy, they're signed and handed straight to the executor
+    pub executor_tx: mpsc::Sender<ExecutionParams>,
+    // the markets are hotloaded straight into the shared state, the ingestor only has to follow them
+    pub markets: Markets,
+    pub disabled: DisabledMarkets,
+    pub stats: MarketsStats,
+    // values the quotes in usd
+    pub oracle: Oracle,
+    // the decimals of the reserve mints, fetched the first time they're served
+    pub token_metas: Arc<Mutex<TokenMetas>>,
+    pub registry: Arc<AmmRegistry>,
+    pub client: Arc<RpcClient>,
+    pub subscription_tx: UnboundedSender<SubscriptionUpdate>,
+    // how long each source gets to quote when they're raced against each other
+    pub quote_deadline: Duration,
+    pub aggregators: Arc<Aggregators>,
 }
 
 impl ApiServer {
     pub fn new(cfg: ApiServerCfg) -> eyre::Result<ApiServer> {
         #[derive(Copy, Clone, OpenApi)]
-        #[openapi(paths(quote::quote_handler, swap::swap_handler))]
+        #[openapi(
+            paths(
+                quote::quote_handler,
+                swap::swap_handler,
+                markets::supported_handler,
+                markets::hotload_handler,
+                markets::remove_handler,
+                markets::disable_handler,
+                markets::enable_handler
+            ),
+            components(schemas(markets::SupportedMarket, markets::ReserveMint))
+        )]
         struct ApiDoc;
         let openapi = ApiDoc::openapi();
 
-        let state = ServerState { request_tx: cfg.request_tx.clone() };
+        let state = ServerState {
+            request_tx: cfg.request_tx.clone(),
+            executor_tx: cfg.executor_tx,
+            markets: cfg.markets,
+            disabled: cfg.disabled,
+            stats: cfg.stats,
+            oracle: cfg.oracle,
+            token_metas: Arc::new(Mutex::new(TokenMetas::default())),
+            registry: Arc::new(AmmRegistry::default()),
+            client: cfg.client,
+            subscription_tx: cfg.subscription_tx,
+            quote_deadline: cfg.quote_deadline,
+            aggregators: Arc::new(cfg.aggregators),
+        };
 
         let http_server = HttpServer::new(move || {
             App::new()
@@ -64,8 +121,11 @@ impl ApiServer {
                             .route("/swap", web::post().to(swap::swap_handler))
 
                             // miscellaneous
-                            .route("/markets/supported", web::get().to(|| async { HttpResponse::NotImplemented().finish() })) // analytics?
-                            .route("/markets/hotload", web::get().to(|| async { HttpResponse::NotImplemented().finish() })) // hotload new markets?
+                            .route("/markets/supported", web::get().to(markets::supported_handler))
+                            .route("/markets/hotload", web::post().to(markets::hotload_handler))
+                            .route("/markets/hotload/{market}", web::delete().to(markets::remove_handler))
+                            .route("/markets/hotload/{market}/disable", web::post().to(markets::disable_handler))
+                            .route("/markets/hotload/{market}/enable", web::post().to(markets::enable_handler))
                         )
                 )
         })
@@ -1,2 +1,3 @@
+pub mod markets;
 pub mod quote;
 pub mod swap;
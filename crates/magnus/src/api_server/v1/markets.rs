@@ -0,0 +1,297 @@
+use std::str::FromStr;
+
+use actix_web::{HttpResponse, web};
+use eyre::eyre;
+#[cfg(feature = "metrics")]
+use metrics::counter;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::{
+    adapters::{
+        amms::{Amm, registry::get_multiple_accounts},
+        token_meta::TokenMeta,
+    },
+    analytics::{MarketStats, StaleReason},
+    api_server::ServerState,
+    bootstrap,
+    ingest::SubscriptionUpdate,
+};
+
+/// Either the market addr alone, or the market as listed in the bootstrap file for the venues that need more than that.
+#[derive(Clone, Debug, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum HotloadUserParam {
+    Pubkey(String),
+    #[schema(value_type = Object)]
+    Market(serde_json::Value),
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketResponse {
+    pub market: String,
+    pub label: String,
+    // the accounts the market follows for updates
+    pub accounts: Vec<String>,
+    pub disabled: bool,
+}
+
+impl MarketResponse {
+    fn new(amm: &dyn Amm, disabled: bool) -> Self {
+        MarketResponse { market: amm.key().to_string(), label: amm.label(), accounts: amm.get_accounts_to_update().iter().map(|acc| acc.to_string()).collect(), disabled }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReserveMint {
+    pub mint: String,
+    // unknown until the mint account could be fetched
+    pub decimals: Option<u8>,
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportedMarket {
+    pub market: String,
+    pub label: String,
+    pub program_id: String,
+    pub reserve_mints: Vec<ReserveMint>,
+    pub is_active: bool,
+    // why the market is left out of the routing, if it is
+    pub stale_reason: Option<StaleReason>,
+    pub disabled: bool,
+    pub last_update_slot: Option<u64>,
+    // the milliseconds elapsed since the last update
+    pub last_update_age_ms: Option<u64>,
+    pub updates: u64,
+    // the updates per second
+    pub update_rate: f64,
+    pub quotes: u64,
+    pub quote_success_rate: Option<f64>,
+    // the price of the first reserve mint in the second one, as of the last quote
+    pub mid_price: Option<f64>,
+}
+
+impl SupportedMarket {
+    fn new(amm: &dyn Amm, disabled: bool, stats: &MarketStats) -> Self {
+        SupportedMarket {
+            market: amm.key().to_string(),
+            label: amm.label(),
+            program_id: amm.program_id().to_string(),
+            reserve_mints: amm.get_reserve_mints().iter().map(|mint| ReserveMint { mint: mint.to_string(), decimals: None }).collect(),
+            is_active: amm.is_active() && stats.stale_reason().is_none(),
+            stale_reason: stats.stale_reason(),
+            disabled,
+            last_update_slot: stats.last_update_slot,
+            last_update_age_ms: stats.age().map(|age| age.as_millis() as u64),
+            updates: stats.updates,
+            update_rate: stats.update_rate(),
+            quotes: stats.quotes,
+            quote_success_rate: stats.quote_success_rate(),
+            mid_price: None,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/markets/supported",
+    responses(
+        (status = 200, description = "Every tracked market along with its state and stats", body = Vec<SupportedMarket>)
+    )
+)]
+pub async fn supported_handler(state: web::Data<ServerState>) -> HttpResponse {
+    #[cfg(feature = "metrics")]
+    counter!("API HITS", "markets" => "/api/v1/markets/supported").increment(1);
+
+    // copied out so that none of the locks is held over the rpc call
+    let mut supported: Vec<(SupportedMarket, Vec<Pubkey>, MarketStats)> = {
+        let markets = state.markets.lock().unwrap();
+        let disabled = state.disabled.lock().unwrap();
+        let stats = state.stats.lock().unwrap();
+
+        markets
+            .values()
+            .map(|amm| {
+                let stats = stats.get(&amm.key()).cloned().unwrap_or_default();
+                (SupportedMarket::new(amm.as_ref(), disabled.contains(&amm.key()), &stats), amm.get_reserve_mints(), stats)
+            })
+            .collect()
+    };
+
+    let mut missing: Vec<Pubkey> = {
+        let token_metas = state.token_metas.lock().unwrap();
+        supported.iter().flat_map(|(_, mints, _)| mints).filter(|mint| **mint != Pubkey::default() && token_metas.get(mint).is_none()).copied().collect()
+    };
+    missing.sort();
+    missing.dedup();
+
+    if !missing.is_empty() {
+        let client = state.client.clone();
+        match web::block(move || get_multiple_accounts(&client, &missing)).await {
+            Ok(Ok(accounts)) => {
+                let mut token_metas = state.token_metas.lock().unwrap();
+                accounts.iter().for_each(|(mint, account)| match TokenMeta::from_mint_account(account) {
+                    Ok(meta) => token_metas.insert(*mint, meta),
+                    Err(e) => warn!("unable to parse mint {}: {}", mint, e),
+                });
+            }
+            Ok(Err(e)) => warn!("unable to fetch the reserve mints: {}", e),
+            Err(e) => warn!("unable to fetch the reserve mints: {}", e),
+        }
+    }
+
+    let token_metas = state.token_metas.lock().unwrap();
+    supported.iter_mut().for_each(|(market, mints, stats)| {
+        market.reserve_mints.iter_mut().zip(mints.iter()).for_each(|(reserve_mint, mint)| reserve_mint.decimals = token_metas.get(mint).map(|meta| meta.decimals));
+        market.mid_price = mints.first().and_then(|base_mint| stats.mid_price(base_mint, &token_metas));
+    });
+
+    let mut supported: Vec<SupportedMarket> = supported.into_iter().map(|(market, _, _)| market).collect();
+    supported.sort_by(|a, b| (&a.label, &a.market).cmp(&(&b.label, &b.market)));
+
+    HttpResponse::Ok().json(supported)
+}
+
+pub fn sanity_check_hotload_param(params: &HotloadUserParam) -> eyre::Result<(Pubkey, Option<serde_json::Value>)> {
+    match params {
+        HotloadUserParam::Pubkey(pubkey) => Ok((Pubkey::from_str(pubkey).map_err(|_| eyre!("Invalid pubkey"))?, None)),
+        HotloadUserParam::Market(market) => {
+            let pubkey = market.get("pubkey").and_then(|pubkey| pubkey.as_str()).ok_or_else(|| eyre!("no pubkey provided"))?;
+            Ok((Pubkey::from_str(pubkey).map_err(|_| eyre!("Invalid pubkey"))?, Some(market.clone())))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/markets/hotload",
+    request_body = HotloadUserParam,
+    responses(
+        (status = 200, description = "Market loaded and followed for updates", body = MarketResponse),
+        (status = 400, description = "Invalid or unsupported market"),
+        (status = 500, description = "Internal Server Error")
+    )
+)]
+pub async fn hotload_handler(params: web::Json<HotloadUserParam>, state: web::Data<ServerState>) -> HttpResponse {
+    #[cfg(feature = "metrics")]
+    counter!("API HITS", "markets" => "/api/v1/markets/hotload").increment(1);
+
+    let market = match sanity_check_hotload_param(&params) {
+        Err(e) => return HttpResponse::BadRequest().json(json!({"error": e.to_string()})),
+        Ok(market) => market,
+    };
+
+    // the registry goes through the blocking rpc client
+    let (registry, client) = (state.registry.clone(), state.client.clone());
+    let (mut amms, account_map) = match web::block(move || registry.load_with_account_map(&client, &[market])).await {
+        Ok(Ok(loaded)) => loaded,
+        Ok(Err(e)) => return HttpResponse::BadRequest().json(json!({"error": e.to_string()})),
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": e.to_string()})),
+    };
+
+    let Some(amm) = amms.pop() else {
+        return HttpResponse::InternalServerError().json(json!({"error": "no market loaded"}));
+    };
+    let response = MarketResponse::new(amm.as_ref(), state.disabled.lock().unwrap().contains(&amm.key()));
+
+    bootstrap::register(&state.markets, vec![amm]);
+    if state.subscription_tx.send(SubscriptionUpdate::Track(account_map)).is_err() {
+        warn!("market {} hotloaded without an ingestor to follow it", response.market);
+    }
+    info!("hotloaded market {} ({})", response.market, response.label);
+
+    HttpResponse::Ok().json(response)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/markets/hotload/{market}",
+    params(("market" = String, Path, description = "The market addr")),
+    responses(
+        (status = 200, description = "Market removed", body = MarketResponse),
+        (status = 400, description = "Invalid market addr"),
+        (status = 404, description = "Unknown market")
+    )
+)]
+pub async fn remove_handler(market: web::Path<String>, state: web::Data<ServerState>) -> HttpResponse {
+    #[cfg(feature = "metrics")]
+    counter!("API HITS", "markets" => "/api/v1/markets/hotload").increment(1);
+
+    let Ok(key) = Pubkey::from_str(&market) else {
+        return HttpResponse::BadRequest().json(json!({"error": "Invalid pubkey"}));
+    };
+
+    let Some(amm) = state.markets.lock().unwrap().remove(&key) else {
+        return HttpResponse::NotFound().json(json!({"error": "unknown market"}));
+    };
+    let disabled = state.disabled.lock().unwrap().remove(&key);
+    state.stats.lock().unwrap().remove(&key);
+
+    if state.subscription_tx.send(SubscriptionUpdate::Untrack(amm.get_accounts_to_update())).is_err() {
+        warn!("market {} removed without an ingestor to unfollow it", key);
+    }
+    info!("removed market {} ({})", key, amm.label());
+
+    HttpResponse::Ok().json(MarketResponse::new(amm.as_ref(), disabled))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/markets/hotload/{market}/disable",
+    params(("market" = String, Path, description = "The market addr")),
+    responses(
+        (status = 200, description = "Market left out of the routing, still followed for updates", body = MarketResponse),
+        (status = 400, description = "Invalid market addr"),
+        (status = 404, description = "Unknown market")
+    )
+)]
+pub async fn disable_handler(market: web::Path<String>, state: web::Data<ServerState>) -> HttpResponse {
+    #[cfg(feature = "metrics")]
+    counter!("API HITS", "markets" => "/api/v1/markets/hotload").increment(1);
+
+    set_disabled(&market, &state, true)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/markets/hotload/{market}/enable",
+    params(("market" = String, Path, description = "The market addr")),
+    responses(
+        (status = 200, description = "Market routed through again", body = MarketResponse),
+        (status = 400, description = "Invalid market addr"),
+        (status = 404, description = "Unknown market")
+    )
+)]
+pub async fn enable_handler(market: web::Path<String>, state: web::Data<ServerState>) -> HttpResponse {
+    #[cfg(feature = "metrics")]
+    counter!("API HITS", "markets" => "/api/v1/markets/hotload").increment(1);
+
+    set_disabled(&market, &state, false)
+}
+
+fn set_disabled(market: &str, state: &ServerState, disabled: bool) -> HttpResponse {
+    let Ok(key) = Pubkey::from_str(market) else {
+        return HttpResponse::BadRequest().json(json!({"error": "Invalid pubkey"}));
+    };
+
+    let markets = state.markets.lock().unwrap();
+    let Some(amm) = markets.get(&key) else {
+        return HttpResponse::NotFound().json(json!({"error": "unknown market"}));
+    };
+
+    let mut disabled_markets = state.disabled.lock().unwrap();
+    if disabled {
+        disabled_markets.insert(key);
+    } else {
+        disabled_markets.remove(&key);
+    }
+    info!("market {} disabled: {}", key, disabled);
+
+    HttpResponse::Ok().json(MarketResponse::new(amm.as_ref(), disabled))
+}
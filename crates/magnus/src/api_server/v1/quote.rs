@@ -1,19 +1,18 @@
-use std::str::FromStr;
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
 use actix_web::{HttpResponse, web};
 #[cfg(feature = "metrics")]
 use metrics::counter;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use solana_sdk::pubkey::Pubkey;
 use utoipa::ToSchema;
 
 use crate::{
-    adapters::{
-        IntQuoteResponse, QuoteParams, SwapMode,
-        aggregators::{Aggregator, dflow::DFlow, jupiter::Jupiter},
-        amms::Target,
-    },
+    adapters::{BestQuoteResponse, IntQuoteResponse, QuoteParams, SourceQuote, SwapMode, amms::Target},
     api_server::ServerState,
     strategy::{DispatchParams, DispatchResponse},
 };
@@ -29,16 +28,25 @@ pub struct QuoteUserParam {
     target: Target,
 }
 
+/// The winning quote along with every source's result for the `all` target, the quote alone for the others.
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum QuoteResponse {
+    Best(BestQuoteResponse),
+    Quote(IntQuoteResponse),
+}
+
 #[utoipa::path(
     get,
     path = "/api/v1/quote",
     params(
         ("inputMint" = String, description = "The input token mint addr"),
         ("outputMint" = String, description = "The output token mint addr"),
-        ("amount" = u64, description = "The amount to quote")
+        ("amount" = u64, description = "The amount to quote"),
+        ("target" = Option<Target>, description = "The source(s) to quote through, the amms if left out")
     ),
     responses(
-        (status = 200, description = "Successfully retrieved the quote", body = IntQuoteResponse),
+        (status = 200, description = "Successfully retrieved the quote, along with every source's result for the `all` target", body = QuoteResponse),
         (status = 500, description = "Internal Server Error")
     )
 )]
@@ -52,53 +60,90 @@ pub async fn quote_handler(params: web::Query<QuoteUserParam>, state: web::Data<
     };
 
     match params.target {
+        Target::All => {
+            let quote_param = QuoteParams { input_mint, output_mint, amount: params.amount, swap_mode: SwapMode::ExactIn };
+            let deadline = state.quote_deadline;
+
+            let aggregators =
+                futures::future::join_all(state.aggregators.all().into_iter().map(|(target, agg)| timed(target, deadline, async move { agg.quote(&quote_param).await })));
+            let (amms, aggregators) = futures::future::join(timed(Target::AMMs, deadline, quote_amms(&state, quote_param)), aggregators).await;
+            let mut sources: Vec<SourceQuote> = std::iter::once(amms).chain(aggregators).collect();
+
+            let oracle = state.oracle.lock().unwrap();
+            sources.iter_mut().filter_map(|source| source.quote.as_mut()).for_each(|quote| oracle.annotate(quote));
+            let best = sources.iter().filter_map(|source| source.quote.as_ref()).filter(|quote| quote.out_amount > 0).max_by_key(|quote| quote.out_amount).cloned();
+
+            match best {
+                Some(best) => HttpResponse::Ok().json(QuoteResponse::Best(BestQuoteResponse { best, sources })),
+                None => HttpResponse::InternalServerError().json(json!({"error": "no source came up with a quote", "sources": sources})),
+            }
+        }
         Target::Aggregators => {
-            let aggregators: Vec<Box<dyn Aggregator>> = vec![Box::new(Jupiter {}), Box::new(DFlow {})];
             let quote_param = QuoteParams { input_mint, output_mint, amount: params.amount, swap_mode: SwapMode::ExactIn };
 
-            let handles: Vec<_> = aggregators.into_iter().map(|agg| tokio::spawn(async move { agg.quote(&quote_param.clone()).await })).collect();
-            let res = futures::future::join_all(handles).await;
-            let best_quote = res.into_iter().filter_map(|handle_result| handle_result.ok().and_then(|quote_result| quote_result.ok())).max_by_key(|quote| quote.out_amount);
+            let quotes = state.aggregators.all().into_iter().map(|(_, agg)| async move { agg.quote(&quote_param).await });
+            let res = futures::future::join_all(quotes).await;
+            let best_quote = res.into_iter().filter_map(|quote_result| quote_result.ok()).max_by_key(|quote| quote.out_amount);
 
             match best_quote {
-                Some(quote) => HttpResponse::Ok().json(quote),
+                Some(mut quote) => {
+                    state.oracle.lock().unwrap().annotate(&mut quote);
+                    HttpResponse::Ok().json(QuoteResponse::Quote(quote))
+                }
                 None => HttpResponse::InternalServerError().json(json!({"error": "err acquiring aggregators market data"})),
             }
         }
-        Target::Jupiter => {
+        Target::Jupiter | Target::DFlow | Target::Okx | Target::Raydium => {
             let param = QuoteParams { input_mint, output_mint, amount: params.amount, swap_mode: SwapMode::ExactIn };
-
-            match (Jupiter {}.quote(&param).await) {
-                Ok(jup) => HttpResponse::Ok().json(jup),
+            let Some(aggregator) = state.aggregators.get(params.target) else {
+                return HttpResponse::BadRequest().json(json!({"error": "not an aggregator"}));
+            };
+
+            match aggregator.quote(&param).await {
+                Ok(mut quote) => {
+                    state.oracle.lock().unwrap().annotate(&mut quote);
+                    HttpResponse::Ok().json(QuoteResponse::Quote(quote))
+                }
                 Err(err) => HttpResponse::InternalServerError().json(json!({"error": err.to_string()})),
             }
         }
-        Target::DFlow => {
-            let param = QuoteParams { input_mint, output_mint, amount: params.amount, swap_mode: SwapMode::ExactIn };
-
-            match (DFlow {}.quote(&param).await) {
-                Ok(dflow) => HttpResponse::Ok().json(dflow),
-                Err(err) => HttpResponse::InternalServerError().json(json!({"error": err.to_string()})),
+        Target::AMMs => match quote_amms(&state, QuoteParams { swap_mode: SwapMode::ExactIn, amount: params.amount, input_mint, output_mint }).await {
+            Ok(mut quote) => {
+                state.oracle.lock().unwrap().annotate(&mut quote);
+                HttpResponse::Ok().json(QuoteResponse::Quote(quote))
             }
-        }
-        Target::AMMs => {
-            let (response_tx, response_rx) = oneshot::channel::<DispatchResponse>();
+            Err(err) => HttpResponse::InternalServerError().json(json!({"error": err.to_string()})),
+        },
+    }
+}
 
-            let dispatch = DispatchParams::Quote { params: QuoteParams { swap_mode: SwapMode::ExactIn, amount: params.amount, input_mint, output_mint }, response_tx };
+/// Quotes through the internal strategy.
+async fn quote_amms(state: &ServerState, params: QuoteParams) -> eyre::Result<IntQuoteResponse> {
+    let (response_tx, response_rx) = oneshot::channel::<DispatchResponse>();
 
-            state.request_tx.send(dispatch).expect("send invalid transmitter req");
-            tracing::info!("sent from `API Server::quote` towards `Strategy`");
-            let response = response_rx.recv();
-            tracing::info!("received from `Strategy`");
+    state.request_tx.send(DispatchParams::Quote { params, response_tx }).map_err(|_| eyre::eyre!("no strategy to quote through"))?;
+    tracing::info!("sent from `API Server::quote` towards `Strategy`");
+    let response = response_rx.await.map_err(|_| eyre::eyre!("no response"))?;
+    tracing::info!("received from `Strategy`");
 
-            match response {
-                Ok(response) => HttpResponse::Ok().json(response),
-                Err(_) => HttpResponse::InternalServerError().json(json!({"error": "no response"})),
-            }
-        }
+    match response {
+        DispatchResponse::Quote(quote) => Ok(quote),
+        DispatchResponse::Swap(_) => eyre::bail!("unexpected swap response"),
     }
 }
 
+/// Runs a single source's quote, giving up on it past the deadline.
+async fn timed(source: Target, deadline: Duration, quote: impl Future<Output = eyre::Result<IntQuoteResponse>>) -> SourceQuote {
+    let start = Instant::now();
+    let (quote, error) = match tokio::time::timeout(deadline, quote).await {
+        Ok(Ok(quote)) => (Some(quote), None),
+        Ok(Err(e)) => (None, Some(e.to_string())),
+        Err(_) => (None, Some(format!("no quote within {}ms", deadline.as_millis()))),
+    };
+
+    SourceQuote { source, quote, error, elapsed_ms: start.elapsed().as_millis() as u64 }
+}
+
 fn sanity_check_quote_param(params: &QuoteUserParam) -> eyre::Result<(Pubkey, Pubkey)> {
     // sanity check the mints are actual valid pubkeys
     let keys = match (Pubkey::from_str(&params.input_mint).is_err(), Pubkey::from_str(&params.output_mint).is_err()) {
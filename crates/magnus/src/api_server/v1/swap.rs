@@ -1,24 +1,27 @@
+use std::str::FromStr;
+
 use actix_web::{HttpResponse, web};
 #[cfg(feature = "metrics")]
 use metrics::counter;
 use serde::Deserialize;
 use serde_json::json;
-use solana_sdk::signature::Keypair;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
 use tracing::info;
 use utoipa::ToSchema;
 
 use crate::{
-    adapters::{SwapParams, amms::Target},
+    adapters::{SwapMode, SwapParams, amms::Target},
     api_server::ServerState,
+    executor::ExecutionParams,
     strategy::{DispatchParams, DispatchResponse},
 };
 
 #[derive(Clone, Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SwapUserParam {
-    _input_mint: String,
-    _output_mint: String,
-    _amount: u64,
+    input_mint: String,
+    output_mint: String,
+    amount: u64,
     _min_amount_out: Option<u64>,
     privkey: String,
 
@@ -26,8 +29,11 @@ pub struct SwapUserParam {
     target: Target,
 }
 
-pub fn sanity_check_swap_param(_: &SwapUserParam) -> eyre::Result<()> {
-    Ok(())
+pub fn sanity_check_swap_param(params: &SwapUserParam) -> eyre::Result<(Pubkey, Pubkey)> {
+    let input_mint = Pubkey::from_str(&params.input_mint).map_err(|_| eyre::eyre!("Invalid inputMint"))?;
+    let output_mint = Pubkey::from_str(&params.output_mint).map_err(|_| eyre::eyre!("Invalid outputMint"))?;
+
+    Ok((input_mint, output_mint))
 }
 
 #[utoipa::path(
@@ -42,9 +48,10 @@ pub async fn swap_handler(params: web::Json<SwapUserParam>, state: web::Data<Ser
     #[cfg(feature = "metrics")]
     counter!("API HITS", "swaps" => "/api/v1/swap").increment(1);
 
-    if let Err(e) = sanity_check_swap_param(&params) {
-        return HttpResponse::BadRequest().json(json!({"error": e.to_string()}));
-    }
+    let (input_mint, output_mint) = match sanity_check_swap_param(&params) {
+        Err(e) => return HttpResponse::BadRequest().json(json!({"error": e.to_string()})),
+        Ok(keys) => keys,
+    };
 
     let keypair = match read_keypair(&params.privkey) {
         Ok(k) => k,
@@ -52,12 +59,36 @@ pub async fn swap_handler(params: web::Json<SwapUserParam>, state: web::Data<Ser
     };
 
     info!(?keypair);
+    let swap_params =
+        SwapParams { swap_mode: SwapMode::ExactIn, amount: params.amount, input_mint, output_mint, token_transfer_authority: keypair.pubkey(), ..SwapParams::default() };
 
     match params.target {
-        Target::Aggregators | Target::Jupiter | Target::DFlow => HttpResponse::NotImplemented().json(serde_json::json!({"error": "can't swap through the aggregators"})),
+        Target::Aggregators => HttpResponse::BadRequest().json(serde_json::json!({"error": "pick the aggregator to swap through"})),
+        Target::All => HttpResponse::BadRequest().json(serde_json::json!({"error": "quote with the `all` target first, then swap through its winner"})),
+        Target::Jupiter | Target::DFlow | Target::Okx | Target::Raydium => {
+            let Some(aggregator) = state.aggregators.get(params.target) else {
+                return HttpResponse::BadRequest().json(json!({"error": "not an aggregator"}));
+            };
+            let swap = match aggregator.swap(&swap_params, &keypair).await {
+                Ok(swap) => swap,
+                Err(e) => return HttpResponse::InternalServerError().json(json!({"error": e.to_string()})),
+            };
+
+            let (response_tx, response_rx) = oneshot::channel();
+            if state.executor_tx.send(ExecutionParams::Signed { swap, response_tx }).is_err() {
+                return HttpResponse::InternalServerError().json(json!({"error": "no executor to submit through"}));
+            }
+            tracing::info!("sent from `API Server::swap` towards `Executor`");
+
+            match response_rx.await {
+                Ok(Ok(response)) => HttpResponse::Ok().json(response),
+                Ok(Err(e)) => HttpResponse::InternalServerError().json(json!({"error": e.to_string()})),
+                Err(_) => HttpResponse::InternalServerError().json(json!({"error": "no response"})),
+            }
+        }
         Target::AMMs => {
             let (response_tx, response_rx) = oneshot::channel::<DispatchResponse>();
-            let dispatch = DispatchParams::Swap { params: SwapParams::default(), response_tx };
+            let dispatch = DispatchParams::Swap { params: swap_params, signer: keypair, response_tx };
 
             state.request_tx.send(dispatch).expect("send invalid transmitter req");
             tracing::info!("sent from `API Server::swap` towards `Strategy`");
@@ -65,6 +96,7 @@ pub async fn swap_handler(params: web::Json<SwapUserParam>, state: web::Data<Ser
             tracing::info!("received from `Executor`");
 
             match response {
+                Ok(DispatchResponse::Swap(response)) if response.error.is_some() => HttpResponse::InternalServerError().json(response),
                 Ok(response) => HttpResponse::Ok().json(response),
                 Err(_) => HttpResponse::InternalServerError().json(json!({"error": "no response"})),
             }
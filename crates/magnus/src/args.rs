@@ -20,6 +20,39 @@ pub struct Args {
     #[arg(long, env = "BOOTSTRAP_FILE", default_value = "cfg/payloads/pmms.json")]
     pub bootstrap_file: String,
 
+    #[arg(long, env = "DISCOVERY_FILE")]
+    pub discovery_file: Option<String>,
+
+    #[arg(long, env = "ORACLE_FILE")]
+    pub oracle_file: Option<String>,
+
+    #[arg(long, env = "AGGREGATORS_FILE")]
+    pub aggregators_file: Option<String>,
+
+    #[arg(long, env = "SNAPSHOT", requires = "snapshot_file")]
+    pub snapshot: bool,
+
+    #[arg(long, env = "SNAPSHOT_FILE")]
+    pub snapshot_file: Option<String>,
+
+    #[arg(long, env = "SNAPSHOT_INTERVAL_SECS", default_value = "60", value_parser = value_parser!(u64).range(1..))]
+    pub snapshot_interval_secs: u64,
+
+    #[arg(long, env = "MAX_REPLAY_SLOTS", default_value = "300")]
+    pub max_replay_slots: u64,
+
+    #[arg(long, env = "MAX_UPDATE_AGE_SECS", default_value = "30")]
+    pub max_update_age_secs: u64,
+
+    #[arg(long, env = "MAX_UPDATE_SLOT_LAG", default_value = "75")]
+    pub max_update_slot_lag: u64,
+
+    #[arg(long, env = "MAX_FAILED_UPDATES", default_value = "5")]
+    pub max_failed_updates: u32,
+
+    #[arg(long, env = "MAX_FAILED_QUOTES", default_value = "10")]
+    pub max_failed_quotes: u32,
+
     #[arg(long, env = "API_SERVER_HOST", default_value = "0.0.0.0:19000")]
     pub api_server_host: String,
 
@@ -41,4 +74,16 @@ pub struct Args {
         value_parser = value_parser!(u16).range(1..)
     )]
     pub metrics_server_workers: u16,
+
+    #[arg(long, env = "MAX_SLOT_TOLERANCE", default_value = "32")]
+    pub max_slot_tolerance: u64,
+
+    #[arg(long, env = "SWAP_DEADLINE_SECS", default_value = "30")]
+    pub swap_deadline_secs: u64,
+
+    #[arg(long, env = "QUOTE_DEADLINE_MS", default_value = "1500")]
+    pub quote_deadline_ms: u64,
+
+    #[arg(long, env = "HOP_SLIPPAGE_BPS", default_value = "50", value_parser = value_parser!(u16).range(0..=10_000))]
+    pub hop_slippage_bps: u16,
 }
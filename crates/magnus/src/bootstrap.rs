@@ -1,47 +1,41 @@
 use std::{collections::HashMap, fs, str::FromStr, sync::Mutex};
 
 use eyre::eyre;
-use magnus_shared::Dex;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
 
 use crate::{
     AccountMap, Markets, StateAccountToMarket,
-    adapters::amms::{
-        Amm,
-        humidifi::{Humidifi, HumidifiCfg},
-    },
+    adapters::amms::{Amm, registry::AmmRegistry},
 };
 
+/// Loads the markets listed in the bootstrap file, each built by the adapter registered for its owner.
 pub fn load(file: &str, client: &RpcClient) -> eyre::Result<Vec<Box<dyn Amm>>> {
     let json = fs::read_to_string(file)?;
 
     let cfgs: serde_json::Value = serde_json::from_str(&json)?;
 
-    let pmms = if let serde_json::Value::Array(items) = &cfgs {
+    let markets = if let serde_json::Value::Array(items) = &cfgs {
         items
             .iter()
-            .map(|item| -> Box<dyn Amm> {
-                let dex = Dex::from_str(item.get("dex").and_then(|dex| dex.as_str()).expect("no DEX provided")).map_err(|e| eyre!(e)).expect("");
-
-                let init: Box<dyn Amm> = match dex {
-                    Dex::HumidiFi => {
-                        let cfg = HumidifiCfg::try_from(item).map_err(|e| eyre!(e)).expect("");
-                        let amm = Humidifi::new(cfg, client).expect("unable to initialise humidifi");
-
-                        Box::new(amm)
-                    }
-                    _ => panic!("Unsupported DEX: {}", dex),
-                };
-
-                init
+            .map(|item| {
+                let pubkey = item.get("pubkey").and_then(|pubkey| pubkey.as_str()).ok_or_else(|| eyre!("no pubkey provided"))?;
+                Ok((Pubkey::from_str(pubkey)?, Some(item.clone())))
             })
-            .collect()
+            .collect::<eyre::Result<Vec<_>>>()?
     } else {
         vec![]
     };
 
-    Ok(pmms)
+    AmmRegistry::default().load(client, &markets)
+}
+
+/// Adds the markets to the tracked ones, replacing any market with the same key.
+pub fn register(markets: &Markets, amms: Vec<Box<dyn Amm>>) {
+    let mut markets = markets.lock().unwrap();
+    amms.into_iter().for_each(|amm| {
+        markets.insert(amm.key(), amm);
+    });
 }
 
 /// Creates a mapping from each account address to its parent market key.
@@ -88,4 +82,14 @@ mod tests {
         let markets = into_markets(pmms);
         assert!(markets.lock().unwrap().is_empty());
     }
+
+    #[test]
+    fn test_register_replaces_by_key() {
+        let markets = into_markets(vec![]);
+        let stake_pool = crate::adapters::amms::spl_stake_pool::SplStakePool::default();
+
+        register(&markets, vec![Box::new(stake_pool.clone())]);
+        register(&markets, vec![Box::new(stake_pool)]);
+        assert_eq!(markets.lock().unwrap().len(), 1);
+    }
 }
@@ -18,6 +18,7 @@ use crate::curves::{
     constant_product::ConstantProductCurve,
     fees::Fees,
     offset::OffsetCurve,
+    stable::StableCurve,
 };
 
 /// Curve types supported by the token-swap program.
@@ -29,7 +30,7 @@ pub enum CurveType {
     ConstantPrice,
     /// Offset curve, like Uniswap, but the token B side has a faked offset
     Offset,
-    /// ..
+    /// Stableswap curve, invariant from curve.fi, flat near parity with an amplification coefficient
     Stable,
 }
 
@@ -163,7 +164,7 @@ impl Pack for SwapCurve {
                 CurveType::ConstantProduct => Arc::new(ConstantProductCurve::unpack_from_slice(calculator)?),
                 CurveType::ConstantPrice => Arc::new(ConstantPriceCurve::unpack_from_slice(calculator)?),
                 CurveType::Offset => Arc::new(OffsetCurve::unpack_from_slice(calculator)?),
-                CurveType::Stable => unimplemented!("not yet implemented."),
+                CurveType::Stable => Arc::new(StableCurve::unpack_from_slice(calculator)?),
             },
         })
     }
@@ -193,6 +194,7 @@ impl TryFrom<u8> for CurveType {
             0 => Ok(CurveType::ConstantProduct),
             1 => Ok(CurveType::ConstantPrice),
             2 => Ok(CurveType::Offset),
+            3 => Ok(CurveType::Stable),
             _ => Err(ProgramError::InvalidAccountData),
         }
     }
@@ -219,6 +221,18 @@ mod test {
         assert_eq!(swap_curve, unpacked);
     }
 
+    #[test]
+    fn pack_stable_swap_curve() {
+        let curve = StableCurve { amp: 100 };
+        let swap_curve = SwapCurve { curve_type: CurveType::Stable, calculator: Arc::new(curve) };
+
+        let mut packed = [0u8; SwapCurve::LEN];
+        Pack::pack_into_slice(&swap_curve, &mut packed[..]);
+        let unpacked = SwapCurve::unpack_from_slice(&packed).unwrap();
+        assert_eq!(swap_curve, unpacked);
+        assert_eq!(unpacked.curve_type, CurveType::Stable);
+    }
+
     #[test]
     fn constant_product_trade_fee() {
         // calculation on https://github.com/solana-labs/solana-program-library/issues/341
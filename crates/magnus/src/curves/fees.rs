@@ -86,6 +86,14 @@ impl Fees {
         calculate_fee(trading_tokens, u128::from(self.trade_fee_numerator), u128::from(self.trade_fee_denominator))
     }
 
+    /// Calculate the trading fee in trading tokens, rounded up rather than to a minimum of one token
+    pub fn trading_fee_ceil(&self, trading_tokens: u128) -> Option<u128> {
+        if self.trade_fee_numerator == 0 || trading_tokens == 0 {
+            return Some(0);
+        }
+        ceil_div(trading_tokens.checked_mul(u128::from(self.trade_fee_numerator))?, u128::from(self.trade_fee_denominator))
+    }
+
     /// Calculate the owner trading fee in trading tokens
     pub fn owner_trading_fee(&self, trading_tokens: u128) -> Option<u128> {
         calculate_fee(trading_tokens, u128::from(self.owner_trade_fee_numerator), u128::from(self.owner_trade_fee_denominator))
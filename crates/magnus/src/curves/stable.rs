@@ -0,0 +1,363 @@
+//! The curve.fi invariant calculator.
+
+use anchor_lang::solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+};
+use arrayref::{array_mut_ref, array_ref};
+use spl_math::{precise_number::PreciseNumber, uint::U256};
+
+use crate::{
+    curves::calculator::{CurveCalculator, DynPack, RoundDirection, SwapWithoutFeesResult, TradeDirection},
+    error::SwapError,
+};
+
+/// Minimum amplification coefficient
+const MIN_AMP: u64 = 1;
+
+/// Maximum amplification coefficient
+const MAX_AMP: u64 = 1_000_000;
+
+/// Number of coins in the pool
+const N_COINS: u8 = 2;
+
+/// Number of coins squared
+const N_COINS_SQUARED: u8 = 4;
+
+/// Maximum number of Newton iterations
+const ITERATIONS: u8 = 32;
+
+/// StableCurve struct implementing CurveCalculator
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StableCurve {
+    /// Amplifier constant
+    pub amp: u64,
+}
+
+/// Calculates A for deriving D
+///
+/// Per discussion with the designer and writer of stable curves, this A is not
+/// the same as the A from the whitepaper, it's actually `A * n**(n-1)`, so when
+/// you set A, you actually set `A * n**(n-1)`. This is because `D**n / prod(x)`
+/// loses precision with a huge A value.
+fn compute_a(amp: u64) -> Option<u64> {
+    amp.checked_mul(N_COINS as u64)
+}
+
+/// Returns `a` to the power of `b`
+fn checked_u8_power(a: &U256, b: u8) -> Option<U256> {
+    let mut result = *a;
+    for _ in 1..b {
+        result = result.checked_mul(*a)?;
+    }
+    Some(result)
+}
+
+/// Returns `a` times `b`
+fn checked_u8_mul(a: &U256, b: u8) -> Option<U256> {
+    let mut result = *a;
+    for _ in 1..b {
+        result = result.checked_add(*a)?;
+    }
+    Some(result)
+}
+
+/// Returns true if the values differ by at most 1
+fn almost_equal(a: &U256, b: &U256) -> Option<bool> {
+    if a > b {
+        Some(a.checked_sub(*b)? <= U256::one())
+    } else {
+        Some(b.checked_sub(*a)? <= U256::one())
+    }
+}
+
+/// A single Newton step towards D:
+/// `d = (leverage * sum_x + d_p * n_coins) * d / ((leverage - 1) * d + (n_coins + 1) * d_p)`
+fn calculate_step(initial_d: &U256, leverage: u64, sum_x: u128, d_product: &U256) -> Option<U256> {
+    let leverage_mul = U256::from(leverage).checked_mul(sum_x.into())?;
+    let d_p_mul = checked_u8_mul(d_product, N_COINS)?;
+
+    let l_val = leverage_mul.checked_add(d_p_mul)?.checked_mul(*initial_d)?;
+
+    let leverage_sub = initial_d.checked_mul((leverage.checked_sub(1)?).into())?;
+    let n_coins_sum = checked_u8_mul(d_product, N_COINS.checked_add(1)?)?;
+
+    let r_val = leverage_sub.checked_add(n_coins_sum)?;
+
+    l_val.checked_div(r_val)
+}
+
+/// Compute the stable swap invariant (D)
+///
+/// Equation:
+///
+/// `A * sum(x_i) * n**n + D = A * D * n**n + D**(n+1) / (n**n * prod(x_i))`
+fn compute_d(leverage: u64, amount_a: u128, amount_b: u128) -> Option<u128> {
+    let sum_x = amount_a.checked_add(amount_b)?; // sum(x_i), a.k.a S
+    if sum_x == 0 {
+        return Some(0);
+    }
+
+    let amount_a_times_coins = checked_u8_mul(&U256::from(amount_a), N_COINS)?;
+    let amount_b_times_coins = checked_u8_mul(&U256::from(amount_b), N_COINS)?;
+
+    // Newton's method to approximate D
+    let mut d_previous: U256;
+    let mut d: U256 = sum_x.into();
+    for _ in 0..ITERATIONS {
+        let mut d_product = d;
+        d_product = d_product.checked_mul(d)?.checked_div(amount_a_times_coins)?;
+        d_product = d_product.checked_mul(d)?.checked_div(amount_b_times_coins)?;
+        d_previous = d;
+        d = calculate_step(&d, leverage, sum_x, &d_product)?;
+        // equality with the precision of 1
+        if almost_equal(&d, &d_previous)? {
+            break;
+        }
+    }
+    u128::try_from(d).ok()
+}
+
+/// Compute the swap amount `y` in proportion to `x`
+///
+/// Solve for y:
+///
+/// `y**2 + y * (sum' - (A*n**n - 1) * D / (A * n**n)) = D ** (n + 1) / (n ** (2 * n) * prod' * A)`
+///
+/// `y**2 + b*y = c`
+fn compute_new_destination_amount(leverage: u64, new_source_amount: u128, d_val: u128) -> Option<u128> {
+    // upscale to U256
+    let leverage: U256 = leverage.into();
+    let new_source_amount: U256 = new_source_amount.into();
+    let d_val: U256 = d_val.into();
+
+    // sum' = prod' = x
+    // c =  D ** (n + 1) / (n ** (2 * n) * prod' * A)
+    let c = checked_u8_power(&d_val, N_COINS.checked_add(1)?)?.checked_div(checked_u8_mul(&new_source_amount, N_COINS_SQUARED)?.checked_mul(leverage)?)?;
+
+    // b = sum' - (A*n**n - 1) * D / (A * n**n)
+    let b = new_source_amount.checked_add(d_val.checked_div(leverage)?)?;
+
+    // solve for y by approximating: y**2 + b*y = c
+    let mut y_prev: U256;
+    let mut y = d_val;
+    for _ in 0..ITERATIONS {
+        y_prev = y;
+        // y = (y * y + c) / (2 * y + b - d), rounded up so the pool never pays out more than the curve allows
+        let y_numerator = checked_u8_power(&y, 2)?.checked_add(c)?;
+        let y_denominator = checked_u8_mul(&y, 2)?.checked_add(b)?.checked_sub(d_val)?;
+        y = y_numerator.checked_add(y_denominator)?.checked_sub(U256::one())?.checked_div(y_denominator)?;
+        if almost_equal(&y, &y_prev)? {
+            break;
+        }
+    }
+    u128::try_from(y).ok()
+}
+
+impl CurveCalculator for StableCurve {
+    /// Stable curve
+    fn swap_without_fees(&self, source_amount: u128, swap_source_amount: u128, swap_destination_amount: u128, trade_direction: TradeDirection) -> Option<SwapWithoutFeesResult> {
+        if source_amount == 0 {
+            return Some(SwapWithoutFeesResult { source_amount_swapped: 0, destination_amount_swapped: 0 });
+        }
+        let leverage = compute_a(self.amp)?;
+        // the truncation in D depends on the order of the amounts, keep it token A first like `normalized_value`
+        let compute_d = |source_amount, destination_amount| match trade_direction {
+            TradeDirection::AtoB => compute_d(leverage, source_amount, destination_amount),
+            TradeDirection::BtoA => compute_d(leverage, destination_amount, source_amount),
+        };
+
+        let d_val = compute_d(swap_source_amount, swap_destination_amount)?;
+        let new_source_amount = swap_source_amount.checked_add(source_amount)?;
+        let mut new_destination_amount = compute_new_destination_amount(leverage, new_source_amount, d_val)?;
+
+        // D is only solved to within 1, so on small pools the rounding can still
+        // favor the trader, hold back destination tokens until the pool keeps its value
+        for _ in 0..ITERATIONS {
+            if new_destination_amount >= swap_destination_amount || compute_d(new_source_amount, new_destination_amount)? >= d_val {
+                break;
+            }
+            new_destination_amount += 1;
+        }
+
+        let amount_swapped = swap_destination_amount.checked_sub(new_destination_amount)?;
+
+        Some(SwapWithoutFeesResult { source_amount_swapped: source_amount, destination_amount_swapped: amount_swapped })
+    }
+
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Option<u128> {
+        if source_amount == 0 {
+            return Some(0);
+        }
+        let leverage = compute_a(self.amp)?;
+        let d0 = PreciseNumber::new(compute_d(leverage, swap_token_a_amount, swap_token_b_amount)?)?;
+        let (withdraw_token_a_amount, withdraw_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (source_amount, 0),
+            TradeDirection::BtoA => (0, source_amount),
+        };
+        let updated_swap_token_a_amount = swap_token_a_amount.checked_sub(withdraw_token_a_amount)?;
+        let updated_swap_token_b_amount = swap_token_b_amount.checked_sub(withdraw_token_b_amount)?;
+        let d1 = PreciseNumber::new(compute_d(leverage, updated_swap_token_a_amount, updated_swap_token_b_amount)?)?;
+        let diff = d0.checked_sub(&d1)?;
+        let final_amount = (diff.checked_mul(&PreciseNumber::new(pool_supply)?))?.checked_div(&d0)?;
+        match round_direction {
+            RoundDirection::Floor => final_amount.floor()?.to_imprecise(),
+            RoundDirection::Ceiling => final_amount.ceiling()?.to_imprecise(),
+        }
+    }
+
+    /// The normalized value of the stable curve is its invariant D, which
+    /// already has the dimension of `tokens ^ 1`
+    fn normalized_value(&self, swap_token_a_amount: u128, swap_token_b_amount: u128) -> Option<PreciseNumber> {
+        let leverage = compute_a(self.amp)?;
+        PreciseNumber::new(compute_d(leverage, swap_token_a_amount, swap_token_b_amount)?)
+    }
+
+    fn validate(&self) -> Result<(), SwapError> {
+        if self.amp < MIN_AMP || self.amp > MAX_AMP {
+            Err(SwapError::InvalidCurve)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// IsInitialized is required to use `Pack::pack` and `Pack::unpack`
+impl IsInitialized for StableCurve {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+impl Sealed for StableCurve {}
+impl Pack for StableCurve {
+    const LEN: usize = 8;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        (self as &dyn DynPack).pack_into_slice(output);
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<StableCurve, ProgramError> {
+        let amp = array_ref![input, 0, 8];
+        Ok(Self { amp: u64::from_le_bytes(*amp) })
+    }
+}
+
+impl DynPack for StableCurve {
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let amp = array_mut_ref![output, 0, 8];
+        *amp = self.amp.to_le_bytes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::curves::calculator::test::check_curve_value_from_swap;
+
+    #[test]
+    fn pack_curve() {
+        let amp = 1;
+        let curve = StableCurve { amp };
+
+        let mut packed = [0u8; StableCurve::LEN];
+        Pack::pack_into_slice(&curve, &mut packed[..]);
+        let unpacked = StableCurve::unpack(&packed).unwrap();
+        assert_eq!(curve, unpacked);
+
+        let mut packed = vec![];
+        packed.extend_from_slice(&amp.to_le_bytes());
+        let unpacked = StableCurve::unpack(&packed).unwrap();
+        assert_eq!(curve, unpacked);
+    }
+
+    #[test]
+    fn validate_amp() {
+        assert!(StableCurve { amp: 0 }.validate().is_err());
+        assert!(StableCurve { amp: 100 }.validate().is_ok());
+        assert!(StableCurve { amp: MAX_AMP + 1 }.validate().is_err());
+    }
+
+    #[test]
+    fn swap_near_parity() {
+        // a balanced USDC/USDT pool, the stable curve stays close to 1:1 where the constant product slips
+        let swap_source_amount: u128 = 1_000_000_000_000;
+        let swap_destination_amount: u128 = 1_000_000_000_000;
+        let source_amount: u128 = 10_000_000_000;
+        let curve = StableCurve { amp: 100 };
+        let result = curve.swap_without_fees(source_amount, swap_source_amount, swap_destination_amount, TradeDirection::AtoB).unwrap();
+        assert_eq!(result.source_amount_swapped, source_amount);
+        assert!(result.destination_amount_swapped < source_amount);
+        assert!(result.destination_amount_swapped > source_amount * 9_999 / 10_000);
+
+        // a flat curve with amp 1 slips more, but still less than the constant product
+        let flat = StableCurve { amp: 1 }.swap_without_fees(source_amount, swap_source_amount, swap_destination_amount, TradeDirection::AtoB).unwrap();
+        assert!(flat.destination_amount_swapped < result.destination_amount_swapped);
+        assert!(flat.destination_amount_swapped > 9_900_990_099);
+    }
+
+    #[test]
+    fn swap_zero() {
+        let curve = StableCurve { amp: 100 };
+        let result = curve.swap_without_fees(0, 100, 1_000_000_000_000_000, TradeDirection::AtoB).unwrap();
+
+        assert_eq!(result.source_amount_swapped, 0);
+        assert_eq!(result.destination_amount_swapped, 0);
+    }
+
+    prop_compose! {
+        // a pool within 2x of parity and a trade of up to a tenth of it, on a
+        // lopsided pool a single token of the short side moves the invariant
+        // by more than the 1 token epsilon
+        pub fn balanced_swap()(swap_source_amount in 1_000..u64::MAX / 2)
+                        (source_token_amount in 1..swap_source_amount / 10,
+                         swap_destination_amount in swap_source_amount / 2..swap_source_amount.saturating_mul(2),
+                         swap_source_amount in Just(swap_source_amount))
+                        -> (u64, u64, u64) {
+           (source_token_amount, swap_source_amount, swap_destination_amount)
+       }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_a_to_b(
+            (source_token_amount, swap_source_amount, swap_destination_amount) in balanced_swap(),
+            amp in 1..100u64,
+        ) {
+            let curve = StableCurve { amp };
+            check_curve_value_from_swap(
+                &curve,
+                source_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::AtoB
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_b_to_a(
+            (source_token_amount, swap_source_amount, swap_destination_amount) in balanced_swap(),
+            amp in 1..100u64,
+        ) {
+            let curve = StableCurve { amp };
+            check_curve_value_from_swap(
+                &curve,
+                source_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::BtoA
+            );
+        }
+    }
+}
@@ -0,0 +1,434 @@
+//! Finds the pools of the supported venues on its own.
+//!
+//! At startup each venue's program is enumerated with `getProgramAccounts`, narrowed down
+//! to its pool accounts with data-size/memcmp filters. The same filters are handed over to
+//! Geyser as `owner` filters, so that the pools created afterwards show up in the ingest.
+//! Either way, a pool is only registered once it trades allow-listed mints and holds
+//! enough of them. The ones short of liquidity are evaluated again every so often.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use magnus_shared::Dex;
+use serde::Deserialize;
+use serde_with::{DisplayFromStr, serde_as};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{account::Account, program_pack::Pack, pubkey::Pubkey};
+use spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount};
+use tracing::{debug, info};
+use yellowstone_grpc_proto::geyser::{
+    SubscribeRequestFilterAccounts, SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterMemcmp, subscribe_request_filter_accounts_filter::Filter,
+    subscribe_request_filter_accounts_filter_memcmp::Data,
+};
+
+use crate::{
+    AccountMap,
+    adapters::amms::{
+        Amm, KeyedAccount,
+        registry::{AmmRegistry, get_multiple_accounts},
+        spl_stake_pool::state::ACCOUNT_TYPE_STAKE_POOL,
+        swap_state::SwapVersion,
+    },
+};
+
+/// sha256("account:PoolState")[..8]
+const RAYDIUM_CP_POOL_DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
+/// sha256("account:Whirlpool")[..8]
+const WHIRLPOOL_DISCRIMINATOR: [u8; 8] = [63, 149, 209, 12, 225, 128, 99, 9];
+/// sha256("account:LbPair")[..8]
+const METEORA_DLMM_LB_PAIR_DISCRIMINATOR: [u8; 8] = [33, 11, 49, 98, 181, 101, 177, 13];
+/// sha256("account:SSTradingPair")[..8]
+const OBRIC_V2_TRADING_PAIR_DISCRIMINATOR: [u8; 8] = [59, 222, 15, 236, 98, 102, 90, 224];
+/// sha256("phoenix::program::accounts::MarketHeader")[..8]
+const PHOENIX_MARKET_DISCRIMINANT: [u8; 8] = [85, 153, 127, 98, 215, 115, 0, 175];
+
+const RAYDIUM_CP_POOL_LEN: u64 = 637;
+const RAYDIUM_AMM_V4_AMM_INFO_LEN: u64 = 752;
+const WHIRLPOOL_LEN: u64 = 653;
+const METEORA_DLMM_LB_PAIR_LEN: u64 = 904;
+
+/// A pool which didn't make it for any reason but its mints is evaluated again at most this often.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The geyser filters' names are prefixed with it, one per program.
+const GEYSER_FILTER_PREFIX: &str = "discovery";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PoolFilter {
+    DataSize(u64),
+    Memcmp { offset: usize, bytes: Vec<u8> },
+}
+
+impl PoolFilter {
+    pub fn matches(&self, account: &Account) -> bool {
+        match self {
+            PoolFilter::DataSize(size) => account.data.len() as u64 == *size,
+            PoolFilter::Memcmp { offset, bytes } => account.data.get(*offset..offset + bytes.len()).is_some_and(|data| data == bytes.as_slice()),
+        }
+    }
+
+    fn rpc(&self) -> RpcFilterType {
+        match self {
+            PoolFilter::DataSize(size) => RpcFilterType::DataSize(*size),
+            PoolFilter::Memcmp { offset, bytes } => RpcFilterType::Memcmp(Memcmp::new_raw_bytes(*offset, bytes.clone())),
+        }
+    }
+
+    fn geyser(&self) -> SubscribeRequestFilterAccountsFilter {
+        let filter = match self {
+            PoolFilter::DataSize(size) => Filter::Datasize(*size),
+            PoolFilter::Memcmp { offset, bytes } => Filter::Memcmp(SubscribeRequestFilterAccountsFilterMemcmp { offset: *offset as u64, data: Some(Data::Bytes(bytes.clone())) }),
+        };
+
+        SubscribeRequestFilterAccountsFilter { filter: Some(filter) }
+    }
+}
+
+fn memcmp(offset: usize, bytes: &[u8]) -> PoolFilter {
+    PoolFilter::Memcmp { offset, bytes: bytes.to_vec() }
+}
+
+/// The filters singling out the pools among the accounts owned by the dex's program.
+/// None for the dexes without an `Amm`, or whose pools can't be told apart by their own account.
+pub fn pool_filters(dex: Dex) -> Option<Vec<PoolFilter>> {
+    let filters = match dex {
+        Dex::RaydiumCp => vec![PoolFilter::DataSize(RAYDIUM_CP_POOL_LEN), memcmp(0, &RAYDIUM_CP_POOL_DISCRIMINATOR)],
+        // discriminator-less
+        Dex::RaydiumAmmV4 => vec![PoolFilter::DataSize(RAYDIUM_AMM_V4_AMM_INFO_LEN)],
+        Dex::Whirlpool => vec![PoolFilter::DataSize(WHIRLPOOL_LEN), memcmp(0, &WHIRLPOOL_DISCRIMINATOR)],
+        Dex::MeteoraDlmm => vec![PoolFilter::DataSize(METEORA_DLMM_LB_PAIR_LEN), memcmp(0, &METEORA_DLMM_LB_PAIR_DISCRIMINATOR)],
+        Dex::SplTokenSwap => vec![PoolFilter::DataSize(SwapVersion::LATEST_LEN as u64)],
+        Dex::ObricV2 => vec![memcmp(0, &OBRIC_V2_TRADING_PAIR_DISCRIMINATOR)],
+        Dex::Phoenix => vec![memcmp(0, &PHOENIX_MARKET_DISCRIMINANT)],
+        // the validator lists share the program
+        Dex::SplStakePool => vec![memcmp(0, &[ACCOUNT_TYPE_STAKE_POOL])],
+        // humidifi's markets need their cfg on top
+        Dex::HumidiFi | Dex::RaydiumClV2 | Dex::SolfiV2 | Dex::ZeroFi | Dex::Tessera | Dex::GoonFi | Dex::BisonFi => return None,
+    };
+
+    Some(filters)
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct DiscoveryCfg {
+    /// The venues to discover, all of those with pool filters if empty.
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[serde(default)]
+    pub dexes: Vec<Dex>,
+    /// A pool is registered only if all of its reserve mints are listed, any mint goes if empty.
+    #[serde_as(as = "HashSet<DisplayFromStr>")]
+    #[serde(default)]
+    pub mints: HashSet<Pubkey>,
+    /// The least raw amount a pool has to hold of any one of its listed reserve mints, no requirement if empty.
+    #[serde_as(as = "HashMap<DisplayFromStr, _>")]
+    #[serde(default)]
+    pub min_reserves: HashMap<Pubkey, u64>,
+}
+
+impl DiscoveryCfg {
+    pub fn load(file: &str) -> eyre::Result<Self> {
+        let json = fs::read_to_string(file)?;
+
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+pub struct Discovery {
+    // shared with the pools being evaluated off the ingest loop
+    cfg: Arc<DiscoveryCfg>,
+    registry: AmmRegistry,
+    // the programs discovered along with their pool filters
+    programs: HashMap<Pubkey, Vec<PoolFilter>>,
+    // the pools registered, or trading mints off the allow-list, whose live updates are ignored for good
+    seen: HashSet<Pubkey>,
+    // when the other pools were last evaluated, so that the live updates of those short of liquidity are only rechecked every so often
+    evaluated: HashMap<Pubkey, Instant>,
+}
+
+impl Discovery {
+    pub fn new(cfg: DiscoveryCfg, registry: AmmRegistry) -> Self {
+        let dexes = if cfg.dexes.is_empty() { Dex::ALL.to_vec() } else { cfg.dexes.clone() };
+        let programs = dexes
+            .into_iter()
+            .filter_map(|dex| Some((Pubkey::new_from_array(dex.program_id().to_bytes()), pool_filters(dex)?)))
+            .filter(|(program_id, _)| registry.supports(program_id))
+            .collect();
+
+        Discovery { cfg: Arc::new(cfg), registry, programs, seen: HashSet::new(), evaluated: HashMap::new() }
+    }
+
+    pub fn programs(&self) -> impl Iterator<Item = &Pubkey> {
+        self.programs.keys()
+    }
+
+    /// The account is owned by one of the discovered programs and passes its pool filters.
+    pub fn is_pool(&self, account: &Account) -> bool {
+        self.programs.get(&account.owner).is_some_and(|filters| filters.iter().all(|filter| filter.matches(account)))
+    }
+
+    /// Marks pools as evaluated for good, i.e. those registered.
+    pub fn mark_seen(&mut self, keys: impl IntoIterator<Item = Pubkey>) {
+        self.seen.extend(keys);
+    }
+
+    /// Enumerates the pools of every discovered program, handing back those that qualify along with the accounts they were primed with.
+    pub fn discover(&mut self, client: &RpcClient) -> eyre::Result<(Vec<Box<dyn Amm>>, AccountMap)> {
+        let mut candidates = vec![];
+        for (program_id, filters) in self.programs.clone() {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(filters.iter().map(PoolFilter::rpc).collect()),
+                account_config: RpcAccountInfoConfig { encoding: Some(UiAccountEncoding::Base64), ..RpcAccountInfoConfig::default() },
+                ..RpcProgramAccountsConfig::default()
+            };
+            let pools = client.get_program_accounts_with_config(&program_id, config)?;
+            info!("found {} pools owned by {}", pools.len(), program_id);
+
+            candidates.extend(pools.into_iter().filter_map(|(key, account)| self.candidate(key, account)));
+        }
+
+        let keys: Vec<Pubkey> = candidates.iter().flat_map(|amm| amm.get_accounts_to_update()).collect();
+        let account_map = get_multiple_accounts(client, &keys)?;
+        let amms: Vec<Box<dyn Amm>> = candidates.into_iter().filter_map(|amm| self.admit(amm, &account_map)).collect();
+        self.mark_seen(amms.iter().map(|amm| amm.key()));
+        info!("discovered {} pools", amms.len());
+
+        Ok((amms, account_map))
+    }
+
+    /// Constructs the pool out of its account, unless it was seen before, was evaluated lately, isn't supported or trades a mint off the allow-list.
+    pub fn candidate(&mut self, key: Pubkey, account: Account) -> Option<Box<dyn Amm>> {
+        if self.seen.contains(&key) || self.evaluated.get(&key).is_some_and(|at| at.elapsed() < RECHECK_INTERVAL) {
+            return None;
+        }
+        self.evaluated.insert(key, Instant::now());
+
+        let amm = self.registry.from_keyed_account(&KeyedAccount { key, account, params: None }).inspect_err(|e| debug!("unable to construct pool {}: {}", key, e)).ok()?;
+
+        if !self.allows_mints(&amm.get_reserve_mints()) {
+            self.evaluated.remove(&key);
+            self.seen.insert(key);
+            return None;
+        }
+
+        Some(amm)
+    }
+
+    /// Primes the pool with its accounts to update, keeping it if it holds enough liquidity.
+    pub fn admit(&self, amm: Box<dyn Amm>, account_map: &AccountMap) -> Option<Box<dyn Amm>> {
+        self.cfg.admit(amm, account_map)
+    }
+
+    /// Same as [`Discovery::admit`], fetching the accounts on the way.
+    /// The discovery isn't borrowed by the evaluation, so it can be awaited off the ingest loop.
+    pub fn admit_live(
+        &self,
+        client: Arc<solana_client::nonblocking::rpc_client::RpcClient>,
+        amm: Box<dyn Amm>,
+    ) -> impl Future<Output = eyre::Result<Option<(Box<dyn Amm>, AccountMap)>>> + Send + 'static {
+        let cfg = self.cfg.clone();
+        async move {
+            let keys = amm.get_accounts_to_update();
+            let accounts = client.get_multiple_accounts(&keys).await?;
+            let account_map: AccountMap = keys.into_iter().zip(accounts).filter_map(|(key, account)| account.map(|account| (key, account))).collect();
+
+            Ok(cfg.admit(amm, &account_map).map(|amm| (amm, account_map)))
+        }
+    }
+
+    /// The owner filters following the pools of every discovered program.
+    pub fn geyser_filters(&self) -> HashMap<String, SubscribeRequestFilterAccounts> {
+        self.programs
+            .iter()
+            .map(|(program_id, filters)| {
+                let filter = SubscribeRequestFilterAccounts {
+                    account: vec![],
+                    owner: vec![program_id.to_string()],
+                    filters: filters.iter().map(PoolFilter::geyser).collect(),
+                    nonempty_txn_signature: None,
+                };
+                (format!("{}-{}", GEYSER_FILTER_PREFIX, program_id), filter)
+            })
+            .collect()
+    }
+
+    fn allows_mints(&self, mints: &[Pubkey]) -> bool {
+        self.cfg.mints.is_empty() || mints.iter().all(|mint| self.cfg.mints.contains(mint))
+    }
+}
+
+impl DiscoveryCfg {
+    fn admit(&self, mut amm: Box<dyn Amm>, account_map: &AccountMap) -> Option<Box<dyn Amm>> {
+        let accounts: AccountMap = amm.get_accounts_to_update().into_iter().filter_map(|key| account_map.get(&key).map(|account| (key, account.clone()))).collect();
+        if let Err(e) = amm.update(&accounts, None) {
+            debug!("unable to prime pool {}: {}", amm.key(), e);
+            return None;
+        }
+
+        self.has_liquidity(amm.as_ref(), &accounts).then_some(amm)
+    }
+
+    fn has_liquidity(&self, amm: &dyn Amm, account_map: &AccountMap) -> bool {
+        if self.min_reserves.is_empty() {
+            return true;
+        }
+
+        let reserves = reserves(amm, account_map);
+        amm.get_reserve_mints().iter().any(|mint| matches!((self.min_reserves.get(mint), reserves.get(mint)), (Some(min_reserve), Some(reserve)) if reserve >= min_reserve))
+    }
+}
+
+/// The largest balance of each reserve mint among the token accounts the pool is updated from, i.e. its vaults.
+/// Pools holding their reserves elsewhere (i.e. stake pools) have none.
+pub fn reserves(amm: &dyn Amm, account_map: &AccountMap) -> HashMap<Pubkey, u64> {
+    let reserve_mints = amm.get_reserve_mints();
+    let token_programs = [spl_token::id(), Pubkey::new_from_array(spl_token_2022::id().to_bytes())];
+
+    let mut reserves = HashMap::new();
+    amm.get_accounts_to_update()
+        .iter()
+        .filter_map(|key| account_map.get(key))
+        .filter(|account| token_programs.contains(&account.owner) && account.data.len() >= spl_token::state::Account::LEN)
+        .filter_map(|account| StateWithExtensions::<TokenAccount>::unpack(&account.data).ok())
+        .map(|token_account| (Pubkey::new_from_array(token_account.base.mint.to_bytes()), token_account.base.amount))
+        .filter(|(mint, _)| reserve_mints.contains(mint))
+        .for_each(|(mint, amount)| {
+            let reserve = reserves.entry(mint).or_insert(0);
+            *reserve = amount.max(*reserve);
+        });
+
+    reserves
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    use super::*;
+    use crate::adapters::amms::raydium_amm_v4::AmmInfo;
+
+    fn token_account(mint: Pubkey, amount: u64) -> Account {
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        let account = spl_token::state::Account { mint, amount, state: spl_token::state::AccountState::Initialized, ..Default::default() };
+        Pack::pack(account, &mut data).unwrap();
+
+        Account { lamports: 1, data, owner: spl_token::id(), executable: false, rent_epoch: u64::MAX }
+    }
+
+    /// A v4 pool along with its vaults, holding `coin_amount` and `pc_amount`.
+    fn raydium_amm_v4(coin_amount: u64, pc_amount: u64) -> (Pubkey, Account, AccountMap) {
+        let state = AmmInfo {
+            status: 6,
+            coin_vault: Pubkey::new_unique(),
+            pc_vault: Pubkey::new_unique(),
+            coin_vault_mint: Pubkey::new_unique(),
+            pc_vault_mint: Pubkey::new_unique(),
+            market: Pubkey::new_unique(),
+            ..AmmInfo::default()
+        };
+        let mut data = vec![];
+        state.serialize(&mut data).unwrap();
+
+        let key = Pubkey::new_unique();
+        let account = Account { data, owner: Pubkey::new_from_array(Dex::RaydiumAmmV4.program_id().to_bytes()), ..Account::default() };
+        let account_map =
+            [(key, account.clone()), (state.coin_vault, token_account(state.coin_vault_mint, coin_amount)), (state.pc_vault, token_account(state.pc_vault_mint, pc_amount))]
+                .into_iter()
+                .collect();
+
+        (key, account, account_map)
+    }
+
+    #[test]
+    fn pool_filters_match_the_pool_accounts() {
+        let (_, account, _) = raydium_amm_v4(0, 0);
+        let filters = pool_filters(Dex::RaydiumAmmV4).unwrap();
+        assert!(filters.iter().all(|filter| filter.matches(&account)));
+
+        let discovery = Discovery::new(DiscoveryCfg::default(), AmmRegistry::default());
+        assert!(discovery.is_pool(&account));
+        // humidifi can't be discovered
+        assert!(!discovery.programs().any(|program_id| *program_id == Pubkey::new_from_array(Dex::HumidiFi.program_id().to_bytes())));
+        // neither an account of another size
+        assert!(!discovery.is_pool(&Account { data: vec![0; 100], ..account }));
+    }
+
+    #[test]
+    fn admits_allow_listed_pools_with_enough_liquidity() {
+        let (key, account, account_map) = raydium_amm_v4(1_000, 10);
+        let state = AmmInfo::deserialize(&mut &account.data[..]).unwrap();
+
+        // off the allow-list
+        let cfg = DiscoveryCfg { mints: HashSet::from([state.coin_vault_mint]), ..DiscoveryCfg::default() };
+        let mut discovery = Discovery::new(cfg, AmmRegistry::default());
+        assert!(discovery.candidate(key, account.clone()).is_none());
+
+        let min_reserves = |min_reserve| HashMap::from([(state.coin_vault_mint, min_reserve)]);
+        let cfg = DiscoveryCfg { mints: HashSet::from([state.coin_vault_mint, state.pc_vault_mint]), min_reserves: min_reserves(1_000), ..DiscoveryCfg::default() };
+        let mut discovery = Discovery::new(cfg, AmmRegistry::default());
+        let amm = discovery.candidate(key, account.clone()).unwrap();
+        assert_eq!(reserves(amm.as_ref(), &account_map), HashMap::from([(state.coin_vault_mint, 1_000), (state.pc_vault_mint, 10)]));
+        assert!(discovery.admit(amm, &account_map).is_some());
+        // not evaluated again so soon
+        assert!(discovery.candidate(key, account.clone()).is_none());
+
+        let cfg = DiscoveryCfg { min_reserves: min_reserves(1_001), ..DiscoveryCfg::default() };
+        let mut discovery = Discovery::new(cfg, AmmRegistry::default());
+        let amm = discovery.candidate(key, account).unwrap();
+        assert!(discovery.admit(amm, &account_map).is_none());
+    }
+
+    #[test]
+    fn rechecks_the_pools_short_of_liquidity() {
+        let (key, account, _) = raydium_amm_v4(1_000, 10);
+        let state = AmmInfo::deserialize(&mut &account.data[..]).unwrap();
+
+        // off the allow-list for good
+        let cfg = DiscoveryCfg { mints: HashSet::from([state.coin_vault_mint]), ..DiscoveryCfg::default() };
+        let mut discovery = Discovery::new(cfg, AmmRegistry::default());
+        assert!(discovery.candidate(key, account.clone()).is_none());
+        discovery.evaluated.clear();
+        assert!(discovery.candidate(key, account.clone()).is_none());
+
+        // short of liquidity, evaluated again once the interval's up
+        let cfg = DiscoveryCfg { min_reserves: HashMap::from([(state.coin_vault_mint, 1_001)]), ..DiscoveryCfg::default() };
+        let mut discovery = Discovery::new(cfg, AmmRegistry::default());
+        assert!(discovery.candidate(key, account.clone()).is_some());
+        assert!(discovery.candidate(key, account.clone()).is_none());
+        discovery.evaluated.insert(key, Instant::now() - RECHECK_INTERVAL);
+        assert!(discovery.candidate(key, account.clone()).is_some());
+
+        // registered for good
+        discovery.mark_seen([key]);
+        discovery.evaluated.clear();
+        assert!(discovery.candidate(key, account).is_none());
+    }
+
+    #[test]
+    fn parses_the_cfg() {
+        let cfg: DiscoveryCfg = serde_json::from_str(
+            r#"{
+                "dexes": ["whirlpool", "raydium-cp"],
+                "mints": ["So11111111111111111111111111111111111111112"],
+                "min_reserves": {"So11111111111111111111111111111111111111112": 1000000000}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(cfg.dexes, vec![Dex::Whirlpool, Dex::RaydiumCp]);
+        assert_eq!(cfg.min_reserves.values().copied().collect::<Vec<_>>(), vec![1_000_000_000]);
+
+        let discovery = Discovery::new(cfg, AmmRegistry::default());
+        assert_eq!(discovery.programs().count(), 2);
+        assert_eq!(discovery.geyser_filters().len(), 2);
+    }
+}
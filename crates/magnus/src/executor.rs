@@ -1,28 +1,112 @@
-use std::sync::mpsc;
+use std::{sync::mpsc, time::SystemTime};
 
+use magnus_router_client::{events::find_event_authority_pda, instructions::SwapV2Builder};
+use magnus_shared::{Dex, Route};
 use solana_client::nonblocking::rpc_client::RpcClient;
-use tracing::info;
+use solana_sdk::{instruction::Instruction, signature::Signature, signer::Signer, transaction::Transaction};
+use tracing::{info, warn};
 
 use crate::{
     Executor, ExecutorCtx,
-    adapters::{IntSwapResponse, amms::Target},
+    adapters::{
+        IntSwapResponse,
+        aggregators::SignedSwap,
+        amms::{Chroot, Target},
+    },
     strategy::{DispatchResponse, WrappedSwapAndAccountMetas},
 };
 
+/// What the executor is asked to land onchain.
+pub enum ExecutionParams {
+    // a route through the AMMs, computed by the strategy
+    Route(WrappedSwapAndAccountMetas),
+    // an aggregator's transaction, already signed by the user
+    Signed { swap: SignedSwap, response_tx: oneshot::Sender<eyre::Result<IntSwapResponse>> },
+}
+
 pub struct BaseExecutorCfg {
     pub client: std::sync::Arc<RpcClient>,
-    pub solver_rx: mpsc::Receiver<WrappedSwapAndAccountMetas>,
+    pub solver_rx: mpsc::Receiver<ExecutionParams>,
+    // how many slots past the quote's context slot the swap is allowed to land
+    pub max_slot_tolerance: u64,
+    // how many seconds from submission the swap is allowed to land, 0 disables the deadline
+    pub deadline_secs: u64,
 }
 
 pub struct BaseExecutor {
-    _client: std::sync::Arc<RpcClient>,
-    // receives swaps & accounts from the solver
-    solver_rx: mpsc::Receiver<WrappedSwapAndAccountMetas>,
+    client: std::sync::Arc<RpcClient>,
+    // receives swaps & accounts from the solver, and the signed aggregator swaps from the api server
+    solver_rx: mpsc::Receiver<ExecutionParams>,
+    max_slot_tolerance: u64,
+    deadline_secs: u64,
+}
+
+/// The `deadline_unix_ts`/`max_slot` pair passed to the router's `swap_v2`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SwapGuard {
+    pub deadline_unix_ts: Option<i64>,
+    pub max_slot: Option<u64>,
 }
 
 impl BaseExecutor {
     pub fn new(cfg: BaseExecutorCfg) -> Self {
-        BaseExecutor { _client: cfg.client, solver_rx: cfg.solver_rx }
+        BaseExecutor { client: cfg.client, solver_rx: cfg.solver_rx, max_slot_tolerance: cfg.max_slot_tolerance, deadline_secs: cfg.deadline_secs }
+    }
+
+    /// Bounds how late a swap quoted at `context_slot` may land onchain.
+    /// An unknown (0) context slot leaves `max_slot` unset.
+    pub fn swap_guard(&self, context_slot: u64) -> SwapGuard {
+        let max_slot = (context_slot > 0).then(|| context_slot.saturating_add(self.max_slot_tolerance));
+        let deadline_unix_ts = (self.deadline_secs > 0).then(|| {
+            let now = SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+            now.saturating_add(self.deadline_secs) as i64
+        });
+
+        SwapGuard { deadline_unix_ts, max_slot }
+    }
+
+    /// The router's `swap_v2` through the route's legs, bounded by the guard of its context slot and each leg's min out.
+    pub fn swap_instruction(&self, swaps: &WrappedSwapAndAccountMetas, order_id: u64) -> Instruction {
+        let guard = self.swap_guard(swaps.context_slot);
+        // a single split, hopping through a single dex on each leg
+        let routes = vec![swaps.metas.iter().map(|leg| Route { dexes: vec![Dex::from(&leg.swap)], weights: vec![100] }.into()).collect()];
+        let leg_accounts_lens = vec![swaps.metas.iter().map(|leg| vec![leg.account_metas.len() as u8]).collect()];
+        let account_metas: Vec<_> = swaps.metas.iter().flat_map(|leg| leg.account_metas.iter().cloned()).collect();
+
+        let mut swap_builder = SwapV2Builder::new();
+        swap_builder
+            .payer(swaps.signer.pubkey())
+            .source_token_account(swaps.src_ta)
+            .destination_token_account(swaps.dst_ta)
+            .source_mint(swaps.input_mint)
+            .destination_mint(swaps.output_mint)
+            .event_authority(find_event_authority_pda().0)
+            .amount_in(swaps.amount_in)
+            .expect_amount_out(swaps.expect_amount_out)
+            .min_return(swaps.min_return)
+            .amounts(vec![swaps.amount_in])
+            .routes(routes)
+            .hop_min_outs(swaps.hop_min_outs.clone())
+            .leg_accounts_lens(leg_accounts_lens)
+            .order_id(order_id)
+            .add_remaining_accounts(&account_metas);
+        if let Some(deadline_unix_ts) = guard.deadline_unix_ts {
+            swap_builder.deadline_unix_ts(deadline_unix_ts);
+        }
+        if let Some(max_slot) = guard.max_slot {
+            swap_builder.max_slot(max_slot);
+        }
+
+        swap_builder.instruction()
+    }
+
+    /// Signs the route's `swap_v2` and submits it through the rpc.
+    async fn submit_route(&self, swaps: &WrappedSwapAndAccountMetas) -> eyre::Result<Signature> {
+        let ix = self.swap_instruction(swaps, Chroot::gen_order_id());
+        let blockhash = self.client.get_latest_blockhash().await?;
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&swaps.signer.pubkey()), &[&swaps.signer], blockhash);
+
+        Ok(self.client.send_transaction(&tx).await?)
     }
 }
 
@@ -31,19 +115,117 @@ impl Executor for BaseExecutor {
     async fn execute<C: ExecutorCtx>(&mut self, _: C) -> eyre::Result<()> {
         // ..
 
-        while let Ok(swaps) = self.solver_rx.recv() {
+        while let Ok(params) = self.solver_rx.recv() {
             info!("received by `Executor`");
-            //tokio::time::sleep(Duration::from_secs(2)).await;
-            if let Ok(()) = swaps.response_tx.send(DispatchResponse::Swap(IntSwapResponse {
-                source: Target::AMMs,
-                input_mint: swaps.input_mint.to_string(),
-                output_mint: swaps.output_mint.to_string(),
-                ..IntSwapResponse::default()
-            })) {
-                info!("sent from `Executor` towards `API Server::swap`")
+            match params {
+                ExecutionParams::Route(swaps) => {
+                    let response = IntSwapResponse {
+                        source: Target::AMMs,
+                        input_mint: swaps.input_mint.to_string(),
+                        output_mint: swaps.output_mint.to_string(),
+                        in_amount: swaps.amount_in,
+                        out_amount: swaps.expect_amount_out,
+                        ..IntSwapResponse::default()
+                    };
+                    let response = match self.submit_route(&swaps).await {
+                        Ok(signature) => IntSwapResponse { signature: signature.to_string(), ..response },
+                        Err(e) => {
+                            warn!("unable to submit the {:?} swap - {}", Target::AMMs, e);
+                            IntSwapResponse { error: Some(e.to_string()), ..response }
+                        }
+                    };
+
+                    if let Ok(()) = swaps.response_tx.send(DispatchResponse::Swap(response)) {
+                        info!("sent from `Executor` towards `API Server::swap`")
+                    }
+                }
+                ExecutionParams::Signed { swap, response_tx } => {
+                    let response = match self.client.send_transaction(&swap.transaction).await {
+                        Ok(signature) => Ok(IntSwapResponse { signature: signature.to_string(), ..swap.response }),
+                        Err(e) => {
+                            warn!("unable to submit the {:?} swap - {}", swap.response.source, e);
+                            Err(e.into())
+                        }
+                    };
+
+                    if let Ok(()) = response_tx.send(response) {
+                        info!("sent from `Executor` towards `API Server::swap`")
+                    }
+                }
             }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use borsh::BorshDeserialize;
+    use magnus_router_client::instructions::SwapV2InstructionArgs;
+    use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, signature::Keypair};
+
+    use super::*;
+    use crate::adapters::{AmmKind, SwapAndAccountMetas};
+
+    fn executor(max_slot_tolerance: u64, deadline_secs: u64) -> BaseExecutor {
+        let client = Arc::new(RpcClient::new("http://127.0.0.1:8899".to_string()));
+        BaseExecutor::new(BaseExecutorCfg { client, solver_rx: mpsc::channel().1, max_slot_tolerance, deadline_secs })
+    }
+
+    fn swaps(context_slot: u64) -> WrappedSwapAndAccountMetas {
+        WrappedSwapAndAccountMetas {
+            response_tx: oneshot::channel().0,
+            metas: vec![SwapAndAccountMetas { swap: AmmKind::RaydiumCP, account_metas: vec![AccountMeta::new(Pubkey::new_unique(), false); 13] }],
+            input_mint: Pubkey::new_unique(),
+            output_mint: Pubkey::new_unique(),
+            src_ta: Pubkey::new_unique(),
+            dst_ta: Pubkey::new_unique(),
+            amount_in: 1_000,
+            expect_amount_out: 990,
+            min_return: 985,
+            context_slot,
+            hop_min_outs: vec![vec![vec![985]]],
+            signer: Keypair::new(),
+        }
+    }
+
+    fn swap_args(ix: &Instruction) -> SwapV2InstructionArgs {
+        SwapV2InstructionArgs::try_from_slice(&ix.data[8..]).unwrap()
+    }
+
+    #[test]
+    fn swap_guard_bounds_the_slot_and_the_deadline() {
+        let now = SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64;
+        let guard = executor(10, 30).swap_guard(100);
+
+        assert_eq!(guard.max_slot, Some(110));
+        assert!(guard.deadline_unix_ts.is_some_and(|deadline| (now + 30..=now + 31).contains(&deadline)));
+    }
+
+    #[test]
+    fn swap_guard_leaves_out_an_unknown_slot_and_a_zero_deadline() {
+        assert_eq!(executor(10, 30).swap_guard(0).max_slot, None);
+        assert_eq!(executor(10, 0).swap_guard(100), SwapGuard { deadline_unix_ts: None, max_slot: Some(110) });
+        assert_eq!(executor(10, 0).swap_guard(0), SwapGuard::default());
+    }
+
+    #[test]
+    fn swap_instruction_carries_the_guard() {
+        let swaps = swaps(100);
+        let ix = executor(10, 0).swap_instruction(&swaps, 7);
+        let args = swap_args(&ix);
+
+        assert_eq!((args.deadline_unix_ts, args.max_slot), (None, Some(110)));
+        assert_eq!((args.amount_in, args.expect_amount_out, args.min_return, args.order_id), (1_000, 990, 985, 7));
+        assert_eq!(args.amounts, vec![1_000]);
+        assert_eq!(Route::from(args.routes[0][0].clone()), Route { dexes: vec![Dex::RaydiumCp], weights: vec![100] });
+        assert_eq!(args.leg_accounts_lens, Some(vec![vec![vec![13]]]));
+        assert_eq!(args.hop_min_outs, Some(vec![vec![vec![985]]]));
+        // the router's 7 accounts, followed by the leg's
+        assert_eq!(ix.accounts.len(), 7 + 13);
+        assert_eq!(ix.accounts[0].pubkey, swaps.signer.pubkey());
+    }
+}
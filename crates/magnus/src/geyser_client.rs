@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use futures::Stream;
+use futures::{Sink, Stream, channel::mpsc::SendError};
 use tonic::Status;
 use yellowstone_grpc_client::{GeyserGrpcClient, Interceptor};
 use yellowstone_grpc_proto::{
@@ -27,9 +27,11 @@ impl<F: Interceptor + Send + Sync> GeyserClientWrapped<F> {
         SubscribeRequest { accounts: filter, ..Default::default() }
     }
 
-    pub async fn subscribe(&mut self, sub_request: SubscribeRequest) -> impl Stream<Item = Result<SubscribeUpdate, Status>> {
-        let (_, stream) = self.0.subscribe_with_request(Some(sub_request)).await.expect("unable to subscribe to geyser");
-
-        stream
+    /// Subscribes with the given filter, the returned sink replaces the filter of the live stream.
+    pub async fn subscribe(
+        &mut self,
+        sub_request: SubscribeRequest,
+    ) -> (impl Sink<SubscribeRequest, Error = SendError> + use<F>, impl Stream<Item = Result<SubscribeUpdate, Status>> + use<F>) {
+        self.0.subscribe_with_request(Some(sub_request)).await.expect("unable to subscribe to geyser")
     }
 }
@@ -1,32 +1,265 @@
-use futures_util::StreamExt as _;
+use std::time::Duration;
+
+use futures_util::{SinkExt as _, StreamExt as _};
+#[cfg(feature = "metrics")]
+use metrics::{counter, gauge};
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
-use tracing::{error, info};
+use tokio::sync::{
+    mpsc::{UnboundedReceiver, UnboundedSender},
+    oneshot,
+};
+use tracing::{error, info, warn};
 use yellowstone_grpc_client::{GeyserGrpcClient, Interceptor};
-use yellowstone_grpc_proto::geyser::subscribe_update;
+use yellowstone_grpc_proto::geyser::{SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterSlots, subscribe_update};
+
+use crate::{
+    AccountMap, Ingest, IngestCtx, Markets, MarketsStats, Oracle, StateAccountToMarket,
+    adapters::amms::{Amm, registry::get_multiple_accounts_live},
+    analytics::StalenessCfg,
+    bootstrap,
+    discovery::Discovery,
+    geyser_client::GeyserClientWrapped,
+    helpers::geyser_acc_to_native,
+    snapshot::{self, AccountSlots, Snapshot, SnapshotCfg},
+};
+
+/// The name of the slots filter following the replay.
+const REPLAY_FILTER: &str = "replay";
 
-use crate::{AccountMap, Ingest, IngestCtx, Markets, StateAccountToMarket, geyser_client::GeyserClientWrapped, helpers::geyser_acc_to_native};
+/// The name of the accounts filter following the oracle's price accounts.
+const ORACLE_FILTER: &str = "oracle";
+
+/// How often the markets are checked against the staleness thresholds.
+const STALENESS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 pub struct IngestorCfg<T: Interceptor + Send + Sync> {
     pub client_geyser: GeyserGrpcClient<T>,
     pub client_default: std::sync::Arc<RpcClient>,
     pub markets: Markets,
     pub account_map: AccountMap,
+    pub stats: MarketsStats,
+    pub subscription_rx: UnboundedReceiver<SubscriptionUpdate>,
+    /// The sender of `subscription_rx`, the accounts fetched off the ingest loop are sent back through it.
+    pub subscription_tx: UnboundedSender<SubscriptionUpdate>,
+    pub discovery: Option<Discovery>,
+    /// The slot the account map is current as of.
+    pub slot: u64,
+    pub account_slots: AccountSlots,
+    pub snapshot: Option<SnapshotCfg>,
+    pub replay: Option<Replay>,
+    pub staleness: StalenessCfg,
+    pub oracle: Oracle,
+}
+
+/// Catching up the state restored from a snapshot, by replaying the geyser stream from the snapshot's slot.
+#[derive(Debug)]
+pub struct Replay {
+    pub from_slot: u64,
+    /// The slot to replay up to, i.e. the current one at startup.
+    pub until_slot: u64,
+    pub caught_up_tx: oneshot::Sender<()>,
+}
+
+/// Changes to the tracked markets, made at runtime (i.e. through the api server) after they're applied to `Markets`.
+#[derive(Debug)]
+pub enum SubscriptionUpdate {
+    /// Markets were added, along with the current state of their accounts to update.
+    Track(AccountMap),
+    /// Markets were removed, along with the accounts they were updated from.
+    Untrack(Vec<Pubkey>),
+    /// A market's dynamic accounts (i.e. tick/bin arrays) moved, along with the current state of the ones it moved onto.
+    Refresh { market: Pubkey, account_map: AccountMap },
 }
 
 pub struct GeyserPoolStateIngestor<T: Interceptor + Send + Sync> {
     client_geyser: GeyserClientWrapped<T>,
-    _client_default: std::sync::Arc<RpcClient>,
+    client_default: std::sync::Arc<RpcClient>,
     markets: Markets,
     account_map: AccountMap,
+    stats: MarketsStats,
+    // the markets added/removed after the subscription started
+    subscription_rx: UnboundedReceiver<SubscriptionUpdate>,
+    subscription_tx: UnboundedSender<SubscriptionUpdate>,
+    // the pools created after startup are caught through its owner filters
+    discovery: Option<Discovery>,
+    slot: u64,
+    account_slots: AccountSlots,
+    snapshot: Option<SnapshotCfg>,
+    // pending until the replay reaches its slot
+    replay: Option<Replay>,
+    staleness: StalenessCfg,
+    // its price accounts are followed along with the markets'
+    oracle: Oracle,
 }
 
 impl<T: Interceptor + Send + Sync> GeyserPoolStateIngestor<T> {
     pub fn new(cfg: IngestorCfg<T>) -> Self {
-        Self { client_geyser: GeyserClientWrapped::new(cfg.client_geyser), _client_default: cfg.client_default, markets: cfg.markets, account_map: cfg.account_map }
+        Self {
+            client_geyser: GeyserClientWrapped::new(cfg.client_geyser),
+            client_default: cfg.client_default,
+            markets: cfg.markets,
+            account_map: cfg.account_map,
+            stats: cfg.stats,
+            subscription_rx: cfg.subscription_rx,
+            subscription_tx: cfg.subscription_tx,
+            discovery: cfg.discovery,
+            slot: cfg.slot,
+            account_slots: cfg.account_slots,
+            snapshot: cfg.snapshot,
+            replay: cfg.replay,
+            staleness: cfg.staleness,
+            oracle: cfg.oracle,
+        }
+    }
+
+    /// The accounts of the tracked markets, along with the pools of the discovered programs and the oracle's price accounts.
+    ///
+    /// While replaying, the stream starts at the replay's slot and follows the slots to tell when it's caught up.
+    async fn filter(&self, state_acc_to_market: &StateAccountToMarket) -> SubscribeRequest {
+        let mut filter = self.client_geyser.craft_filter(accounts_filter(state_acc_to_market)).await;
+        if let Some(discovery) = &self.discovery {
+            filter.accounts.extend(discovery.geyser_filters());
+        }
+        let feeds = self.oracle.lock().unwrap().feeds();
+        if !feeds.is_empty() {
+            filter
+                .accounts
+                .insert(ORACLE_FILTER.to_string(), SubscribeRequestFilterAccounts { account: feeds.iter().map(|feed| feed.to_string()).collect(), ..Default::default() });
+        }
+        if let Some(replay) = &self.replay {
+            filter.from_slot = Some(replay.from_slot);
+            filter.slots.insert(REPLAY_FILTER.to_string(), SubscribeRequestFilterSlots::default());
+        }
+
+        filter
+    }
+
+    /// Ends the replay and lets the api server start serving.
+    fn caught_up(&mut self) {
+        if let Some(replay) = self.replay.take() {
+            if replay.caught_up_tx.send(()).is_err() {
+                warn!("caught up without anyone waiting on it");
+            }
+            info!("caught up at slot {}", self.slot);
+        }
+    }
+
+    /// Falls back on diffing the accounts over rpc, once the stream can't replay (i.e. the slot is past its retention).
+    async fn reconcile(&mut self) -> eyre::Result<()> {
+        let slot = self.client_default.get_slot().await?;
+        let keys: Vec<Pubkey> = self.account_map.keys().copied().collect();
+        let fetched = get_multiple_accounts_live(&self.client_default, &keys).await?;
+
+        let changed = snapshot::reconcile(&self.markets, &self.stats, &mut self.account_map, fetched, slot);
+        self.slot = self.slot.max(slot);
+        info!("reconciled {} changed accounts over rpc", changed);
+
+        Ok(())
+    }
+
+    /// Leaves the markets past the staleness thresholds out of the routing, and lets the recovered ones back in.
+    fn check_staleness(&self) {
+        let markets = self.markets.lock().unwrap();
+        let mut stats = self.stats.lock().unwrap();
+
+        for (key, amm) in markets.iter() {
+            let stats = stats.entry(*key).or_default();
+            stats.tracked_since.get_or_insert_with(std::time::Instant::now);
+
+            let stale = self.staleness.check(stats, amm.expects_regular_updates(), self.slot);
+            if stale == stats.stale {
+                continue;
+            }
+
+            match stale {
+                Some(reason) => {
+                    warn!("market {} ({}) is stale, left out of the routing | {}", key, amm.label(), reason);
+                    #[cfg(feature = "metrics")]
+                    counter!("MARKETS GONE STALE", "reason" => reason.as_str()).increment(1);
+                }
+                None => info!("market {} ({}) recovered, routed through again", key, amm.label()),
+            }
+            stats.stale = stale;
+        }
+
+        #[cfg(feature = "metrics")]
+        gauge!("STALE MARKETS").set(stats.values().filter(|stats| stats.stale_reason().is_some()).count() as f64);
+    }
+
+    /// Fetches the accounts a market moved onto off the ingest loop, they're followed once sent back as a [`SubscriptionUpdate::Refresh`].
+    /// The ones failing to fetch are left to the stream.
+    fn follow(&self, market: Pubkey, accounts: Vec<Pubkey>) {
+        let (client, subscription_tx) = (self.client_default.clone(), self.subscription_tx.clone());
+        tokio::spawn(async move {
+            let account_map = get_multiple_accounts_live(&client, &accounts).await.unwrap_or_else(|e| {
+                warn!("unable to fetch the accounts market {} moved onto - {}", market, e);
+                AccountMap::default()
+            });
+
+            if subscription_tx.send(SubscriptionUpdate::Refresh { market, account_map }).is_err() {
+                warn!("unable to follow the accounts market {} moved onto, the ingest is gone", market);
+            }
+        });
+    }
+
+    /// Awaits the evaluation of a discovered pool off the ingest loop, it's tracked once sent back as a [`SubscriptionUpdate::Track`].
+    fn admit(&self, pool: Pubkey, admission: impl Future<Output = eyre::Result<Option<(Box<dyn Amm>, AccountMap)>>> + Send + 'static) {
+        let (markets, subscription_tx) = (self.markets.clone(), self.subscription_tx.clone());
+        tokio::spawn(async move {
+            match admission.await {
+                Ok(Some((amm, account_map))) => {
+                    info!("discovered market {} ({})", amm.key(), amm.label());
+                    bootstrap::register(&markets, vec![amm]);
+                    if subscription_tx.send(SubscriptionUpdate::Track(account_map)).is_err() {
+                        warn!("unable to track market {}, the ingest is gone", pool);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!("unable to evaluate pool {} - {}", pool, e),
+            }
+        });
+    }
+
+    /// Writes the snapshot off the ingest loop.
+    fn write_snapshot(&self) {
+        let Some(cfg) = &self.snapshot else { return };
+
+        let snapshot = Snapshot::capture(self.slot, &self.markets, &self.account_map, &self.account_slots);
+        let file = cfg.file.clone();
+        tokio::task::spawn_blocking(move || match snapshot.write(&file) {
+            Ok(_) => info!("wrote snapshot of {} markets at slot {}", snapshot.markets.len(), snapshot.slot),
+            Err(e) => warn!("unable to write snapshot {} - {}", file, e),
+        });
     }
 }
 
+/// Maps the accounts to update of every tracked market to the market.
+fn map_accs_to_market(markets: &Markets) -> StateAccountToMarket {
+    markets
+        .lock()
+        .unwrap()
+        .values()
+        .flat_map(|market| {
+            let accs = market.get_accounts_to_update();
+            accs.into_iter().map(|acc| (acc, market.key()))
+        })
+        .collect()
+}
+
+/// The accounts a market with dynamic accounts moved onto since the subscription was last made, i.e. the ones nothing follows yet.
+fn moved_accounts(market: &dyn Amm, state_acc_to_market: &StateAccountToMarket) -> Vec<Pubkey> {
+    if !market.has_dynamic_accounts() {
+        return vec![];
+    }
+
+    market.get_accounts_to_update().into_iter().filter(|acc| !state_acc_to_market.contains_key(acc)).collect()
+}
+
+fn accounts_filter(state_acc_to_market: &StateAccountToMarket) -> Vec<String> {
+    state_acc_to_market.keys().map(|v| v.to_string()).collect()
+}
+
 #[async_trait::async_trait]
 impl<T: Interceptor + Send + Sync> Ingest for GeyserPoolStateIngestor<T> {
     fn name(&self) -> &str {
@@ -36,44 +269,163 @@ impl<T: Interceptor + Send + Sync> Ingest for GeyserPoolStateIngestor<T> {
     async fn ingest<C: IngestCtx>(&mut self, _: C) -> eyre::Result<()> {
         info!("starting service: {}", self.name());
 
-        let state_acc_to_market: StateAccountToMarket = self
-            .markets
-            .lock()
-            .unwrap()
-            .values()
-            .flat_map(|market| {
-                let accs = market.get_accounts_to_update();
-                accs.into_iter().map(|acc| (acc, market.key()))
-            })
-            .collect();
-
-        let filter = self.client_geyser.craft_filter(state_acc_to_market.keys().map(|v| v.to_string()).collect()).await;
-        let mut stream = self.client_geyser.subscribe(filter).await;
-
-        while let Some(message) = stream.next().await {
-            match message {
-                Ok(msg) => {
-                    if let Some(update) = msg.update_oneof
-                        && let subscribe_update::UpdateOneof::Account(account_update) = update
-                        && let Some(account_info) = account_update.account
-                    {
-                        let pubkey = Pubkey::try_from(account_info.pubkey.as_slice()).expect("Invalid pubkey");
-                        let account = geyser_acc_to_native(&account_info);
-                        let slot = account_update.slot;
-                        self.account_map.insert(pubkey, account);
-
-                        // we don't need to send a msg to `Strategy` since we're sharing the underlying structure
-                        let market_pubkey = state_acc_to_market.get(&pubkey).unwrap();
-                        if let Some(market) = self.markets.lock().unwrap().get_mut(market_pubkey)
-                            && let Ok(_) = market.update(&self.account_map, Some(slot))
-                        {
-                            info!("recv update for market: {:?}", market);
+        let mut state_acc_to_market = map_accs_to_market(&self.markets);
+
+        let filter = self.filter(&state_acc_to_market).await;
+        let (mut sink, mut stream) = self.client_geyser.subscribe(filter).await;
+
+        // the first tick is a whole interval away, there's nothing new to persist at startup
+        let period = self.snapshot.as_ref().map_or(Duration::from_secs(60), |cfg| cfg.interval);
+        let mut snapshot_interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        let mut staleness_interval = tokio::time::interval(STALENESS_CHECK_INTERVAL);
+
+        loop {
+            let mut resubscribe = false;
+            let mut replay_failed = false;
+
+            tokio::select! {
+                message = stream.next() => {
+                    let Some(message) = message else { break };
+
+                    match message {
+                        Ok(msg) => match msg.update_oneof {
+                            Some(subscribe_update::UpdateOneof::Account(account_update)) => {
+                                let Some(account_info) = account_update.account else { continue };
+
+                                let pubkey = Pubkey::try_from(account_info.pubkey.as_slice()).expect("Invalid pubkey");
+                                let account = geyser_acc_to_native(&account_info);
+                                let slot = account_update.slot;
+
+                                // a price account may be followed by a market as well (i.e. obric's)
+                                {
+                                    let mut oracle = self.oracle.lock().unwrap();
+                                    if oracle.is_feed(&pubkey)
+                                        && let Err(e) = oracle.update(&pubkey, &account)
+                                    {
+                                        warn!("unable to update price account {} - {}", pubkey, e);
+                                    }
+                                }
+
+                                if let Some(market_pubkey) = state_acc_to_market.get(&pubkey).copied() {
+                                    // the replay starts a few slots before the snapshot, it may well lag behind the account
+                                    if self.account_slots.get(&pubkey).is_some_and(|last| *last > slot) {
+                                        continue;
+                                    }
+                                    self.account_map.insert(pubkey, account);
+                                    self.account_slots.insert(pubkey, slot);
+                                    self.slot = self.slot.max(slot);
+
+                                    // we don't need to send a msg to `Strategy` since we're sharing the underlying structure
+                                    let mut moved = vec![];
+                                    if let Some(market) = self.markets.lock().unwrap().get_mut(&market_pubkey) {
+                                        let mut stats = self.stats.lock().unwrap();
+                                        let stats = stats.entry(market_pubkey).or_default();
+
+                                        match market.update(&self.account_map, Some(slot)) {
+                                            Ok(_) => {
+                                                stats.record_update(Some(slot));
+                                                // a live update makes up for a failed catch up
+                                                stats.restored &= self.replay.is_some();
+                                                info!("recv update for market: {:?}", market);
+                                                moved = moved_accounts(market.as_ref(), &state_acc_to_market);
+                                            }
+                                            Err(e) => {
+                                                stats.record_failed_update();
+                                                warn!("unable to update market {} - {}", market_pubkey, e);
+                                            }
+                                        }
+                                    }
+
+                                    // i.e. the active bin/current tick crossed into an array nothing follows yet
+                                    if !moved.is_empty() {
+                                        info!("market {} moved onto {} accounts", market_pubkey, moved.len());
+                                        // counted as followed already, so it's fetched the once
+                                        state_acc_to_market.extend(moved.iter().map(|acc| (*acc, market_pubkey)));
+                                        self.follow(market_pubkey, moved);
+                                    }
+                                } else if let Some(discovery) = self.discovery.as_mut()
+                                    && discovery.is_pool(&account)
+                                    && let Some(amm) = discovery.candidate(pubkey, account)
+                                {
+                                    // a pool created since the startup
+                                    let admission = discovery.admit_live(self.client_default.clone(), amm);
+                                    self.admit(pubkey, admission);
+                                }
+                                // otherwise the update may still be in flight for an account we no longer follow
+                            }
+                            Some(subscribe_update::UpdateOneof::Slot(slot_update)) => {
+                                self.slot = self.slot.max(slot_update.slot);
+                                if self.replay.as_ref().is_some_and(|replay| slot_update.slot >= replay.until_slot) {
+                                    snapshot::mark_fresh(&self.stats);
+                                    self.caught_up();
+                                    // the slots are of no use past the replay
+                                    resubscribe = true;
+                                }
+                            }
+                            _ => {}
+                        },
+                        Err(e) => {
+                            error!("received unsupported message - {}", e);
+                            replay_failed = self.replay.is_some();
+                            // metrics?
+                        }
+                    }
+                }
+                Some(update) = self.subscription_rx.recv() => {
+                    match update {
+                        SubscriptionUpdate::Track(account_map) => {
+                            // the markets registered from elsewhere aren't discovered again
+                            if let Some(discovery) = self.discovery.as_mut() {
+                                let markets = self.markets.lock().unwrap();
+                                discovery.mark_seen(account_map.keys().filter(|acc| markets.contains_key(acc)).copied());
+                            }
+                            self.account_map.extend(account_map);
+                        }
+                        // the accounts may be shared with the markets left (i.e. the clock sysvar)
+                        SubscriptionUpdate::Untrack(accounts) => {
+                            let state_acc_to_market = map_accs_to_market(&self.markets);
+                            accounts.iter().filter(|acc| !state_acc_to_market.contains_key(acc)).for_each(|acc| {
+                                self.account_map.remove(acc);
+                                self.account_slots.remove(acc);
+                            });
+                        }
+                        // the market's updated again, with the accounts it moved onto
+                        SubscriptionUpdate::Refresh { market, account_map } => {
+                            self.account_map.extend(account_map);
+                            if let Some(amm) = self.markets.lock().unwrap().get_mut(&market)
+                                && let Err(e) = amm.update(&self.account_map, None)
+                            {
+                                warn!("unable to update market {} - {}", market, e);
+                            }
                         }
                     }
+
+                    resubscribe = true;
+                }
+                _ = snapshot_interval.tick(), if self.snapshot.is_some() && self.replay.is_none() => self.write_snapshot(),
+                // the replayed updates are behind by design
+                _ = staleness_interval.tick(), if self.replay.is_none() => self.check_staleness(),
+            }
+
+            if replay_failed {
+                warn!("unable to replay from slot {:?}, reconciling over rpc", self.replay.as_ref().map(|replay| replay.from_slot));
+                if let Err(e) = self.reconcile().await {
+                    error!("unable to reconcile over rpc, the markets stay stale until their next update - {}", e);
                 }
-                Err(e) => {
-                    error!("received unsupported message - {}", e);
-                    // metrics?
+                self.caught_up();
+
+                // the failed replay ends the stream along with it
+                state_acc_to_market = map_accs_to_market(&self.markets);
+                let filter = self.filter(&state_acc_to_market).await;
+                (sink, stream) = self.client_geyser.subscribe(filter).await;
+            } else if resubscribe {
+                state_acc_to_market = map_accs_to_market(&self.markets);
+
+                // the new filter replaces the previous one on the live stream
+                let filter = self.filter(&state_acc_to_market).await;
+                match sink.send(filter).await {
+                    Ok(_) => info!("resubscribed to {} accounts", state_acc_to_market.len()),
+                    Err(e) => warn!("unable to resubscribe - {}", e),
                 }
             }
         }
@@ -81,3 +433,69 @@ impl<T: Interceptor + Send + Sync> Ingest for GeyserPoolStateIngestor<T> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use solana_sdk::account::Account;
+
+    use super::*;
+    use crate::adapters::amms::{
+        KeyedAccount,
+        meteora_dlmm::{LbPair, MeteoraDlmm, state::bin_array_address},
+        whirlpool::{Whirlpool, WhirlpoolState, state::tick_array_address},
+    };
+
+    fn lb_pair_account(active_id: i32, mints: [Pubkey; 2]) -> Account {
+        let mut lb_pair = LbPair { active_id, bin_step: 10, token_x_mint: mints[0], token_y_mint: mints[1], ..LbPair::default() };
+        lb_pair.parameters.min_bin_id = -443_636;
+        lb_pair.parameters.max_bin_id = 443_636;
+
+        let mut data = vec![0; 8];
+        lb_pair.serialize(&mut data).unwrap();
+        Account { data, ..Account::default() }
+    }
+
+    #[test]
+    fn follows_the_bin_arrays_past_an_array_boundary() {
+        let (key, mints) = (Pubkey::new_unique(), [Pubkey::new_unique(), Pubkey::new_unique()]);
+        let mut lb_pair = MeteoraDlmm::from_keyed_account(&KeyedAccount { key, account: lb_pair_account(0, mints), params: None }).unwrap();
+        let state_acc_to_market: StateAccountToMarket = lb_pair.get_accounts_to_update().into_iter().map(|acc| (acc, key)).collect();
+
+        // still within the arrays -2..=2 followed from bin 0
+        let account_map: AccountMap = [(key, lb_pair_account(69, mints))].into_iter().collect();
+        lb_pair.update(&account_map, Some(1)).unwrap();
+        assert!(moved_accounts(&lb_pair, &state_acc_to_market).is_empty());
+
+        // bin -71 is in array -2, swapping for y passes arrays -3 & -4 which nothing follows yet
+        let account_map: AccountMap = [(key, lb_pair_account(-71, mints))].into_iter().collect();
+        lb_pair.update(&account_map, Some(2)).unwrap();
+        let program_id = lb_pair.program_id();
+        assert_eq!(moved_accounts(&lb_pair, &state_acc_to_market), vec![bin_array_address(&program_id, &key, -3), bin_array_address(&program_id, &key, -4)]);
+    }
+
+    fn whirlpool_account(tick_current_index: i32, mints: [Pubkey; 2]) -> Account {
+        let whirlpool = WhirlpoolState { tick_spacing: 64, tick_current_index, token_mint_a: mints[0], token_mint_b: mints[1], ..WhirlpoolState::default() };
+
+        let mut data = vec![0; 8];
+        whirlpool.serialize(&mut data).unwrap();
+        Account { data, ..Account::default() }
+    }
+
+    #[test]
+    fn follows_the_tick_arrays_past_an_array_boundary() {
+        let (key, mints) = (Pubkey::new_unique(), [Pubkey::new_unique(), Pubkey::new_unique()]);
+        let mut whirlpool = Whirlpool::from_keyed_account(&KeyedAccount { key, account: whirlpool_account(0, mints), params: None }).unwrap();
+        let state_acc_to_market: StateAccountToMarket = whirlpool.get_accounts_to_update().into_iter().map(|acc| (acc, key)).collect();
+
+        // an array spans 88 ticks of 64, the ones starting at -11264..=11264 are followed from tick 0
+        let account_map: AccountMap = [(key, whirlpool_account(5631, mints))].into_iter().collect();
+        whirlpool.update(&account_map, Some(1)).unwrap();
+        assert!(moved_accounts(&whirlpool, &state_acc_to_market).is_empty());
+
+        // tick 5632 starts the next array, swapping b to a passes the one starting at 16896 which nothing follows yet
+        let account_map: AccountMap = [(key, whirlpool_account(5632, mints))].into_iter().collect();
+        whirlpool.update(&account_map, Some(2)).unwrap();
+        assert_eq!(moved_accounts(&whirlpool, &state_acc_to_market), vec![tick_array_address(&whirlpool.program_id(), &key, 16896)]);
+    }
+}
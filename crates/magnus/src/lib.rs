@@ -1,7 +1,9 @@
 pub mod adapters;
+pub mod analytics;
 pub mod api_server;
 pub mod bootstrap;
 pub mod curves;
+pub mod discovery;
 pub mod error;
 pub mod executor;
 pub mod geyser_client;
@@ -9,6 +11,8 @@ pub mod helpers;
 pub mod ingest;
 #[cfg(feature = "metrics")]
 pub mod metrics_server;
+pub mod oracle;
+pub mod snapshot;
 pub mod strategy;
 
 ///   -> the key is the program (amm/pmm) addr | the value is a list of the markets we collect data for
@@ -17,6 +21,15 @@ pub type Programs = std::collections::HashMap<solana_sdk::pubkey::Pubkey, Vec<so
 ///   -> the pubkey is the market addr | the value is the actual market impl
 pub type Markets = std::sync::Arc<std::sync::Mutex<std::collections::HashMap<solana_sdk::pubkey::Pubkey, Box<dyn crate::adapters::amms::Amm>>>>;
 
+///   -> the pubkey is the market addr | the value is its update/quote counters
+pub type MarketsStats = std::sync::Arc<std::sync::Mutex<std::collections::HashMap<solana_sdk::pubkey::Pubkey, crate::analytics::MarketStats>>>;
+
+///   -> the market addrs that are still tracked, yet left out of the routing
+pub type DisabledMarkets = std::sync::Arc<std::sync::Mutex<std::collections::HashSet<solana_sdk::pubkey::Pubkey>>>;
+
+///   -> the usd prices of the mints the oracle follows a price account of
+pub type Oracle = std::sync::Arc<std::sync::Mutex<crate::oracle::PriceService>>;
+
 ///   -> the key is an account addr we receive subscription updates for | the value is the market addr (i.e the 'owner acc' of the key account addr)
 pub type StateAccountToMarket = std::collections::HashMap<solana_sdk::pubkey::Pubkey, solana_sdk::pubkey::Pubkey>;
 
@@ -1,17 +1,25 @@
 pub mod args;
 
-use std::sync::mpsc;
+use std::{sync::mpsc, time::Duration};
 
 use clap::Parser;
 #[cfg(feature = "metrics")]
 use magnus::metrics_server;
 use magnus::{
-    EmptyCtx, Executor, Ingest, Strategy,
+    AccountMap, DisabledMarkets, EmptyCtx, Executor, Ingest, MarketsStats, Oracle, Strategy,
+    adapters::{
+        aggregators::{Aggregators, AggregatorsCfg},
+        amms::registry::{AmmRegistry, get_multiple_accounts},
+    },
+    analytics::StalenessCfg,
     api_server::{self, ApiServerCfg},
     bootstrap,
-    executor::{BaseExecutor, BaseExecutorCfg},
-    ingest::{GeyserPoolStateIngestor, IngestorCfg},
-    strategy::{BaseStrategy, BaseStrategyCfg, DispatchParams, WrappedSwapAndAccountMetas},
+    discovery::{Discovery, DiscoveryCfg},
+    executor::{BaseExecutor, BaseExecutorCfg, ExecutionParams},
+    ingest::{GeyserPoolStateIngestor, IngestorCfg, Replay, SubscriptionUpdate},
+    oracle::{OracleCfg, PriceService},
+    snapshot::{self, AccountSlots, Snapshot, SnapshotCfg},
+    strategy::{BaseStrategy, BaseStrategyCfg, DispatchParams},
 };
 use secrecy::ExposeSecret;
 use tokio::signal::unix::{SignalKind, signal};
@@ -37,10 +45,28 @@ async fn main() {
         yellowstone_url: args.yellowstone_url.map(|v| v.expose_secret().into()),
         yellowstone_x_token: args.yellowstone_x_token.map(|v| v.expose_secret().into()),
         bootstrap_file: args.bootstrap_file,
+        discovery_file: args.discovery_file,
+        oracle_file: args.oracle_file,
+        aggregators_file: args.aggregators_file,
+        snapshot: args.snapshot,
+        snapshot_file: args.snapshot_file,
+        snapshot_interval_secs: args.snapshot_interval_secs,
+        max_replay_slots: args.max_replay_slots,
+        // 0 turns the threshold off
+        staleness: StalenessCfg {
+            max_update_age: (args.max_update_age_secs > 0).then(|| Duration::from_secs(args.max_update_age_secs)),
+            max_update_slot_lag: (args.max_update_slot_lag > 0).then_some(args.max_update_slot_lag),
+            max_failed_updates: (args.max_failed_updates > 0).then_some(args.max_failed_updates),
+            max_failed_quotes: (args.max_failed_quotes > 0).then_some(args.max_failed_quotes),
+        },
         api_server_host: args.api_server_host,
         api_server_workers: args.api_server_workers,
         metrics_server_host: args.metrics_server_host,
         metrics_server_workers: args.metrics_server_workers,
+        max_slot_tolerance: args.max_slot_tolerance,
+        swap_deadline_secs: args.swap_deadline_secs,
+        hop_slippage_bps: args.hop_slippage_bps,
+        quote_deadline_ms: args.quote_deadline_ms,
     };
 
     run(cfg).await;
@@ -51,10 +77,22 @@ pub struct Cfg {
     yellowstone_url: Option<String>,
     yellowstone_x_token: Option<String>,
     bootstrap_file: String,
+    discovery_file: Option<String>,
+    oracle_file: Option<String>,
+    aggregators_file: Option<String>,
+    snapshot: bool,
+    snapshot_file: Option<String>,
+    snapshot_interval_secs: u64,
+    max_replay_slots: u64,
+    staleness: StalenessCfg,
     api_server_host: String,
     api_server_workers: u16,
     metrics_server_host: String,
     metrics_server_workers: u16,
+    max_slot_tolerance: u64,
+    swap_deadline_secs: u64,
+    hop_slippage_bps: u16,
+    quote_deadline_ms: u64,
 }
 
 async fn run(cfg: Cfg) {
@@ -67,7 +105,7 @@ async fn run(cfg: Cfg) {
     metrics_server::initialise_prometheus_description_metrics();
 
     let client_http = std::sync::Arc::new(solana_client::nonblocking::rpc_client::RpcClient::new(cfg.http_url.clone()));
-    let client_http_blocking = solana_client::rpc_client::RpcClient::new(cfg.http_url.clone());
+    let client_http_blocking = std::sync::Arc::new(solana_client::rpc_client::RpcClient::new(cfg.http_url.clone()));
     let client_geyser = GeyserGrpcClient::build_from_shared(cfg.yellowstone_url.unwrap_or_default())
         .expect("invalid grpc url")
         .tls_config(ClientTlsConfig::new().with_native_roots())
@@ -79,10 +117,70 @@ async fn run(cfg: Cfg) {
         .await
         .expect("unable to connect");
 
-    let pmms = bootstrap::load(&cfg.bootstrap_file, &client_http_blocking).expect("unable to load bootstrap file");
+    // the accounts fetched from here on are at least as recent
+    let slot = client_http_blocking.get_slot().expect("unable to fetch the current slot");
+
+    let snapshot = match (&cfg.snapshot_file, cfg.snapshot) {
+        (Some(file), true) => Some(Snapshot::read(file).expect("unable to read snapshot file")),
+        _ => None,
+    };
+    let (mut pmms, mut account_map, account_slots) = match &snapshot {
+        Some(snapshot) => {
+            let (mut pmms, missing) = snapshot.restore(&AmmRegistry::default());
+            info!("restored {} markets from the snapshot at slot {}, {} to load over rpc", pmms.len(), snapshot.slot, missing.len());
+            let (loaded, loaded_account_map) =
+                AmmRegistry::default().load_with_account_map(&client_http_blocking, &missing).expect("unable to load the markets missing from the snapshot");
+            pmms.extend(loaded);
+
+            let mut account_map = snapshot.account_map();
+            account_map.extend(loaded_account_map);
+            (pmms, account_map, snapshot.account_slots())
+        }
+        None => (bootstrap::load(&cfg.bootstrap_file, &client_http_blocking).expect("unable to load bootstrap file"), AccountMap::default(), AccountSlots::default()),
+    };
+    let stats = MarketsStats::new(std::sync::Mutex::new(snapshot.as_ref().map(|snapshot| snapshot::restored_stats(snapshot.slot, &pmms)).unwrap_or_default()));
+
+    let mut discovery = cfg.discovery_file.map(|file| Discovery::new(DiscoveryCfg::load(&file).expect("unable to load discovery file"), AmmRegistry::default()));
+    let mut discovered_account_map = AccountMap::default();
+    if let Some(discovery) = discovery.as_mut() {
+        discovery.mark_seen(pmms.iter().map(|pmm| pmm.key()));
+        let (discovered, account_map) = discovery.discover(&client_http_blocking).expect("unable to discover pools");
+        pmms.extend(discovered);
+        discovered_account_map = account_map;
+    }
     let markets = bootstrap::into_markets(pmms);
-    let account_map = bootstrap::acquire_account_map(&client_http_blocking, &markets).expect("unable to acquire account map");
+    if snapshot.is_none() {
+        account_map = bootstrap::acquire_account_map(&client_http_blocking, &markets).expect("unable to acquire account map");
+    }
+    account_map.extend(discovered_account_map);
     debug!(?account_map);
+    let disabled = DisabledMarkets::default();
+
+    let mut price_service = cfg.oracle_file.map(|file| PriceService::new(OracleCfg::load(&file).expect("unable to load oracle file"))).unwrap_or_default();
+    let oracle_accounts: Vec<_> = price_service.feeds().into_iter().chain(price_service.mints()).collect();
+    if !oracle_accounts.is_empty() {
+        let accounts = get_multiple_accounts(&client_http_blocking, &oracle_accounts).expect("unable to fetch the oracle accounts");
+        price_service.prime(&accounts).expect("unable to prime the oracle");
+    }
+    let oracle = Oracle::new(std::sync::Mutex::new(price_service));
+
+    // the restored markets are caught up either by geyser replaying the slots since the snapshot, or past its reach, by an rpc diff
+    let mut caught_up_rx = None;
+    let replay = match &snapshot {
+        Some(snapshot) if slot.saturating_sub(snapshot.slot) <= cfg.max_replay_slots => {
+            let (caught_up_tx, rx) = tokio::sync::oneshot::channel();
+            caught_up_rx = Some(rx);
+            Some(Replay { from_slot: snapshot.replay_from(), until_slot: slot, caught_up_tx })
+        }
+        Some(snapshot) => {
+            let keys: Vec<_> = account_map.keys().copied().collect();
+            let fetched = get_multiple_accounts(&client_http_blocking, &keys).expect("unable to reconcile the snapshot");
+            let changed = snapshot::reconcile(&markets, &stats, &mut account_map, fetched, slot);
+            info!("snapshot {} slots behind, reconciled {} changed accounts over rpc", slot - snapshot.slot, changed);
+            None
+        }
+        None => None,
+    };
 
     let bare_ctx = EmptyCtx;
 
@@ -94,26 +192,70 @@ async fn run(cfg: Cfg) {
      */
     /* sender == API server | receiver = Solver thread */
     let (request_tx, request_rx) = mpsc::channel::<DispatchParams>();
-    /* sender = Solver thread | receiver = Executor thread */
-    let (response_tx, response_rx) = mpsc::channel::<WrappedSwapAndAccountMetas>();
+    /* sender = Solver thread & API server (aggregator swaps) | receiver = Executor thread */
+    let (response_tx, response_rx) = mpsc::channel::<ExecutionParams>();
+    /* sender = API server (hotloaded markets) | receiver = Ingestor thread */
+    let (subscription_tx, subscription_rx) = tokio::sync::mpsc::unbounded_channel::<SubscriptionUpdate>();
 
     {
-        let cfg = IngestorCfg { client_geyser, client_default: client_http.clone(), markets: markets.clone(), account_map };
+        let cfg = IngestorCfg {
+            client_geyser,
+            client_default: client_http.clone(),
+            markets: markets.clone(),
+            account_map,
+            stats: stats.clone(),
+            subscription_rx,
+            subscription_tx: subscription_tx.clone(),
+            discovery,
+            slot,
+            account_slots,
+            snapshot: cfg.snapshot_file.map(|file| SnapshotCfg { file, interval: Duration::from_secs(cfg.snapshot_interval_secs) }),
+            replay,
+            staleness: cfg.staleness,
+            oracle: oracle.clone(),
+        };
         tokio::spawn(async move { GeyserPoolStateIngestor::new(cfg).ingest(bare_ctx).await });
     };
 
     {
-        let cfg = BaseStrategyCfg { markets, api_server_rx: request_rx, tx: response_tx };
+        let cfg = BaseStrategyCfg {
+            markets: markets.clone(),
+            disabled: disabled.clone(),
+            stats: stats.clone(),
+            oracle: oracle.clone(),
+            api_server_rx: request_rx,
+            tx: response_tx.clone(),
+            hop_slippage_bps: cfg.hop_slippage_bps,
+        };
         tokio::spawn(async move { BaseStrategy::new(cfg).compute(bare_ctx).await });
     };
 
     {
-        let cfg = BaseExecutorCfg { client: client_http, solver_rx: response_rx };
+        let cfg = BaseExecutorCfg { client: client_http, solver_rx: response_rx, max_slot_tolerance: cfg.max_slot_tolerance, deadline_secs: cfg.swap_deadline_secs };
         tokio::spawn(async move { BaseExecutor::new(cfg).execute(bare_ctx).await });
     };
 
+    // nothing's served off the restored state until it's caught up
+    if let Some(caught_up_rx) = caught_up_rx {
+        info!("waiting for the replay to catch up");
+        caught_up_rx.await.expect("the ingestor stopped before catching up");
+    }
+
     let server_handle = {
-        let cfg = ApiServerCfg { host: cfg.api_server_host, workers: cfg.api_server_workers, request_tx };
+        let cfg = ApiServerCfg {
+            host: cfg.api_server_host,
+            workers: cfg.api_server_workers,
+            request_tx,
+            executor_tx: response_tx,
+            markets,
+            disabled,
+            stats,
+            oracle,
+            client: client_http_blocking,
+            subscription_tx,
+            quote_deadline: Duration::from_millis(cfg.quote_deadline_ms),
+            aggregators: Aggregators::new(cfg.aggregators_file.map(|file| AggregatorsCfg::load(&file).expect("unable to load aggregators file")).unwrap_or_default()),
+        };
         let server = api_server::ApiServer::new(cfg).expect("failed to create server");
         let handle = server.handle().clone();
         tokio::spawn(async move { server.start().await.expect("failed to start server") });
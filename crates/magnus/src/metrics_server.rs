@@ -1,5 +1,5 @@
 use actix_web::{App, HttpResponse, HttpServer, middleware::Logger, web};
-use metrics::describe_counter;
+use metrics::{describe_counter, describe_gauge};
 use metrics_exporter_prometheus::PrometheusHandle;
 
 #[derive(Debug, Clone)]
@@ -49,4 +49,6 @@ async fn metrics(handle: web::Data<PrometheusHandle>) -> HttpResponse {
 pub fn initialise_prometheus_description_metrics() {
     describe_counter!("API HITS", "The amount of hits experienced by the API since the server started");
     describe_counter!("METRICS HITS", "The amount of hits experienced by /metrics since the (metrics) server started");
+    describe_counter!("MARKETS GONE STALE", "The amount of times a market was left out of the routing, by reason");
+    describe_gauge!("STALE MARKETS", "The amount of markets currently left out of the routing");
 }
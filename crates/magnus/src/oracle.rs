@@ -0,0 +1,262 @@
+//! USD prices off the Pyth price accounts, followed through the ingest like the market accounts.
+//!
+//! The prices value the quotes in USD and tell the quotes that stray too far from them, i.e.
+//! a pmm quoting off an outdated or broken price, before anything gets executed.
+
+use std::{
+    collections::HashMap,
+    fs,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use eyre::eyre;
+use pyth_sdk_solana::state::{GenericPriceAccount, load_price_account};
+use serde::Deserialize;
+use serde_with::{DisplayFromStr, serde_as};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use tracing::warn;
+
+use crate::{
+    AccountMap,
+    adapters::{IntQuoteResponse, Quote, QuoteParams, token_meta::TokenMeta},
+};
+
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct OracleCfg {
+    /// The price account of each mint, priced in USD.
+    #[serde_as(as = "HashMap<DisplayFromStr, DisplayFromStr>")]
+    #[serde(default)]
+    pub feeds: HashMap<Pubkey, Pubkey>,
+    /// The oldest a price may be published to be used, any age goes if unset.
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+    /// How far a quote's USD out value may deviate from its USD in value, no check if unset.
+    #[serde(default)]
+    pub max_deviation_bps: Option<u16>,
+}
+
+impl OracleCfg {
+    pub fn load(file: &str) -> eyre::Result<Self> {
+        let json = fs::read_to_string(file)?;
+
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UsdPrice {
+    pub price: f64,
+    // the confidence interval, around the price
+    pub conf: f64,
+    // unix timestamp
+    pub publish_time: i64,
+}
+
+impl From<pyth_sdk::Price> for UsdPrice {
+    fn from(price: pyth_sdk::Price) -> Self {
+        let scale = 10f64.powi(price.expo);
+        UsdPrice { price: price.price as f64 * scale, conf: price.conf as f64 * scale, publish_time: price.publish_time }
+    }
+}
+
+/// A quote straying from the oracle prices by more than the configured band.
+#[derive(Debug, thiserror::Error)]
+#[error("quote deviates from the oracle by {deviation_bps}bps (${in_usd_value:.4} in, ${out_usd_value:.4} out)")]
+pub struct Deviation {
+    pub deviation_bps: u64,
+    pub in_usd_value: f64,
+    pub out_usd_value: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct PriceService {
+    // the key is the price account | the value is the mint it prices
+    feeds: HashMap<Pubkey, Pubkey>,
+    // the decimals of the priced mints, the prices being per whole token
+    decimals: HashMap<Pubkey, u8>,
+    prices: HashMap<Pubkey, UsdPrice>,
+    max_age: Option<Duration>,
+    max_deviation_bps: Option<u16>,
+}
+
+impl PriceService {
+    pub fn new(cfg: OracleCfg) -> Self {
+        PriceService {
+            feeds: cfg.feeds.into_iter().map(|(mint, feed)| (feed, mint)).collect(),
+            max_age: cfg.max_age_secs.map(Duration::from_secs),
+            max_deviation_bps: cfg.max_deviation_bps,
+            ..PriceService::default()
+        }
+    }
+
+    /// The price accounts to follow for updates.
+    pub fn feeds(&self) -> Vec<Pubkey> {
+        self.feeds.keys().copied().collect()
+    }
+
+    pub fn mints(&self) -> Vec<Pubkey> {
+        self.feeds.values().copied().collect()
+    }
+
+    pub fn is_feed(&self, key: &Pubkey) -> bool {
+        self.feeds.contains_key(key)
+    }
+
+    /// Primes the service with the price accounts and the mint accounts of the priced mints.
+    /// The missing ones are skipped, their mints left unpriced.
+    pub fn prime(&mut self, account_map: &AccountMap) -> eyre::Result<()> {
+        for mint in self.mints() {
+            let Some(account) = account_map.get(&mint) else {
+                warn!("mint {} not found, it's left unpriced", mint);
+                continue;
+            };
+            self.decimals.insert(mint, TokenMeta::from_mint_account(account)?.decimals);
+        }
+        for feed in self.feeds() {
+            let Some(account) = account_map.get(&feed) else {
+                warn!("price account {} not found, its mint is left unpriced", feed);
+                continue;
+            };
+            self.update(&feed, account)?;
+        }
+
+        Ok(())
+    }
+
+    /// Parses the price account into the price of its mint.
+    pub fn update(&mut self, key: &Pubkey, account: &Account) -> eyre::Result<()> {
+        let mint = *self.feeds.get(key).ok_or_else(|| eyre!("{} is not a followed price account", key))?;
+        let price_account: &GenericPriceAccount<32, ()> = load_price_account(&account.data).map_err(|e| eyre!("unable to parse price account {}: {:?}", key, e))?;
+        self.set_price(mint, price_account.to_price_feed(key).get_price_unchecked().into());
+
+        Ok(())
+    }
+
+    pub fn set_price(&mut self, mint: Pubkey, price: UsdPrice) {
+        self.prices.insert(mint, price);
+    }
+
+    pub fn set_decimals(&mut self, mint: Pubkey, decimals: u8) {
+        self.decimals.insert(mint, decimals);
+    }
+
+    /// The USD price of a whole token, unless unknown or older than the max age.
+    pub fn usd_price(&self, mint: &Pubkey) -> Option<f64> {
+        let price = self.prices.get(mint)?;
+        if let Some(max_age) = self.max_age {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+            if now.saturating_sub(price.publish_time) > max_age.as_secs() as i64 {
+                return None;
+            }
+        }
+
+        Some(price.price)
+    }
+
+    /// The USD value of a raw amount.
+    pub fn usd_value(&self, mint: &Pubkey, amount: u64) -> Option<f64> {
+        let decimals = self.decimals.get(mint)?;
+        Some(amount as f64 / 10f64.powi(*decimals as i32) * self.usd_price(mint)?)
+    }
+
+    /// Fails the quote whose USD out value deviates from its USD in value by more than the band.
+    /// There's no telling without both prices, in which case the quote passes.
+    pub fn check(&self, params: &QuoteParams, quote: &Quote) -> Result<(), Deviation> {
+        let Some(max_deviation_bps) = self.max_deviation_bps else { return Ok(()) };
+        let (Some(in_usd_value), Some(out_usd_value)) = (self.usd_value(&params.input_mint, quote.in_amount), self.usd_value(&params.output_mint, quote.out_amount)) else {
+            return Ok(());
+        };
+        if in_usd_value <= 0.0 {
+            return Ok(());
+        }
+
+        let deviation_bps = ((out_usd_value - in_usd_value).abs() / in_usd_value * 10_000.0).round() as u64;
+        if deviation_bps > max_deviation_bps as u64 {
+            return Err(Deviation { deviation_bps, in_usd_value, out_usd_value });
+        }
+
+        Ok(())
+    }
+
+    /// Values the quote's in and out amounts in USD, as far as the prices go.
+    pub fn annotate(&self, quote: &mut IntQuoteResponse) {
+        let (Ok(input_mint), Ok(output_mint)) = (quote.input_mint.parse::<Pubkey>(), quote.output_mint.parse::<Pubkey>()) else { return };
+
+        quote.in_usd_value = self.usd_value(&input_mint, quote.in_amount);
+        quote.out_usd_value = self.usd_value(&output_mint, quote.out_amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::SwapMode;
+
+    fn service(max_deviation_bps: Option<u16>) -> (PriceService, Pubkey, Pubkey) {
+        let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut service = PriceService::new(OracleCfg { max_age_secs: Some(60), max_deviation_bps, ..OracleCfg::default() });
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+
+        service.set_decimals(sol, 9);
+        service.set_decimals(usdc, 6);
+        service.set_price(sol, pyth_sdk::Price { price: 15_000_000_000, conf: 1_000_000, expo: -8, publish_time: now }.into());
+        service.set_price(usdc, UsdPrice { price: 1.0, conf: 0.0, publish_time: now });
+
+        (service, sol, usdc)
+    }
+
+    fn quote(in_amount: u64, out_amount: u64) -> Quote {
+        Quote { in_amount, out_amount, ..Quote::default() }
+    }
+
+    #[test]
+    fn values_amounts_in_usd() {
+        let (mut service, sol, usdc) = service(None);
+
+        assert_eq!(service.usd_price(&sol), Some(150.0));
+        assert_eq!(service.usd_value(&sol, 2_000_000_000), Some(300.0));
+        assert_eq!(service.usd_value(&usdc, 1_500_000), Some(1.5));
+        assert_eq!(service.usd_value(&Pubkey::new_unique(), 1), None);
+
+        // too old to go by
+        service.set_price(sol, UsdPrice { price: 150.0, conf: 0.0, publish_time: 0 });
+        assert_eq!(service.usd_price(&sol), None);
+
+        let mut response =
+            IntQuoteResponse { input_mint: usdc.to_string(), output_mint: sol.to_string(), in_amount: 150_000_000, out_amount: 1_000_000_000, ..IntQuoteResponse::default() };
+        service.annotate(&mut response);
+        assert_eq!(response.in_usd_value, Some(150.0));
+        assert_eq!(response.out_usd_value, None);
+    }
+
+    #[test]
+    fn primes_without_the_missing_accounts() {
+        let (mint, feed) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut service = PriceService::new(OracleCfg { feeds: HashMap::from([(mint, feed)]), ..OracleCfg::default() });
+
+        service.prime(&AccountMap::default()).unwrap();
+        assert_eq!(service.usd_price(&mint), None);
+    }
+
+    #[test]
+    fn rejects_quotes_outside_the_band() {
+        let (service, sol, usdc) = service(Some(100));
+        let params = QuoteParams { input_mint: sol, output_mint: usdc, amount: 1_000_000_000, swap_mode: SwapMode::ExactIn };
+
+        // 1 SOL for 149 USDC, 67bps off
+        assert!(service.check(&params, &quote(1_000_000_000, 149_000_000)).is_ok());
+        // 1 SOL for 140 USDC, 667bps off
+        let deviation = service.check(&params, &quote(1_000_000_000, 140_000_000)).unwrap_err();
+        assert_eq!(deviation.deviation_bps, 667);
+        // a quote too good to be true is just as off
+        assert!(service.check(&params, &quote(1_000_000_000, 160_000_000)).is_err());
+
+        // unpriced mints pass
+        let params = QuoteParams { output_mint: Pubkey::new_unique(), ..params };
+        assert!(service.check(&params, &quote(1_000_000_000, 1)).is_ok());
+        // as does everything without a band
+        let (service, sol, usdc) = self::service(None);
+        assert!(service.check(&QuoteParams { input_mint: sol, output_mint: usdc, ..params }, &quote(1_000_000_000, 1)).is_ok());
+    }
+}
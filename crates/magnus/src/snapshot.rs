@@ -0,0 +1,235 @@
+//! Snapshots of the tracked markets and the accounts they're updated from, for a warm restart.
+//!
+//! A cold start fetches every account over rpc and rebuilds each market from scratch. Restoring from
+//! a snapshot skips both, though the state is only as recent as the snapshot: the restored markets stay
+//! out of the routing (see [`MarketStats::restored`]) until they're caught up, either by replaying the geyser
+//! stream from the snapshot's slot or, once that's out of reach, by diffing the accounts over rpc.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{BufReader, BufWriter},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use tracing::warn;
+
+use crate::{
+    AccountMap, Markets, MarketsStats,
+    adapters::amms::{Amm, KeyedAccount, registry::AmmRegistry},
+    analytics::MarketStats,
+};
+
+/// The slots replayed on top of the snapshot's, for the updates of the last few slots that may not have made it in.
+const REPLAY_MARGIN_SLOTS: u64 = 8;
+
+///   -> the key is the account that we follow for updates | the value is the slot of its last update
+pub type AccountSlots = HashMap<Pubkey, u64, ahash::RandomState>;
+
+/// A market left for [`AmmRegistry::load`], along with its params.
+pub type Unrestored = (Pubkey, Option<serde_json::Value>);
+
+/// Where and how often the ingestor persists its state.
+#[derive(Clone, Debug)]
+pub struct SnapshotCfg {
+    pub file: String,
+    pub interval: Duration,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The slot the accounts are current as of.
+    pub slot: u64,
+    pub markets: Vec<SnapshotMarket>,
+    pub accounts: Vec<SnapshotAccount>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotMarket {
+    pub key: Pubkey,
+    // json encoded, bincode can't tell the shape of an untyped `serde_json::Value`
+    pub params: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotAccount {
+    pub key: Pubkey,
+    // 0 for the accounts fetched over rpc and not updated since
+    pub slot: u64,
+    pub account: Account,
+}
+
+impl Snapshot {
+    pub fn capture(slot: u64, markets: &Markets, account_map: &AccountMap, account_slots: &AccountSlots) -> Self {
+        let markets = markets.lock().unwrap().values().map(|amm| SnapshotMarket { key: amm.key(), params: amm.params().map(|params| params.to_string()) }).collect();
+        let accounts =
+            account_map.iter().map(|(key, account)| SnapshotAccount { key: *key, slot: account_slots.get(key).copied().unwrap_or_default(), account: account.clone() }).collect();
+
+        Snapshot { slot, markets, accounts }
+    }
+
+    /// Writes the snapshot next to the file and moves it over, a failure midway leaves the previous one intact.
+    pub fn write(&self, file: &str) -> eyre::Result<()> {
+        let tmp = format!("{}.tmp", file);
+        let mut writer = BufWriter::new(fs::File::create(&tmp)?);
+        bincode::serde::encode_into_std_write(self, &mut writer, bincode::config::standard())?;
+        writer.into_inner()?.sync_all()?;
+        fs::rename(&tmp, file)?;
+
+        Ok(())
+    }
+
+    pub fn read(file: &str) -> eyre::Result<Self> {
+        let mut reader = BufReader::new(fs::File::open(file)?);
+
+        Ok(bincode::serde::decode_from_std_read(&mut reader, bincode::config::standard())?)
+    }
+
+    /// The slot the geyser stream is replayed from.
+    pub fn replay_from(&self) -> u64 {
+        self.slot.saturating_sub(REPLAY_MARGIN_SLOTS)
+    }
+
+    pub fn account_map(&self) -> AccountMap {
+        self.accounts.iter().map(|account| (account.key, account.account.clone())).collect()
+    }
+
+    pub fn account_slots(&self) -> AccountSlots {
+        self.accounts.iter().filter(|account| account.slot > 0).map(|account| (account.key, account.slot)).collect()
+    }
+
+    /// Rebuilds the markets out of the snapshot's accounts.
+    ///
+    /// The markets that can't be (i.e. their adapter changed since) are handed back along with their params, to be loaded over rpc instead.
+    pub fn restore(&self, registry: &AmmRegistry) -> (Vec<Box<dyn Amm>>, Vec<Unrestored>) {
+        let account_map = self.account_map();
+        let (mut amms, mut missing) = (vec![], vec![]);
+
+        for market in &self.markets {
+            let params = market.params.as_deref().and_then(|params| serde_json::from_str(params).ok());
+            let Some(account) = account_map.get(&market.key) else {
+                missing.push((market.key, params));
+                continue;
+            };
+
+            let mut amm = match registry.from_keyed_account(&KeyedAccount { key: market.key, account: account.clone(), params: params.clone() }) {
+                Ok(amm) => amm,
+                Err(e) => {
+                    warn!("unable to restore market {}: {}", market.key, e);
+                    missing.push((market.key, params));
+                    continue;
+                }
+            };
+
+            // the second round reads the dynamic accounts picked on the first one (i.e. tick arrays)
+            let rounds = if amm.has_dynamic_accounts() { 2 } else { 1 };
+            for _ in 0..rounds {
+                if let Err(e) = amm.update(&account_map, Some(self.slot)) {
+                    warn!("unable to prime market {}: {}", market.key, e);
+                }
+            }
+            amms.push(amm);
+        }
+
+        (amms, missing)
+    }
+}
+
+/// The stats of the markets restored from the snapshot, stale until they're caught up.
+pub fn restored_stats(slot: u64, amms: &[Box<dyn Amm>]) -> HashMap<Pubkey, MarketStats> {
+    amms.iter().map(|amm| (amm.key(), MarketStats::restored(slot))).collect()
+}
+
+/// Every market is now caught up with the chain.
+pub fn mark_fresh(stats: &MarketsStats) {
+    stats.lock().unwrap().values_mut().for_each(|stats| stats.restored = false);
+}
+
+/// Replaces the accounts with the ones just fetched, updates the markets following any that changed and marks every market fresh.
+///
+/// Returns the number of accounts that changed (or were closed) since.
+pub fn reconcile(markets: &Markets, stats: &MarketsStats, account_map: &mut AccountMap, fetched: AccountMap, slot: u64) -> usize {
+    let changed: HashSet<Pubkey> = account_map.iter().filter(|(key, account)| fetched.get(key) != Some(account)).map(|(key, _)| *key).collect();
+    *account_map = fetched;
+
+    markets.lock().unwrap().values_mut().filter(|amm| amm.get_accounts_to_update().iter().any(|key| changed.contains(key))).for_each(|amm| {
+        if let Err(e) = amm.update(account_map, Some(slot)) {
+            warn!("unable to update market {}: {}", amm.key(), e);
+        }
+    });
+    mark_fresh(stats);
+
+    changed.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use magnus_shared::amm_raydium_amm_v4;
+
+    use super::*;
+    use crate::{adapters::amms::raydium_amm_v4::AmmInfo, bootstrap};
+
+    fn market_account() -> Account {
+        let mut data = vec![];
+        AmmInfo::default().serialize(&mut data).unwrap();
+        Account { data, owner: Pubkey::new_from_array(amm_raydium_amm_v4::id().to_bytes()), ..Account::default() }
+    }
+
+    #[test]
+    fn round_trips_through_the_file() {
+        let (market, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let markets = bootstrap::into_markets(vec![]);
+        let account_map: AccountMap = [(market, market_account()), (other, Account { lamports: 1, ..Account::default() })].into_iter().collect();
+        let account_slots: AccountSlots = [(other, 42)].into_iter().collect();
+
+        let snapshot = Snapshot::capture(50, &markets, &account_map, &account_slots);
+        let file = std::env::temp_dir().join(format!("magnus-snapshot-{}.bin", Pubkey::new_unique()));
+        let file = file.to_str().unwrap();
+        snapshot.write(file).unwrap();
+        let read = Snapshot::read(file).unwrap();
+        fs::remove_file(file).unwrap();
+
+        assert_eq!(read.slot, 50);
+        assert_eq!(read.replay_from(), 50 - REPLAY_MARGIN_SLOTS);
+        assert_eq!(read.account_map(), account_map);
+        // the accounts never updated over geyser carry no slot
+        assert_eq!(read.account_slots(), account_slots);
+    }
+
+    #[test]
+    fn restores_the_markets_it_has_the_accounts_of() {
+        let (market, gone) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let snapshot = Snapshot {
+            slot: 50,
+            markets: vec![SnapshotMarket { key: market, params: None }, SnapshotMarket { key: gone, params: Some(r#"{"pubkey":"x"}"#.to_string()) }],
+            accounts: vec![SnapshotAccount { key: market, slot: 50, account: market_account() }],
+        };
+
+        let (amms, missing) = snapshot.restore(&AmmRegistry::default());
+        assert_eq!(amms.iter().map(|amm| amm.key()).collect::<Vec<_>>(), vec![market]);
+        assert_eq!(missing, vec![(gone, Some(serde_json::json!({"pubkey": "x"})))]);
+
+        let stats = restored_stats(snapshot.slot, &amms);
+        assert!(stats[&market].restored);
+        assert_eq!(stats[&market].last_update_slot, Some(50));
+    }
+
+    #[test]
+    fn reconciles_the_changed_accounts() {
+        let (unchanged, changed, closed) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let account = |lamports| Account { lamports, ..Account::default() };
+        let mut account_map: AccountMap = [(unchanged, account(1)), (changed, account(1)), (closed, account(1))].into_iter().collect();
+        let fetched: AccountMap = [(unchanged, account(1)), (changed, account(2))].into_iter().collect();
+
+        let markets = bootstrap::into_markets(vec![]);
+        let stats = MarketsStats::default();
+        stats.lock().unwrap().insert(Pubkey::new_unique(), MarketStats::restored(1));
+
+        assert_eq!(reconcile(&markets, &stats, &mut account_map, fetched.clone(), 2), 2);
+        assert_eq!(account_map, fetched);
+        assert!(stats.lock().unwrap().values().all(|stats| !stats.restored));
+    }
+}
@@ -1,46 +1,90 @@
 use std::sync::mpsc::{Receiver, Sender};
 
-use solana_sdk::pubkey::Pubkey;
-use tracing::info;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use spl_associated_token_account::get_associated_token_address;
+use tracing::{info, warn};
 
 use crate::{
-    Markets, Strategy, StrategyCtx,
+    DisabledMarkets, Markets, MarketsStats, Oracle, Strategy, StrategyCtx,
     adapters::{IntQuoteResponse, IntSwapResponse, Quote, QuoteParams, SwapAndAccountMetas, SwapParams, amms::Target},
+    executor::ExecutionParams,
 };
 
 pub struct BaseStrategyCfg {
     pub markets: Markets,
+    pub disabled: DisabledMarkets,
+    pub stats: MarketsStats,
+    pub oracle: Oracle,
     pub api_server_rx: Receiver<DispatchParams>,
-    pub tx: Sender<WrappedSwapAndAccountMetas>,
+    pub tx: Sender<ExecutionParams>,
+    pub hop_slippage_bps: u16,
 }
 
 pub struct BaseStrategy {
     pub markets: Markets,
+    // the markets disabled through the api server, kept up to date but never routed through
+    disabled: DisabledMarkets,
+    // the quotes are recorded per market, successful or not
+    stats: MarketsStats,
+    // the quotes straying too far from its prices are dropped
+    oracle: Oracle,
     // the received quote/swap request from the api server
     api_server_rx: Receiver<DispatchParams>,
     // the response we send to the executor if the request we received is swap-related
     // alternatively we immediately respond to the server if the request:
     // - is for quote
     // - fails for one reason or another
-    tx: Sender<WrappedSwapAndAccountMetas>,
+    tx: Sender<ExecutionParams>,
+    // the slippage tolerated on each leg's quoted out amount before the router aborts the swap
+    hop_slippage_bps: u16,
 }
 
 pub struct WrappedSwapAndAccountMetas {
     pub response_tx: oneshot::Sender<DispatchResponse>,
+    // the accounts of each leg, in the order they're hopped through
     pub metas: Vec<SwapAndAccountMetas>,
     pub input_mint: Pubkey,
     pub output_mint: Pubkey,
+    pub src_ta: Pubkey,
+    pub dst_ta: Pubkey,
+    pub amount_in: u64,
+    // the route's quoted out amount, and what it's allowed to degrade to
+    pub expect_amount_out: u64,
+    pub min_return: u64,
+    // the slot of the state the route was quoted against
+    pub context_slot: u64,
+    // the per split/hop/dex min outs, shaped like the router's routes
+    pub hop_min_outs: Vec<Vec<Vec<u64>>>,
+    // signs & pays for the swap
+    pub signer: Keypair,
 }
 
 impl BaseStrategy {
     pub fn new(cfg: BaseStrategyCfg) -> Self {
-        BaseStrategy { markets: cfg.markets, api_server_rx: cfg.api_server_rx, tx: cfg.tx }
+        BaseStrategy {
+            markets: cfg.markets,
+            disabled: cfg.disabled,
+            stats: cfg.stats,
+            oracle: cfg.oracle,
+            api_server_rx: cfg.api_server_rx,
+            tx: cfg.tx,
+            hop_slippage_bps: cfg.hop_slippage_bps,
+        }
+    }
+
+    /// Discounts a leg's quoted out amount by `hop_slippage_bps` to get its onchain min out.
+    pub fn hop_min_out(&self, quote: &Quote) -> u64 {
+        let min_out = (quote.out_amount as u128) * (10_000 - self.hop_slippage_bps as u128) / 10_000;
+        min_out as u64
     }
 
     pub fn whole_swap_route(&self, input_mint: Pubkey, output_mint: Pubkey, amount: u64) -> eyre::Result<Option<(Pubkey, Quote)>> {
         let default_pubkey = Pubkey::default();
 
         let mut markets = self.markets.lock().unwrap();
+        let disabled = self.disabled.lock().unwrap();
+        let mut stats = self.stats.lock().unwrap();
+        let oracle = self.oracle.lock().unwrap();
         info!("Markets: {:?}", markets);
         let matching_markets: Vec<(Pubkey, Quote)> = markets
             .iter_mut()
@@ -50,6 +94,16 @@ impl BaseStrategy {
                     return None;
                 }
 
+                if disabled.contains(market_key) {
+                    info!("Market {} is disabled", market_key);
+                    return None;
+                }
+
+                if let Some(reason) = stats.get(market_key).and_then(|stats| stats.stale_reason()) {
+                    info!("Market {} is stale | {}", market_key, reason);
+                    return None;
+                }
+
                 let reserve_mints = amm.get_reserve_mints();
                 if reserve_mints.contains(&default_pubkey) {
                     info!("Market {} has default pubkey as reserve", market_key);
@@ -62,7 +116,11 @@ impl BaseStrategy {
                 if has_input && has_output {
                     let quote_params = QuoteParams { input_mint, output_mint, amount, swap_mode: crate::adapters::SwapMode::ExactIn };
 
-                    match amm.quote(&quote_params) {
+                    // a misquote counts as a failed quote
+                    let quote = amm.quote(&quote_params).and_then(|quote| oracle.check(&quote_params, &quote).map(|_| quote).map_err(eyre::Report::from));
+                    stats.entry(*market_key).or_default().record_quote(&quote_params, &quote);
+
+                    match quote {
                         Ok(quote) => {
                             info!(?market_key, ?quote);
                             Some((*market_key, quote))
@@ -82,6 +140,24 @@ impl BaseStrategy {
 
         Ok(best_market)
     }
+
+    /// The legs of the best route, each with the accounts it's swapped through and its own quote.
+    /// The whole route is a single leg through the best market for now.
+    pub fn swap_legs(&self, params: &SwapParams) -> eyre::Result<Option<Vec<(SwapAndAccountMetas, Quote)>>> {
+        let Some((market_key, quote)) = self.whole_swap_route(params.input_mint, params.output_mint, params.amount)? else {
+            return Ok(None);
+        };
+
+        let markets = self.markets.lock().unwrap();
+        let amm = markets.get(&market_key).ok_or_else(|| eyre::eyre!("market {} is no longer tracked", market_key))?;
+
+        Ok(Some(vec![(amm.get_swap_and_account_metas(params)?, quote)]))
+    }
+
+    /// The per split/hop/dex min outs of a single split hopping through the legs, a dex each.
+    pub fn hop_min_outs(&self, legs: &[(SwapAndAccountMetas, Quote)]) -> Vec<Vec<Vec<u64>>> {
+        vec![legs.iter().map(|(_, quote)| vec![self.hop_min_out(quote)]).collect()]
+    }
 }
 
 #[async_trait::async_trait]
@@ -120,15 +196,66 @@ impl Strategy for BaseStrategy {
                 // that then proceeds to evaluate the path, attach the relevant accounts,
                 // craft the instruction data payload and send the tx/bundles towards
                 // an RPC
-                DispatchParams::Swap { params, response_tx } => {
+                DispatchParams::Swap { params, signer, response_tx } => {
+                    // swapped from & into the signer's token accounts
+                    let params = SwapParams {
+                        src_ta: get_associated_token_address(&signer.pubkey(), &params.input_mint),
+                        dst_ta: get_associated_token_address(&signer.pubkey(), &params.output_mint),
+                        token_transfer_authority: signer.pubkey(),
+                        ..params
+                    };
+
+                    let legs = match self.swap_legs(&params) {
+                        Ok(Some(legs)) => legs,
+                        Ok(None) => {
+                            info!("no route found");
+                            // there's nothing to execute, so the swap is sent straight back
+                            if let Ok(()) = response_tx.send(DispatchResponse::Swap(IntSwapResponse {
+                                source: Target::AMMs,
+                                input_mint: params.input_mint.to_string(),
+                                output_mint: params.output_mint.to_string(),
+                                ..Default::default()
+                            })) {
+                                info!("sent from `Strategy` towards `API Server::swap`");
+                            }
+                            continue;
+                        }
+                        Err(e) => {
+                            warn!("unable to route the swap | {}", e);
+                            if let Ok(()) = response_tx.send(DispatchResponse::Swap(IntSwapResponse {
+                                source: Target::AMMs,
+                                input_mint: params.input_mint.to_string(),
+                                output_mint: params.output_mint.to_string(),
+                                error: Some(e.to_string()),
+                                ..Default::default()
+                            })) {
+                                info!("sent from `Strategy` towards `API Server::swap`");
+                            }
+                            continue;
+                        }
+                    };
+
+                    let hop_min_outs = self.hop_min_outs(&legs);
+                    // the route's out amount is the last leg's, its state as old as the oldest leg's
+                    let Some((_, last)) = legs.last() else { continue };
+                    let (expect_amount_out, min_return) = (last.out_amount, self.hop_min_out(last));
+                    let context_slot = legs.iter().map(|(_, quote)| quote.context_slot).min().unwrap_or_default();
                     if self
                         .tx
-                        .send(WrappedSwapAndAccountMetas {
+                        .send(ExecutionParams::Route(WrappedSwapAndAccountMetas {
                             response_tx,
+                            metas: legs.into_iter().map(|(metas, _)| metas).collect(),
                             input_mint: params.input_mint,
                             output_mint: params.output_mint,
-                            metas: vec![SwapAndAccountMetas::default()],
-                        })
+                            src_ta: params.src_ta,
+                            dst_ta: params.dst_ta,
+                            amount_in: params.amount,
+                            expect_amount_out,
+                            min_return,
+                            context_slot,
+                            hop_min_outs,
+                            signer,
+                        }))
                         .is_ok()
                     {
                         info!("sent from Strategy towards `Executor`");
@@ -144,7 +271,7 @@ impl Strategy for BaseStrategy {
 #[derive(Debug)]
 pub enum DispatchParams {
     Quote { params: QuoteParams, response_tx: oneshot::Sender<DispatchResponse> },
-    Swap { params: SwapParams, response_tx: oneshot::Sender<DispatchResponse> },
+    Swap { params: SwapParams, signer: Keypair, response_tx: oneshot::Sender<DispatchResponse> },
 }
 
 #[derive(Clone, Debug, serde::Serialize)]
@@ -1,13 +1,34 @@
-use std::{net::TcpListener, sync::mpsc};
+use std::{
+    net::TcpListener,
+    sync::{Arc, mpsc},
+    time::Duration,
+};
 
 use magnus::{
+    DisabledMarkets, Markets, MarketsStats, Oracle,
+    adapters::{
+        IntQuoteResponse,
+        aggregators::{AggregatorCfg, Aggregators, AggregatorsCfg, okx::OkxCfg},
+        amms::{Amm, Target, spl_stake_pool::SplStakePool},
+    },
     api_server::{ApiServer, ApiServerCfg},
-    strategy::DispatchParams,
+    bootstrap,
+    executor::ExecutionParams,
+    ingest::SubscriptionUpdate,
+    strategy::{DispatchParams, DispatchResponse},
 };
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::mpsc::UnboundedReceiver;
 
 pub struct TestServer {
     pub base_url: String,
     pub request_rx: mpsc::Receiver<DispatchParams>,
+    pub executor_rx: mpsc::Receiver<ExecutionParams>,
+    pub subscription_rx: UnboundedReceiver<SubscriptionUpdate>,
+    pub markets: Markets,
+    pub disabled: DisabledMarkets,
+    pub stats: MarketsStats,
     pub server_handle: actix_web::dev::ServerHandle,
 }
 
@@ -23,11 +44,32 @@ impl TestServer {
         let base_url = format!("http://{}", host);
 
         let (request_tx, request_rx) = mpsc::channel::<DispatchParams>();
+        let (executor_tx, executor_rx) = mpsc::channel::<ExecutionParams>();
+        let (subscription_tx, subscription_rx) = tokio::sync::mpsc::unbounded_channel::<SubscriptionUpdate>();
+        let markets = bootstrap::into_markets(vec![]);
+        let disabled = DisabledMarkets::default();
+        let stats = MarketsStats::default();
 
         let cfg = ApiServerCfg {
             host: host.clone(),
             workers: 1, // Use single worker for tests
             request_tx,
+            executor_tx,
+            markets: markets.clone(),
+            disabled: disabled.clone(),
+            stats: stats.clone(),
+            oracle: Oracle::default(),
+            // never reached, the tests don't hotload from the chain
+            client: Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())),
+            subscription_tx,
+            quote_deadline: Duration::from_secs(2),
+            // nothing's listening, the aggregators fail straight away
+            aggregators: Aggregators::new(AggregatorsCfg {
+                jupiter: AggregatorCfg { base_url: Some("http://127.0.0.1:1".to_string()), ..AggregatorCfg::default() },
+                dflow: AggregatorCfg { base_url: Some("http://127.0.0.1:1".to_string()), ..AggregatorCfg::default() },
+                okx: OkxCfg { aggregator: AggregatorCfg { base_url: Some("http://127.0.0.1:1".to_string()), ..AggregatorCfg::default() }, ..OkxCfg::default() },
+                raydium: AggregatorCfg { base_url: Some("http://127.0.0.1:1".to_string()), ..AggregatorCfg::default() },
+            }),
         };
 
         let server = ApiServer::new(cfg).expect("Failed to create test server");
@@ -53,7 +95,7 @@ impl TestServer {
             }
         }
 
-        TestServer { base_url, request_rx, server_handle }
+        TestServer { base_url, request_rx, executor_rx, subscription_rx, markets, disabled, stats, server_handle }
     }
 
     pub fn url(&self, path: &str) -> String {
@@ -70,3 +112,104 @@ async fn test_health_endpoint() {
 
     assert_eq!(response.status(), 200);
 }
+
+#[tokio::test]
+async fn test_hotload_rejects_invalid_market() {
+    let server = TestServer::spawn().await;
+    let client = reqwest::Client::new();
+
+    let response = client.post(server.url("/api/v1/markets/hotload")).json(&"not-a-pubkey").send().await.expect("Failed to send request");
+    assert_eq!(response.status(), 400);
+
+    let response = client.post(server.url("/api/v1/markets/hotload")).json(&serde_json::json!({"name": "no pubkey"})).send().await.expect("Failed to send request");
+    assert_eq!(response.status(), 400);
+}
+
+#[tokio::test]
+async fn test_disable_and_remove_market() {
+    let mut server = TestServer::spawn().await;
+    let client = reqwest::Client::new();
+
+    let market = SplStakePool::default();
+    let key = market.key();
+    bootstrap::register(&server.markets, vec![Box::new(market.clone())]);
+
+    let response = client.post(server.url(&format!("/api/v1/markets/hotload/{}/disable", key))).send().await.expect("Failed to send request");
+    assert_eq!(response.status(), 200);
+    assert!(server.disabled.lock().unwrap().contains(&key));
+
+    let response = client.post(server.url(&format!("/api/v1/markets/hotload/{}/enable", key))).send().await.expect("Failed to send request");
+    assert_eq!(response.status(), 200);
+    assert!(server.disabled.lock().unwrap().is_empty());
+
+    let response = client.delete(server.url(&format!("/api/v1/markets/hotload/{}", key))).send().await.expect("Failed to send request");
+    assert_eq!(response.status(), 200);
+    assert!(server.markets.lock().unwrap().is_empty());
+    match server.subscription_rx.recv().await {
+        Some(SubscriptionUpdate::Untrack(accounts)) => assert_eq!(accounts, market.get_accounts_to_update()),
+        update => panic!("unexpected subscription update {:?}", update),
+    }
+
+    // the market is gone
+    let response = client.delete(server.url(&format!("/api/v1/markets/hotload/{}", key))).send().await.expect("Failed to send request");
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+async fn test_supported_markets() {
+    let server = TestServer::spawn().await;
+    let client = reqwest::Client::new();
+
+    let market = SplStakePool::default();
+    let key = market.key();
+    bootstrap::register(&server.markets, vec![Box::new(market)]);
+    server.stats.lock().unwrap().entry(key).or_default().record_update(Some(42));
+
+    let response = client.get(server.url("/api/v1/markets/supported")).send().await.expect("Failed to send request");
+    assert_eq!(response.status(), 200);
+
+    let supported: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let supported = supported.as_array().expect("expected a list of markets");
+    assert_eq!(supported.len(), 1);
+    assert_eq!(supported[0]["market"], key.to_string());
+    assert_eq!(supported[0]["label"], "SplStakePool");
+    assert_eq!(supported[0]["lastUpdateSlot"], 42);
+    assert_eq!(supported[0]["updates"], 1);
+    assert_eq!(supported[0]["disabled"], false);
+    // nothing quoted yet
+    assert!(supported[0]["quoteSuccessRate"].is_null());
+    assert!(supported[0]["midPrice"].is_null());
+}
+
+#[tokio::test]
+async fn test_quote_all_attaches_every_source() {
+    let TestServer { base_url, request_rx, .. } = TestServer::spawn().await;
+    let client = reqwest::Client::new();
+
+    // stands in for the strategy
+    std::thread::spawn(move || {
+        if let Ok(DispatchParams::Quote { params, response_tx }) = request_rx.recv() {
+            let quote = IntQuoteResponse {
+                source: Target::AMMs,
+                input_mint: params.input_mint.to_string(),
+                output_mint: params.output_mint.to_string(),
+                in_amount: params.amount,
+                out_amount: 42,
+                ..IntQuoteResponse::default()
+            };
+            response_tx.send(DispatchResponse::Quote(quote)).unwrap();
+        }
+    });
+
+    let url = format!("{}/api/v1/quote?inputMint={}&outputMint={}&amount=1000&target=best", base_url, Pubkey::new_unique(), Pubkey::new_unique());
+    let response = client.get(url).send().await.expect("Failed to send request");
+    assert_eq!(response.status(), 200);
+
+    let body: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(body["source"], "amms");
+    assert_eq!(body["outAmount"], 42);
+
+    let sources = body["sources"].as_array().expect("expected a list of sources");
+    assert_eq!(sources.iter().map(|source| source["source"].as_str().unwrap()).collect::<Vec<_>>(), vec!["amms", "jupiter", "dflow", "okx", "raydium"]);
+    assert!(sources[1..].iter().all(|source| source["quote"].is_null() && source["error"].is_string()));
+}
@@ -0,0 +1,108 @@
+//! Decoders for the events the router emits.
+//!
+//! `swap_v2` and `arb` emit through anchor's `emit_cpi!`, i.e. as the data of a self-CPI
+//! signed by the event authority, which can be read from the inner instructions.
+//! `swap` still emits through `emit!`, i.e. as base64 `Program data:` log lines.
+
+use borsh::BorshDeserialize;
+use solana_pubkey::Pubkey;
+
+use crate::{
+    types::{ArbCompleted, SwapCompleted, SwapEvent},
+    ROUTER_ID,
+};
+
+/// Prefix anchor puts in front of every `emit_cpi!` instruction data.
+pub const EVENT_IX_TAG_LE: [u8; 8] = [228, 69, 165, 46, 81, 203, 154, 29];
+
+pub const SWAP_EVENT_DISCRIMINATOR: [u8; 8] = [64, 198, 205, 232, 38, 8, 113, 226];
+pub const SWAP_COMPLETED_DISCRIMINATOR: [u8; 8] = [118, 93, 218, 77, 215, 165, 112, 76];
+pub const ARB_COMPLETED_DISCRIMINATOR: [u8; 8] = [54, 66, 155, 140, 6, 109, 112, 136];
+
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RouterEvent {
+    Swap(SwapEvent),
+    SwapCompleted(SwapCompleted),
+    ArbCompleted(ArbCompleted),
+}
+
+/// The PDA signing the router's `emit_cpi!` self-CPIs.
+pub fn find_event_authority_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], &ROUTER_ID)
+}
+
+/// Decodes the event from a discriminator-prefixed payload, as found in `Program data:` logs.
+/// Returns `None` for payloads that aren't router events.
+pub fn decode_event(data: &[u8]) -> Option<RouterEvent> {
+    let (discriminator, mut payload) = data.split_first_chunk::<8>()?;
+
+    match *discriminator {
+        SWAP_EVENT_DISCRIMINATOR => SwapEvent::deserialize(&mut payload).ok().map(RouterEvent::Swap),
+        SWAP_COMPLETED_DISCRIMINATOR => SwapCompleted::deserialize(&mut payload).ok().map(RouterEvent::SwapCompleted),
+        ARB_COMPLETED_DISCRIMINATOR => ArbCompleted::deserialize(&mut payload).ok().map(RouterEvent::ArbCompleted),
+        _ => None,
+    }
+}
+
+/// Decodes the event from the data of an `emit_cpi!` inner instruction invoking `program_id`.
+/// Returns `None` for inner instructions that aren't router events.
+pub fn decode_cpi_event(program_id: &Pubkey, ix_data: &[u8]) -> Option<RouterEvent> {
+    if *program_id != ROUTER_ID {
+        return None;
+    }
+
+    decode_event(ix_data.strip_prefix(&EVENT_IX_TAG_LE)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+    use crate::types::{Dex, Route};
+
+    fn cpi_data(discriminator: [u8; 8], event: &impl BorshSerialize) -> Vec<u8> {
+        let mut data = EVENT_IX_TAG_LE.to_vec();
+        data.extend_from_slice(&discriminator);
+        data.extend_from_slice(&borsh::to_vec(event).unwrap());
+        data
+    }
+
+    #[test]
+    fn decodes_swap_event_from_cpi_data() {
+        let event = SwapEvent { dex: Dex::HumidiFi, amount_in: 1_000_000_000, amount_out: 121_518_066 };
+        let data = cpi_data(SWAP_EVENT_DISCRIMINATOR, &event);
+
+        assert_eq!(decode_cpi_event(&ROUTER_ID, &data), Some(RouterEvent::Swap(event)));
+    }
+
+    #[test]
+    fn decodes_swap_completed_from_cpi_data() {
+        let event = SwapCompleted {
+            order_id: 7,
+            source_mint: Pubkey::new_unique(),
+            destination_mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount_in: 100,
+            amount_out: 90,
+            amounts: vec![100],
+            routes: vec![vec![Route { dexes: vec![Dex::RaydiumCp, Dex::ObricV2], weights: vec![50, 50] }]],
+            leg_amounts_out: vec![vec![vec![45, 45]]],
+        };
+        let data = cpi_data(SWAP_COMPLETED_DISCRIMINATOR, &event);
+
+        assert_eq!(decode_cpi_event(&ROUTER_ID, &data), Some(RouterEvent::SwapCompleted(event)));
+    }
+
+    #[test]
+    fn ignores_foreign_programs_and_untagged_data() {
+        let event = SwapEvent { dex: Dex::ZeroFi, amount_in: 1, amount_out: 1 };
+        let data = cpi_data(SWAP_EVENT_DISCRIMINATOR, &event);
+
+        assert_eq!(decode_cpi_event(&Pubkey::new_unique(), &data), None);
+        assert_eq!(decode_cpi_event(&ROUTER_ID, &data[8..]), None);
+        assert_eq!(decode_event(&[0u8; 4]), None);
+    }
+}
@@ -93,6 +93,33 @@ pub enum RouterError {
     /// 6027 - Invalid Goonfi parameters
     #[error("Invalid Goonfi parameters")]
     InvalidGoonfiParameters = 0x178B,
+    /// 6028 - Swap deadline exceeded
+    #[error("Swap deadline exceeded")]
+    SwapDeadlineExceeded = 0x178C,
+    /// 6029 - Swap max slot exceeded
+    #[error("Swap max slot exceeded")]
+    SwapMaxSlotExceeded = 0x178D,
+    /// 6030 - Hop min outs must match the routes
+    #[error("Hop min outs must match the routes")]
+    HopMinOutsMustMatchRoutes = 0x178E,
+    /// 6031 - Hop min return not reached
+    #[error("Hop min return not reached")]
+    HopMinReturnNotReached = 0x178F,
+    /// 6032 - Quote route is simulation only
+    #[error("Quote route is simulation only")]
+    QuoteRouteSimulationOnly = 0x1790,
+    /// 6033 - Arb min profit not reached
+    #[error("Arb min profit not reached")]
+    ArbMinProfitNotReached = 0x1791,
+    /// 6034 - Leg accounts lens must match the routes
+    #[error("Leg accounts lens must match the routes")]
+    LegAccountsLensMustMatchRoutes = 0x1792,
+    /// 6035 - Invalid leg accounts len
+    #[error("Invalid leg accounts len")]
+    InvalidLegAccountsLen = 0x1793,
+    /// 6036 - Invalid Phoenix market
+    #[error("Invalid Phoenix market")]
+    InvalidPhoenixMarket = 0x1794,
 }
 
 impl From<RouterError> for solana_program_error::ProgramError {
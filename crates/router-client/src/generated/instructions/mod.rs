@@ -4,6 +4,9 @@
 //!
 //! <https://github.com/codama-idl/codama>
 
+pub(crate) mod r#arb;
+pub(crate) mod r#quote_route;
 pub(crate) mod r#swap;
+pub(crate) mod r#swap_v2;
 
-pub use self::r#swap::*;
+pub use self::{r#arb::*, r#quote_route::*, r#swap::*, r#swap_v2::*};
@@ -19,4 +19,10 @@ pub enum Dex {
     Tessera,
     GoonFi,
     BisonFi,
+    Whirlpool,
+    MeteoraDlmm,
+    Phoenix,
+    RaydiumAmmV4,
+    SplStakePool,
+    SplTokenSwap,
 }
@@ -4,8 +4,11 @@
 //!
 //! <https://github.com/codama-idl/codama>
 
+pub(crate) mod r#arb_completed;
 pub(crate) mod r#dex;
 pub(crate) mod r#route;
+pub(crate) mod r#route_quote;
+pub(crate) mod r#swap_completed;
 pub(crate) mod r#swap_event;
 
-pub use self::{r#dex::*, r#route::*, r#swap_event::*};
+pub use self::{r#arb_completed::*, r#dex::*, r#route::*, r#route_quote::*, r#swap_completed::*, r#swap_event::*};
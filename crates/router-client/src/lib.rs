@@ -1,3 +1,4 @@
+pub mod events;
 pub mod generated;
 
 pub use generated::*;
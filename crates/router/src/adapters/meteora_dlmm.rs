@@ -0,0 +1,213 @@
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use arrayref::array_ref;
+use magnus_shared::amm_meteora_dlmm::{self, ACCOUNTS_LEN, ARGS_LEN, MIN_ACCOUNTS_LEN};
+
+use crate::{
+    adapters::common::{before_check, invoke_process, DexProcessor},
+    error::ErrorCode,
+    HopAccounts, SWAP_SELECTOR,
+};
+
+pub struct MeteoraDlmmSwapProcessor;
+impl DexProcessor for MeteoraDlmmSwapProcessor {}
+
+pub struct MeteoraDlmmAccounts<'info> {
+    pub dex_program_id: &'info AccountInfo<'info>,
+    pub swap_authority_pubkey: &'info AccountInfo<'info>,
+    pub swap_source_token: InterfaceAccount<'info, TokenAccount>,
+    pub swap_destination_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub lb_pair: &'info AccountInfo<'info>,
+    pub bin_array_bitmap_extension: &'info AccountInfo<'info>, // the dex program id if the pair has none
+    pub reserve_x: InterfaceAccount<'info, TokenAccount>,
+    pub reserve_y: InterfaceAccount<'info, TokenAccount>,
+    pub token_x_mint: InterfaceAccount<'info, Mint>,
+    pub token_y_mint: InterfaceAccount<'info, Mint>,
+    pub oracle: &'info AccountInfo<'info>,
+    pub host_fee_in: &'info AccountInfo<'info>, // the dex program id if there's no host fee
+    pub token_x_program: Interface<'info, TokenInterface>,
+    pub token_y_program: Interface<'info, TokenInterface>,
+    pub event_authority: &'info AccountInfo<'info>,
+    pub bin_array0: &'info AccountInfo<'info>,
+    pub bin_arrays: &'info [AccountInfo<'info>], // the bin arrays past the first one
+}
+
+impl<'info> MeteoraDlmmAccounts<'info> {
+    fn parse_accounts(accounts: &'info [AccountInfo<'info>], offset: usize, accounts_len: usize) -> Result<Self> {
+        let [
+            dex_program_id,
+            swap_authority_pubkey,
+            swap_source_token,
+            swap_destination_token,
+            lb_pair,
+            bin_array_bitmap_extension,
+            reserve_x,
+            reserve_y,
+            token_x_mint,
+            token_y_mint,
+            oracle,
+            host_fee_in,
+            token_x_program,
+            token_y_program,
+            event_authority,
+            bin_array0,
+        ]: &[AccountInfo<'info>; MIN_ACCOUNTS_LEN] = array_ref![accounts, offset, MIN_ACCOUNTS_LEN];
+        let bin_arrays = &accounts[offset + MIN_ACCOUNTS_LEN..offset + accounts_len];
+
+        Ok(Self {
+            dex_program_id,
+            swap_authority_pubkey,
+            swap_source_token: InterfaceAccount::try_from(swap_source_token)?,
+            swap_destination_token: InterfaceAccount::try_from(swap_destination_token)?,
+            lb_pair,
+            bin_array_bitmap_extension,
+            reserve_x: InterfaceAccount::try_from(reserve_x)?,
+            reserve_y: InterfaceAccount::try_from(reserve_y)?,
+            token_x_mint: InterfaceAccount::try_from(token_x_mint)?,
+            token_y_mint: InterfaceAccount::try_from(token_y_mint)?,
+            oracle,
+            host_fee_in,
+            token_x_program: Interface::try_from(token_x_program)?,
+            token_y_program: Interface::try_from(token_y_program)?,
+            event_authority,
+            bin_array0,
+            bin_arrays,
+        })
+    }
+}
+
+pub fn swap<'a>(
+    remaining_accounts: &'a [AccountInfo<'a>],
+    amount_in: u64,
+    offset: &mut usize,
+    hop_accounts: &mut HopAccounts,
+    hop: usize,
+    proxy_swap: bool,
+    owner_seeds: Option<&[&[&[u8]]]>,
+) -> Result<u64> {
+    swap_with_accounts_len(remaining_accounts, amount_in, offset, hop_accounts, hop, ACCOUNTS_LEN, proxy_swap, owner_seeds)
+}
+
+/// Same as `swap`, with the leg carrying `accounts_len - MIN_ACCOUNTS_LEN` bin arrays past the first one.
+pub fn swap_with_accounts_len<'a>(
+    remaining_accounts: &'a [AccountInfo<'a>],
+    amount_in: u64,
+    offset: &mut usize,
+    hop_accounts: &mut HopAccounts,
+    hop: usize,
+    accounts_len: usize,
+    proxy_swap: bool,
+    owner_seeds: Option<&[&[&[u8]]]>,
+) -> Result<u64> {
+    msg!("Dex::MeteoraDlmm amount_in: {}, offset: {}, accounts_len: {}", amount_in, offset, accounts_len);
+    require!(accounts_len >= MIN_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+    require!(remaining_accounts.len() >= *offset + accounts_len, ErrorCode::InvalidAccountsLength);
+
+    let mut swap_accounts = MeteoraDlmmAccounts::parse_accounts(remaining_accounts, *offset, accounts_len)?;
+    if swap_accounts.dex_program_id.key != &amm_meteora_dlmm::id() {
+        return Err(ErrorCode::InvalidProgramId.into());
+    }
+
+    // log pool address
+    swap_accounts.lb_pair.key().log();
+
+    // check hop accounts & swap authority
+    let swap_source_token = swap_accounts.swap_source_token.key();
+    let swap_destination_token = swap_accounts.swap_destination_token.key();
+    before_check(swap_accounts.swap_authority_pubkey, &swap_accounts.swap_source_token, swap_destination_token, hop_accounts, hop, proxy_swap, owner_seeds)?;
+
+    let min_amount_out = 1u64;
+    let mut data = Vec::with_capacity(ARGS_LEN);
+    data.extend_from_slice(SWAP_SELECTOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_amount_out.to_le_bytes());
+
+    // optional accounts are passed as the dex program id, which can't be writable
+    let host_fee_in = swap_accounts.host_fee_in.key();
+    let host_fee_in_meta = if host_fee_in == amm_meteora_dlmm::id() {
+        AccountMeta::new_readonly(host_fee_in, false)
+    } else {
+        AccountMeta::new(host_fee_in, false)
+    };
+
+    let mut accounts = vec![
+        AccountMeta::new(swap_accounts.lb_pair.key(), false),
+        AccountMeta::new_readonly(swap_accounts.bin_array_bitmap_extension.key(), false),
+        AccountMeta::new(swap_accounts.reserve_x.key(), false),
+        AccountMeta::new(swap_accounts.reserve_y.key(), false),
+        AccountMeta::new(swap_source_token, false),
+        AccountMeta::new(swap_destination_token, false),
+        AccountMeta::new_readonly(swap_accounts.token_x_mint.key(), false),
+        AccountMeta::new_readonly(swap_accounts.token_y_mint.key(), false),
+        AccountMeta::new(swap_accounts.oracle.key(), false),
+        host_fee_in_meta,
+        AccountMeta::new_readonly(swap_accounts.swap_authority_pubkey.key(), true),
+        AccountMeta::new_readonly(swap_accounts.token_x_program.key(), false),
+        AccountMeta::new_readonly(swap_accounts.token_y_program.key(), false),
+        AccountMeta::new_readonly(swap_accounts.event_authority.key(), false),
+        AccountMeta::new_readonly(swap_accounts.dex_program_id.key(), false),
+        AccountMeta::new(swap_accounts.bin_array0.key(), false),
+    ];
+
+    let mut account_infos = vec![
+        swap_accounts.lb_pair.to_account_info(),
+        swap_accounts.bin_array_bitmap_extension.to_account_info(),
+        swap_accounts.reserve_x.to_account_info(),
+        swap_accounts.reserve_y.to_account_info(),
+        swap_accounts.swap_source_token.to_account_info(),
+        swap_accounts.swap_destination_token.to_account_info(),
+        swap_accounts.token_x_mint.to_account_info(),
+        swap_accounts.token_y_mint.to_account_info(),
+        swap_accounts.oracle.to_account_info(),
+        swap_accounts.host_fee_in.to_account_info(),
+        swap_accounts.swap_authority_pubkey.to_account_info(),
+        swap_accounts.token_x_program.to_account_info(),
+        swap_accounts.token_y_program.to_account_info(),
+        swap_accounts.event_authority.to_account_info(),
+        swap_accounts.dex_program_id.to_account_info(),
+        swap_accounts.bin_array0.to_account_info(),
+    ];
+
+    for bin_array in swap_accounts.bin_arrays {
+        accounts.push(AccountMeta::new(bin_array.key(), false));
+        account_infos.push(bin_array.to_account_info());
+    }
+
+    let instruction = Instruction { program_id: swap_accounts.dex_program_id.key(), accounts, data };
+
+    let dex_processor = &MeteoraDlmmSwapProcessor;
+    let amount_out = invoke_process(
+        amount_in,
+        dex_processor,
+        &account_infos,
+        &mut swap_accounts.swap_source_token,
+        &mut swap_accounts.swap_destination_token,
+        hop_accounts,
+        instruction,
+        hop,
+        offset,
+        accounts_len,
+        proxy_swap,
+        owner_seeds,
+    )?;
+    Ok(amount_out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_pack_meteora_dlmm_instruction() {
+        let amount_in = 100u64;
+        let min_amount_out = 1u64;
+
+        let mut data = Vec::with_capacity(ARGS_LEN);
+        data.extend_from_slice(SWAP_SELECTOR);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&min_amount_out.to_le_bytes());
+
+        assert!(data.len() == ARGS_LEN);
+    }
+}
@@ -2,9 +2,15 @@ pub mod bisonfi;
 pub mod common;
 pub mod goonfi;
 pub mod humidifi;
+pub mod meteora_dlmm;
 pub mod obric_v2;
+pub mod phoenix;
+pub mod raydium_amm_v4;
 pub mod raydium_cl_v2;
 pub mod raydium_cp;
 pub mod solfi_v2;
+pub mod spl_stake_pool;
+pub mod spl_token_swap;
 pub mod tessera;
+pub mod whirlpool;
 pub mod zerofi;
@@ -0,0 +1,182 @@
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
+use anchor_spl::token_interface::{TokenAccount, TokenInterface};
+use arrayref::array_ref;
+use magnus_shared::clob_phoenix::{self, ACCOUNTS_LEN, ARGS_LEN};
+
+use crate::{
+    adapters::common::{before_check, invoke_process, DexProcessor},
+    error::ErrorCode,
+    HopAccounts, PHOENIX_BASE_LOT_SIZE_OFFSET, PHOENIX_BASE_MINT_OFFSET, PHOENIX_QUOTE_LOT_SIZE_OFFSET, PHOENIX_SWAP_SELECTOR,
+};
+
+// OrderPacket::ImmediateOrCancel and its sides
+const IMMEDIATE_OR_CANCEL: u8 = 2;
+const SIDE_BID: u8 = 0;
+const SIDE_ASK: u8 = 1;
+// SelfTradeBehavior::Abort
+const SELF_TRADE_ABORT: u8 = 0;
+
+pub struct PhoenixSwapProcessor;
+impl DexProcessor for PhoenixSwapProcessor {}
+
+pub struct PhoenixAccounts<'info> {
+    pub dex_program_id: &'info AccountInfo<'info>,
+    pub swap_authority_pubkey: &'info AccountInfo<'info>,
+    pub swap_source_token: InterfaceAccount<'info, TokenAccount>,
+    pub swap_destination_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub market: &'info AccountInfo<'info>,
+    pub log_authority: &'info AccountInfo<'info>,
+    pub base_vault: InterfaceAccount<'info, TokenAccount>,
+    pub quote_vault: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> PhoenixAccounts<'info> {
+    fn parse_accounts(accounts: &'info [AccountInfo<'info>], offset: usize) -> Result<Self> {
+        let [dex_program_id, swap_authority_pubkey, swap_source_token, swap_destination_token, market, log_authority, base_vault, quote_vault, token_program]: &[AccountInfo<'info>;
+             ACCOUNTS_LEN] = array_ref![accounts, offset, ACCOUNTS_LEN];
+
+        Ok(Self {
+            dex_program_id,
+            swap_authority_pubkey,
+            swap_source_token: InterfaceAccount::try_from(swap_source_token)?,
+            swap_destination_token: InterfaceAccount::try_from(swap_destination_token)?,
+            market,
+            log_authority,
+            base_vault: InterfaceAccount::try_from(base_vault)?,
+            quote_vault: InterfaceAccount::try_from(quote_vault)?,
+            token_program: Interface::try_from(token_program)?,
+        })
+    }
+}
+
+/// The base mint and the base/quote lot sizes from the market header.
+fn market_params(market: &AccountInfo) -> Result<(Pubkey, u64, u64)> {
+    let data = market.try_borrow_data()?;
+    require!(data.len() >= PHOENIX_QUOTE_LOT_SIZE_OFFSET + 8, ErrorCode::InvalidPhoenixMarket);
+
+    let base_mint = Pubkey::new_from_array(*array_ref![data, PHOENIX_BASE_MINT_OFFSET, 32]);
+    let base_lot_size = u64::from_le_bytes(*array_ref![data, PHOENIX_BASE_LOT_SIZE_OFFSET, 8]);
+    let quote_lot_size = u64::from_le_bytes(*array_ref![data, PHOENIX_QUOTE_LOT_SIZE_OFFSET, 8]);
+    require!(base_lot_size > 0 && quote_lot_size > 0, ErrorCode::InvalidPhoenixMarket);
+
+    Ok((base_mint, base_lot_size, quote_lot_size))
+}
+
+/// An immediate-or-cancel taker order crossing the whole book, sized in base lots when selling and in quote lots when buying.
+fn pack_instruction_data(side: u8, num_base_lots: u64, num_quote_lots: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(ARGS_LEN);
+    data.push(PHOENIX_SWAP_SELECTOR);
+    data.push(IMMEDIATE_OR_CANCEL);
+    data.push(side);
+    data.push(0); // price_in_ticks: None
+    data.extend_from_slice(&num_base_lots.to_le_bytes());
+    data.extend_from_slice(&num_quote_lots.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // min_base_lots_to_fill
+    data.extend_from_slice(&0u64.to_le_bytes()); // min_quote_lots_to_fill
+    data.push(SELF_TRADE_ABORT);
+    data.push(0); // match_limit: None
+    data.extend_from_slice(&0u128.to_le_bytes()); // client_order_id
+    data.push(0); // use_only_deposited_funds: false
+    data.push(0); // last_valid_slot: None
+    data.push(0); // last_valid_unix_timestamp_in_seconds: None
+    data
+}
+
+pub fn swap<'a>(
+    remaining_accounts: &'a [AccountInfo<'a>],
+    amount_in: u64,
+    offset: &mut usize,
+    hop_accounts: &mut HopAccounts,
+    hop: usize,
+    proxy_swap: bool,
+    owner_seeds: Option<&[&[&[u8]]]>,
+) -> Result<u64> {
+    msg!("Dex::Phoenix amount_in: {}, offset: {}", amount_in, offset);
+    require!(remaining_accounts.len() >= *offset + ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+
+    let mut swap_accounts = PhoenixAccounts::parse_accounts(remaining_accounts, *offset)?;
+    if swap_accounts.dex_program_id.key != &clob_phoenix::id() {
+        return Err(ErrorCode::InvalidProgramId.into());
+    }
+
+    // log pool address
+    swap_accounts.market.key().log();
+
+    // check hop accounts & swap authority
+    let swap_source_token = swap_accounts.swap_source_token.key();
+    let swap_destination_token = swap_accounts.swap_destination_token.key();
+    before_check(swap_accounts.swap_authority_pubkey, &swap_accounts.swap_source_token, swap_destination_token, hop_accounts, hop, proxy_swap, owner_seeds)?;
+
+    // selling base takes the bids, buying base takes the asks, the dust under a lot stays with the trader
+    let (base_mint, base_lot_size, quote_lot_size) = market_params(swap_accounts.market)?;
+    let is_ask = swap_accounts.swap_source_token.mint == base_mint;
+    let (data, base_account, quote_account) = if is_ask {
+        (pack_instruction_data(SIDE_ASK, amount_in / base_lot_size, 0), swap_source_token, swap_destination_token)
+    } else {
+        (pack_instruction_data(SIDE_BID, 0, amount_in / quote_lot_size), swap_destination_token, swap_source_token)
+    };
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_accounts.dex_program_id.key(), false),
+        AccountMeta::new_readonly(swap_accounts.log_authority.key(), false),
+        AccountMeta::new(swap_accounts.market.key(), false),
+        AccountMeta::new_readonly(swap_accounts.swap_authority_pubkey.key(), true),
+        AccountMeta::new(base_account, false),
+        AccountMeta::new(quote_account, false),
+        AccountMeta::new(swap_accounts.base_vault.key(), false),
+        AccountMeta::new(swap_accounts.quote_vault.key(), false),
+        AccountMeta::new_readonly(swap_accounts.token_program.key(), false),
+    ];
+
+    let (base_account_info, quote_account_info) = if is_ask {
+        (swap_accounts.swap_source_token.to_account_info(), swap_accounts.swap_destination_token.to_account_info())
+    } else {
+        (swap_accounts.swap_destination_token.to_account_info(), swap_accounts.swap_source_token.to_account_info())
+    };
+
+    let account_infos = vec![
+        swap_accounts.dex_program_id.to_account_info(),
+        swap_accounts.log_authority.to_account_info(),
+        swap_accounts.market.to_account_info(),
+        swap_accounts.swap_authority_pubkey.to_account_info(),
+        base_account_info,
+        quote_account_info,
+        swap_accounts.base_vault.to_account_info(),
+        swap_accounts.quote_vault.to_account_info(),
+        swap_accounts.token_program.to_account_info(),
+    ];
+
+    let instruction = Instruction { program_id: swap_accounts.dex_program_id.key(), accounts, data };
+
+    let dex_processor = &PhoenixSwapProcessor;
+    let amount_out = invoke_process(
+        amount_in,
+        dex_processor,
+        &account_infos,
+        &mut swap_accounts.swap_source_token,
+        &mut swap_accounts.swap_destination_token,
+        hop_accounts,
+        instruction,
+        hop,
+        offset,
+        ACCOUNTS_LEN,
+        proxy_swap,
+        owner_seeds,
+    )?;
+    Ok(amount_out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_pack_phoenix_instruction() {
+        let data = pack_instruction_data(SIDE_ASK, 100, 0);
+
+        assert!(data.len() == ARGS_LEN);
+        assert_eq!(data[..3], [PHOENIX_SWAP_SELECTOR, IMMEDIATE_OR_CANCEL, SIDE_ASK]);
+    }
+}
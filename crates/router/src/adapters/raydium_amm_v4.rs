@@ -0,0 +1,191 @@
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
+use anchor_spl::token_interface::{TokenAccount, TokenInterface};
+use arrayref::array_ref;
+use magnus_shared::amm_raydium_amm_v4::{self, ACCOUNTS_LEN, ARGS_LEN};
+
+use crate::{
+    adapters::common::{before_check, invoke_process, DexProcessor},
+    error::ErrorCode,
+    HopAccounts, RAYDIUM_AMM_V4_SWAP_BASE_IN_SELECTOR,
+};
+
+pub struct RaydiumAmmV4SwapProcessor;
+impl DexProcessor for RaydiumAmmV4SwapProcessor {}
+
+pub struct RaydiumAmmV4Accounts<'info> {
+    pub dex_program_id: &'info AccountInfo<'info>,
+    pub swap_authority_pubkey: &'info AccountInfo<'info>,
+    pub swap_source_token: InterfaceAccount<'info, TokenAccount>,
+    pub swap_destination_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub amm: &'info AccountInfo<'info>,
+    pub amm_authority: &'info AccountInfo<'info>,
+    pub amm_open_orders: &'info AccountInfo<'info>,
+    pub pool_coin_vault: InterfaceAccount<'info, TokenAccount>,
+    pub pool_pc_vault: InterfaceAccount<'info, TokenAccount>,
+    pub market_program: &'info AccountInfo<'info>,
+    pub market: &'info AccountInfo<'info>,
+    pub market_bids: &'info AccountInfo<'info>,
+    pub market_asks: &'info AccountInfo<'info>,
+    pub market_event_queue: &'info AccountInfo<'info>,
+    pub market_coin_vault: &'info AccountInfo<'info>,
+    pub market_pc_vault: &'info AccountInfo<'info>,
+    pub market_vault_signer: &'info AccountInfo<'info>,
+}
+
+impl<'info> RaydiumAmmV4Accounts<'info> {
+    fn parse_accounts(accounts: &'info [AccountInfo<'info>], offset: usize) -> Result<Self> {
+        let [
+            dex_program_id,
+            swap_authority_pubkey,
+            swap_source_token,
+            swap_destination_token,
+            token_program,
+            amm,
+            amm_authority,
+            amm_open_orders,
+            pool_coin_vault,
+            pool_pc_vault,
+            market_program,
+            market,
+            market_bids,
+            market_asks,
+            market_event_queue,
+            market_coin_vault,
+            market_pc_vault,
+            market_vault_signer,
+        ]: &[AccountInfo<'info>; ACCOUNTS_LEN] = array_ref![accounts, offset, ACCOUNTS_LEN];
+
+        Ok(Self {
+            dex_program_id,
+            swap_authority_pubkey,
+            swap_source_token: InterfaceAccount::try_from(swap_source_token)?,
+            swap_destination_token: InterfaceAccount::try_from(swap_destination_token)?,
+            token_program: Interface::try_from(token_program)?,
+            amm,
+            amm_authority,
+            amm_open_orders,
+            pool_coin_vault: InterfaceAccount::try_from(pool_coin_vault)?,
+            pool_pc_vault: InterfaceAccount::try_from(pool_pc_vault)?,
+            market_program,
+            market,
+            market_bids,
+            market_asks,
+            market_event_queue,
+            market_coin_vault,
+            market_pc_vault,
+            market_vault_signer,
+        })
+    }
+}
+
+pub fn swap<'a>(
+    remaining_accounts: &'a [AccountInfo<'a>],
+    amount_in: u64,
+    offset: &mut usize,
+    hop_accounts: &mut HopAccounts,
+    hop: usize,
+    proxy_swap: bool,
+    owner_seeds: Option<&[&[&[u8]]]>,
+) -> Result<u64> {
+    msg!("Dex::RaydiumAmmV4 amount_in: {}, offset: {}", amount_in, offset);
+    require!(remaining_accounts.len() >= *offset + ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+
+    let mut swap_accounts = RaydiumAmmV4Accounts::parse_accounts(remaining_accounts, *offset)?;
+    if swap_accounts.dex_program_id.key != &amm_raydium_amm_v4::id() {
+        return Err(ErrorCode::InvalidProgramId.into());
+    }
+
+    // log pool address
+    swap_accounts.amm.key().log();
+
+    // check hop accounts & swap authority
+    let swap_source_token = swap_accounts.swap_source_token.key();
+    let swap_destination_token = swap_accounts.swap_destination_token.key();
+    before_check(swap_accounts.swap_authority_pubkey, &swap_accounts.swap_source_token, swap_destination_token, hop_accounts, hop, proxy_swap, owner_seeds)?;
+
+    let minimum_amount_out = 1u64;
+    let mut data = Vec::with_capacity(ARGS_LEN);
+    data.push(RAYDIUM_AMM_V4_SWAP_BASE_IN_SELECTOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+    // the 17 accounts variant, without the target orders
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_accounts.token_program.key(), false),
+        AccountMeta::new(swap_accounts.amm.key(), false),
+        AccountMeta::new_readonly(swap_accounts.amm_authority.key(), false),
+        AccountMeta::new(swap_accounts.amm_open_orders.key(), false),
+        AccountMeta::new(swap_accounts.pool_coin_vault.key(), false),
+        AccountMeta::new(swap_accounts.pool_pc_vault.key(), false),
+        AccountMeta::new_readonly(swap_accounts.market_program.key(), false),
+        AccountMeta::new(swap_accounts.market.key(), false),
+        AccountMeta::new(swap_accounts.market_bids.key(), false),
+        AccountMeta::new(swap_accounts.market_asks.key(), false),
+        AccountMeta::new(swap_accounts.market_event_queue.key(), false),
+        AccountMeta::new(swap_accounts.market_coin_vault.key(), false),
+        AccountMeta::new(swap_accounts.market_pc_vault.key(), false),
+        AccountMeta::new_readonly(swap_accounts.market_vault_signer.key(), false),
+        AccountMeta::new(swap_source_token, false),
+        AccountMeta::new(swap_destination_token, false),
+        AccountMeta::new_readonly(swap_accounts.swap_authority_pubkey.key(), true),
+    ];
+
+    let account_infos = vec![
+        swap_accounts.token_program.to_account_info(),
+        swap_accounts.amm.to_account_info(),
+        swap_accounts.amm_authority.to_account_info(),
+        swap_accounts.amm_open_orders.to_account_info(),
+        swap_accounts.pool_coin_vault.to_account_info(),
+        swap_accounts.pool_pc_vault.to_account_info(),
+        swap_accounts.market_program.to_account_info(),
+        swap_accounts.market.to_account_info(),
+        swap_accounts.market_bids.to_account_info(),
+        swap_accounts.market_asks.to_account_info(),
+        swap_accounts.market_event_queue.to_account_info(),
+        swap_accounts.market_coin_vault.to_account_info(),
+        swap_accounts.market_pc_vault.to_account_info(),
+        swap_accounts.market_vault_signer.to_account_info(),
+        swap_accounts.swap_source_token.to_account_info(),
+        swap_accounts.swap_destination_token.to_account_info(),
+        swap_accounts.swap_authority_pubkey.to_account_info(),
+    ];
+
+    let instruction = Instruction { program_id: swap_accounts.dex_program_id.key(), accounts, data };
+
+    let dex_processor = &RaydiumAmmV4SwapProcessor;
+    let amount_out = invoke_process(
+        amount_in,
+        dex_processor,
+        &account_infos,
+        &mut swap_accounts.swap_source_token,
+        &mut swap_accounts.swap_destination_token,
+        hop_accounts,
+        instruction,
+        hop,
+        offset,
+        ACCOUNTS_LEN,
+        proxy_swap,
+        owner_seeds,
+    )?;
+    Ok(amount_out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_pack_raydium_amm_v4_instruction() {
+        let amount_in = 100u64;
+        let minimum_amount_out = 1u64;
+
+        let mut data = Vec::with_capacity(ARGS_LEN);
+        data.push(RAYDIUM_AMM_V4_SWAP_BASE_IN_SELECTOR);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+        assert!(data.len() == ARGS_LEN);
+    }
+}
@@ -4,7 +4,7 @@ use anchor_spl::{
     token_interface::{Mint, Token2022, TokenAccount},
 };
 use arrayref::array_ref;
-use magnus_shared::amm_raydium_cl_v2::{self, ACCOUNTS_LEN, ARGS_LEN};
+use magnus_shared::amm_raydium_cl_v2::{self, ACCOUNTS_LEN, ARGS_LEN, MIN_ACCOUNTS_LEN};
 
 use crate::{
     adapters::{
@@ -33,12 +33,11 @@ pub struct RaydiumCLV2Accounts<'info> {
     pub output_vault_mint: InterfaceAccount<'info, Mint>,
     pub ex_bitmap: &'info AccountInfo<'info>,
     pub tick_array0: &'info AccountInfo<'info>,
-    pub tick_array1: &'info AccountInfo<'info>,
-    pub tick_array2: &'info AccountInfo<'info>,
+    pub tick_arrays: &'info [AccountInfo<'info>], // the tick arrays past the first one
 }
 
 impl<'info> RaydiumCLV2Accounts<'info> {
-    fn parse_accounts(accounts: &'info [AccountInfo<'info>], offset: usize) -> Result<Self> {
+    fn parse_accounts(accounts: &'info [AccountInfo<'info>], offset: usize, accounts_len: usize) -> Result<Self> {
         let [
             dex_program_id,
             swap_authority_pubkey,
@@ -56,9 +55,8 @@ impl<'info> RaydiumCLV2Accounts<'info> {
             output_vault_mint,
             ex_bitmap,
             tick_array0,
-            tick_array1,
-            tick_array2,
-        ]: &[AccountInfo<'info>; ACCOUNTS_LEN] = array_ref![accounts, offset, ACCOUNTS_LEN];
+        ]: &[AccountInfo<'info>; MIN_ACCOUNTS_LEN] = array_ref![accounts, offset, MIN_ACCOUNTS_LEN];
+        let tick_arrays = &accounts[offset + MIN_ACCOUNTS_LEN..offset + accounts_len];
 
         Ok(Self {
             dex_program_id,
@@ -77,8 +75,7 @@ impl<'info> RaydiumCLV2Accounts<'info> {
             output_vault_mint: InterfaceAccount::try_from(output_vault_mint)?,
             ex_bitmap,
             tick_array0,
-            tick_array1,
-            tick_array2,
+            tick_arrays,
         })
     }
 }
@@ -92,10 +89,25 @@ pub fn swap<'a>(
     proxy_swap: bool,
     owner_seeds: Option<&[&[&[u8]]]>,
 ) -> Result<u64> {
-    msg!("Dex::RaydiumClmmSwapV2 amount_in: {}, offset: {}", amount_in, offset);
-    require!(remaining_accounts.len() >= *offset + ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+    swap_with_accounts_len(remaining_accounts, amount_in, offset, hop_accounts, hop, ACCOUNTS_LEN, proxy_swap, owner_seeds)
+}
 
-    let mut swap_accounts = RaydiumCLV2Accounts::parse_accounts(remaining_accounts, *offset)?;
+/// Same as `swap`, with the leg carrying `accounts_len - MIN_ACCOUNTS_LEN` tick arrays past the first one.
+pub fn swap_with_accounts_len<'a>(
+    remaining_accounts: &'a [AccountInfo<'a>],
+    amount_in: u64,
+    offset: &mut usize,
+    hop_accounts: &mut HopAccounts,
+    hop: usize,
+    accounts_len: usize,
+    proxy_swap: bool,
+    owner_seeds: Option<&[&[&[u8]]]>,
+) -> Result<u64> {
+    msg!("Dex::RaydiumClmmSwapV2 amount_in: {}, offset: {}, accounts_len: {}", amount_in, offset, accounts_len);
+    require!(accounts_len >= MIN_ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+    require!(remaining_accounts.len() >= *offset + accounts_len, ErrorCode::InvalidAccountsLength);
+
+    let mut swap_accounts = RaydiumCLV2Accounts::parse_accounts(remaining_accounts, *offset, accounts_len)?;
     if swap_accounts.dex_program_id.key != &amm_raydium_cl_v2::id() {
         return Err(ErrorCode::InvalidProgramId.into());
     }
@@ -155,15 +167,9 @@ pub fn swap<'a>(
         swap_accounts.tick_array0.to_account_info(),
     ];
 
-    let tick_array1 = swap_accounts.tick_array1.key();
-    let tick_array2 = swap_accounts.tick_array2.key();
-    if tick_array1 != ZERO_ADDRESS {
-        accounts.push(AccountMeta::new(tick_array1, false));
-        account_infos.push(swap_accounts.tick_array1.to_account_info());
-    }
-    if tick_array2 != ZERO_ADDRESS {
-        accounts.push(AccountMeta::new(tick_array2, false));
-        account_infos.push(swap_accounts.tick_array2.to_account_info());
+    for tick_array in swap_accounts.tick_arrays.iter().filter(|tick_array| tick_array.key() != ZERO_ADDRESS) {
+        accounts.push(AccountMeta::new(tick_array.key(), false));
+        account_infos.push(tick_array.to_account_info());
     }
 
     let instruction = Instruction { program_id: swap_accounts.dex_program_id.key(), accounts, data };
@@ -179,7 +185,7 @@ pub fn swap<'a>(
         instruction,
         hop,
         offset,
-        ACCOUNTS_LEN,
+        accounts_len,
         proxy_swap,
         owner_seeds,
     )?;
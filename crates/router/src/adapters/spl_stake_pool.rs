@@ -0,0 +1,306 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::Instruction,
+        program::{invoke, invoke_signed},
+        program_pack::Pack,
+        system_instruction::create_account_with_seed,
+    },
+};
+use anchor_spl::{
+    token::{self, spl_token::state::Account as SplTokenAccount},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use arrayref::array_ref;
+use magnus_shared::{
+    lst_spl_stake_pool::{self, ACCOUNTS_LEN, ARGS_LEN, UNWRAP_SEED},
+    wsol_program,
+};
+
+use crate::{
+    adapters::common::{before_check, invoke_process, DexProcessor},
+    error::ErrorCode,
+    utils::{close_token_account, transfer_token},
+    HopAccounts, SA_AUTHORITY_SEED, STAKE_POOL_DEPOSIT_SOL_SELECTOR, STAKE_POOL_WITHDRAW_SOL_SELECTOR,
+};
+
+const WSOL_DECIMALS: u8 = 9;
+
+pub struct DepositSolProcessor;
+impl DexProcessor for DepositSolProcessor {}
+
+/// Withdrawn lamports land in the wSOL destination as plain lamports, syncing it makes them count as its balance.
+pub struct WithdrawSolProcessor<'info> {
+    pub token_program: AccountInfo<'info>,
+    pub destination: AccountInfo<'info>,
+}
+
+impl DexProcessor for WithdrawSolProcessor<'_> {
+    fn after_invoke(&self, _account_infos: &[AccountInfo], _hop: usize, _owner_seeds: Option<&[&[&[u8]]]>, _before_sa_authority_lamports: u64) -> Result<u64> {
+        token::sync_native(CpiContext::new(self.token_program.clone(), token::SyncNative { account: self.destination.clone() }))?;
+        Ok(0)
+    }
+}
+
+pub struct SplStakePoolAccounts<'info> {
+    pub dex_program_id: &'info AccountInfo<'info>,
+    pub swap_authority_pubkey: &'info AccountInfo<'info>,
+    pub swap_source_token: InterfaceAccount<'info, TokenAccount>,
+    pub swap_destination_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub stake_pool: &'info AccountInfo<'info>,
+    pub withdraw_authority: &'info AccountInfo<'info>,
+    pub reserve_stake: &'info AccountInfo<'info>,
+    pub manager_fee_account: &'info AccountInfo<'info>,
+    pub pool_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: &'info AccountInfo<'info>,
+    pub clock: &'info AccountInfo<'info>,
+    pub stake_history: &'info AccountInfo<'info>,
+    pub stake_program: &'info AccountInfo<'info>,
+    pub unwrap_account: &'info AccountInfo<'info>,
+    pub native_mint: &'info AccountInfo<'info>,
+}
+
+impl<'info> SplStakePoolAccounts<'info> {
+    fn parse_accounts(accounts: &'info [AccountInfo<'info>], offset: usize) -> Result<Self> {
+        let [
+            dex_program_id,
+            swap_authority_pubkey,
+            swap_source_token,
+            swap_destination_token,
+            stake_pool,
+            withdraw_authority,
+            reserve_stake,
+            manager_fee_account,
+            pool_mint,
+            token_program,
+            system_program,
+            clock,
+            stake_history,
+            stake_program,
+            unwrap_account,
+            native_mint,
+        ]: &[AccountInfo<'info>; ACCOUNTS_LEN] = array_ref![accounts, offset, ACCOUNTS_LEN];
+
+        Ok(Self {
+            dex_program_id,
+            swap_authority_pubkey,
+            swap_source_token: InterfaceAccount::try_from(swap_source_token)?,
+            swap_destination_token: InterfaceAccount::try_from(swap_destination_token)?,
+            stake_pool,
+            withdraw_authority,
+            reserve_stake,
+            manager_fee_account,
+            pool_mint: InterfaceAccount::try_from(pool_mint)?,
+            token_program: Interface::try_from(token_program)?,
+            system_program,
+            clock,
+            stake_history,
+            stake_program,
+            unwrap_account,
+            native_mint,
+        })
+    }
+}
+
+fn invoke_with_seeds(instruction: &Instruction, account_infos: &[AccountInfo], signer_seeds: Option<&[&[&[u8]]]>) -> Result<()> {
+    match signer_seeds {
+        Some(signer_seeds) => invoke_signed(instruction, account_infos, signer_seeds)?,
+        None => invoke(instruction, account_infos)?,
+    }
+    Ok(())
+}
+
+/// Turns `amount_in` of the wSOL source into lamports of the swap authority, through a token account created and closed on the spot.
+fn unwrap_sol<'info>(swap_accounts: &SplStakePoolAccounts<'info>, amount_in: u64, signer_seeds: Option<&[&[&[u8]]]>) -> Result<()> {
+    let authority = swap_accounts.swap_authority_pubkey;
+    let unwrap_account = swap_accounts.unwrap_account;
+    let token_program = swap_accounts.token_program.to_account_info();
+
+    let expected = Pubkey::create_with_seed(authority.key, UNWRAP_SEED, token_program.key).map_err(|_| ErrorCode::InvalidTokenAccount)?;
+    require_keys_eq!(unwrap_account.key(), expected, ErrorCode::InvalidTokenAccount);
+
+    let rent = Rent::get()?.minimum_balance(SplTokenAccount::LEN);
+    let create = create_account_with_seed(authority.key, unwrap_account.key, authority.key, UNWRAP_SEED, rent, SplTokenAccount::LEN as u64, token_program.key);
+    invoke_with_seeds(&create, &[authority.clone(), unwrap_account.clone(), swap_accounts.system_program.clone()], signer_seeds)?;
+
+    token::initialize_account3(CpiContext::new(
+        token_program.clone(),
+        token::InitializeAccount3 { account: unwrap_account.clone(), mint: swap_accounts.native_mint.clone(), authority: authority.clone() },
+    ))?;
+    transfer_token(
+        authority.clone(),
+        swap_accounts.swap_source_token.to_account_info(),
+        unwrap_account.clone(),
+        swap_accounts.native_mint.clone(),
+        token_program.clone(),
+        amount_in,
+        WSOL_DECIMALS,
+        signer_seeds,
+    )?;
+    close_token_account(unwrap_account.clone(), authority.clone(), authority.clone(), token_program, signer_seeds)
+}
+
+pub fn swap<'a>(
+    remaining_accounts: &'a [AccountInfo<'a>],
+    amount_in: u64,
+    offset: &mut usize,
+    hop_accounts: &mut HopAccounts,
+    hop: usize,
+    proxy_swap: bool,
+    owner_seeds: Option<&[&[&[u8]]]>,
+) -> Result<u64> {
+    msg!("Dex::SplStakePool amount_in: {}, offset: {}", amount_in, offset);
+    require!(remaining_accounts.len() >= *offset + ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+
+    let mut swap_accounts = SplStakePoolAccounts::parse_accounts(remaining_accounts, *offset)?;
+    if swap_accounts.dex_program_id.key != &lst_spl_stake_pool::id() {
+        return Err(ErrorCode::InvalidProgramId.into());
+    }
+    require_keys_eq!(swap_accounts.native_mint.key(), wsol_program::id(), ErrorCode::InvalidTokenMint);
+
+    // log pool address
+    swap_accounts.stake_pool.key().log();
+
+    // check hop accounts & swap authority
+    let swap_source_token = swap_accounts.swap_source_token.key();
+    let swap_destination_token = swap_accounts.swap_destination_token.key();
+    before_check(swap_accounts.swap_authority_pubkey, &swap_accounts.swap_source_token, swap_destination_token, hop_accounts, hop, proxy_swap, owner_seeds)?;
+
+    // the seeds the swap authority signs with, as `invoke_process` does
+    let signer_seeds = if !proxy_swap && hop == 0 { owner_seeds } else { Some(SA_AUTHORITY_SEED) };
+
+    let is_withdraw = swap_accounts.swap_source_token.mint == swap_accounts.pool_mint.key();
+    let (instruction, account_infos) = if is_withdraw {
+        require_keys_eq!(swap_accounts.swap_destination_token.mint, wsol_program::id(), ErrorCode::InvalidTokenMint);
+
+        let mut data = Vec::with_capacity(ARGS_LEN);
+        data.push(STAKE_POOL_WITHDRAW_SOL_SELECTOR);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+
+        let accounts = vec![
+            AccountMeta::new(swap_accounts.stake_pool.key(), false),
+            AccountMeta::new_readonly(swap_accounts.withdraw_authority.key(), false),
+            AccountMeta::new_readonly(swap_accounts.swap_authority_pubkey.key(), true),
+            AccountMeta::new(swap_source_token, false),
+            AccountMeta::new(swap_accounts.reserve_stake.key(), false),
+            AccountMeta::new(swap_destination_token, false),
+            AccountMeta::new(swap_accounts.manager_fee_account.key(), false),
+            AccountMeta::new(swap_accounts.pool_mint.key(), false),
+            AccountMeta::new_readonly(swap_accounts.clock.key(), false),
+            AccountMeta::new_readonly(swap_accounts.stake_history.key(), false),
+            AccountMeta::new_readonly(swap_accounts.stake_program.key(), false),
+            AccountMeta::new_readonly(swap_accounts.token_program.key(), false),
+        ];
+
+        let account_infos = vec![
+            swap_accounts.stake_pool.to_account_info(),
+            swap_accounts.withdraw_authority.to_account_info(),
+            swap_accounts.swap_authority_pubkey.to_account_info(),
+            swap_accounts.swap_source_token.to_account_info(),
+            swap_accounts.reserve_stake.to_account_info(),
+            swap_accounts.swap_destination_token.to_account_info(),
+            swap_accounts.manager_fee_account.to_account_info(),
+            swap_accounts.pool_mint.to_account_info(),
+            swap_accounts.clock.to_account_info(),
+            swap_accounts.stake_history.to_account_info(),
+            swap_accounts.stake_program.to_account_info(),
+            swap_accounts.token_program.to_account_info(),
+        ];
+
+        (Instruction { program_id: swap_accounts.dex_program_id.key(), accounts, data }, account_infos)
+    } else {
+        require_keys_eq!(swap_accounts.swap_source_token.mint, wsol_program::id(), ErrorCode::InvalidTokenMint);
+        require_keys_eq!(swap_accounts.swap_destination_token.mint, swap_accounts.pool_mint.key(), ErrorCode::InvalidTokenMint);
+
+        // the pool takes lamports from a system account, so the swap authority deposits the unwrapped amount
+        unwrap_sol(&swap_accounts, amount_in, signer_seeds)?;
+
+        let mut data = Vec::with_capacity(ARGS_LEN);
+        data.push(STAKE_POOL_DEPOSIT_SOL_SELECTOR);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+
+        // the manager fee account doubles as the referrer, so the referral fee stays with the pool manager
+        let accounts = vec![
+            AccountMeta::new(swap_accounts.stake_pool.key(), false),
+            AccountMeta::new_readonly(swap_accounts.withdraw_authority.key(), false),
+            AccountMeta::new(swap_accounts.reserve_stake.key(), false),
+            AccountMeta::new(swap_accounts.swap_authority_pubkey.key(), true),
+            AccountMeta::new(swap_destination_token, false),
+            AccountMeta::new(swap_accounts.manager_fee_account.key(), false),
+            AccountMeta::new(swap_accounts.manager_fee_account.key(), false),
+            AccountMeta::new(swap_accounts.pool_mint.key(), false),
+            AccountMeta::new_readonly(swap_accounts.system_program.key(), false),
+            AccountMeta::new_readonly(swap_accounts.token_program.key(), false),
+        ];
+
+        let account_infos = vec![
+            swap_accounts.stake_pool.to_account_info(),
+            swap_accounts.withdraw_authority.to_account_info(),
+            swap_accounts.reserve_stake.to_account_info(),
+            swap_accounts.swap_authority_pubkey.to_account_info(),
+            swap_accounts.swap_destination_token.to_account_info(),
+            swap_accounts.manager_fee_account.to_account_info(),
+            swap_accounts.manager_fee_account.to_account_info(),
+            swap_accounts.pool_mint.to_account_info(),
+            swap_accounts.system_program.to_account_info(),
+            swap_accounts.token_program.to_account_info(),
+        ];
+
+        (Instruction { program_id: swap_accounts.dex_program_id.key(), accounts, data }, account_infos)
+    };
+
+    let amount_out = if is_withdraw {
+        let dex_processor =
+            &WithdrawSolProcessor { token_program: swap_accounts.token_program.to_account_info(), destination: swap_accounts.swap_destination_token.to_account_info() };
+        invoke_process(
+            amount_in,
+            dex_processor,
+            &account_infos,
+            &mut swap_accounts.swap_source_token,
+            &mut swap_accounts.swap_destination_token,
+            hop_accounts,
+            instruction,
+            hop,
+            offset,
+            ACCOUNTS_LEN,
+            proxy_swap,
+            owner_seeds,
+        )?
+    } else {
+        invoke_process(
+            amount_in,
+            &DepositSolProcessor,
+            &account_infos,
+            &mut swap_accounts.swap_source_token,
+            &mut swap_accounts.swap_destination_token,
+            hop_accounts,
+            instruction,
+            hop,
+            offset,
+            ACCOUNTS_LEN,
+            proxy_swap,
+            owner_seeds,
+        )?
+    };
+    Ok(amount_out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_pack_spl_stake_pool_instruction() {
+        let amount_in = 100u64;
+
+        for selector in [STAKE_POOL_DEPOSIT_SOL_SELECTOR, STAKE_POOL_WITHDRAW_SOL_SELECTOR] {
+            let mut data = Vec::with_capacity(ARGS_LEN);
+            data.push(selector);
+            data.extend_from_slice(&amount_in.to_le_bytes());
+
+            assert!(data.len() == ARGS_LEN);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
+use anchor_spl::token_interface::{TokenAccount, TokenInterface};
+use arrayref::array_ref;
+use magnus_shared::amm_spl_token_swap::{self, ACCOUNTS_LEN, ARGS_LEN};
+
+use crate::{
+    adapters::common::{before_check, invoke_process, DexProcessor},
+    error::ErrorCode,
+    HopAccounts, SPL_TOKEN_SWAP_SELECTOR,
+};
+
+pub struct SplTokenSwapProcessor;
+impl DexProcessor for SplTokenSwapProcessor {}
+
+pub struct SplTokenSwapAccounts<'info> {
+    pub dex_program_id: &'info AccountInfo<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub swap: &'info AccountInfo<'info>,
+    pub authority: &'info AccountInfo<'info>,
+    pub swap_authority_pubkey: &'info AccountInfo<'info>,
+    pub swap_source_token: InterfaceAccount<'info, TokenAccount>,
+    pub pool_source: InterfaceAccount<'info, TokenAccount>,
+    pub pool_destination: InterfaceAccount<'info, TokenAccount>,
+    pub swap_destination_token: InterfaceAccount<'info, TokenAccount>,
+    pub pool_mint: &'info AccountInfo<'info>,
+    pub pool_fee: &'info AccountInfo<'info>,
+}
+
+impl<'info> SplTokenSwapAccounts<'info> {
+    fn parse_accounts(accounts: &'info [AccountInfo<'info>], offset: usize) -> Result<Self> {
+        let [
+            dex_program_id,
+            token_program,
+            swap,
+            authority,
+            swap_authority_pubkey,
+            swap_source_token,
+            pool_source,
+            pool_destination,
+            swap_destination_token,
+            pool_mint,
+            pool_fee,
+        ]: &[AccountInfo<'info>; ACCOUNTS_LEN] = array_ref![accounts, offset, ACCOUNTS_LEN];
+
+        Ok(Self {
+            dex_program_id,
+            token_program: Interface::try_from(token_program)?,
+            swap,
+            authority,
+            swap_authority_pubkey,
+            swap_source_token: InterfaceAccount::try_from(swap_source_token)?,
+            pool_source: InterfaceAccount::try_from(pool_source)?,
+            pool_destination: InterfaceAccount::try_from(pool_destination)?,
+            swap_destination_token: InterfaceAccount::try_from(swap_destination_token)?,
+            pool_mint,
+            pool_fee,
+        })
+    }
+}
+
+pub fn swap<'a>(
+    remaining_accounts: &'a [AccountInfo<'a>],
+    amount_in: u64,
+    offset: &mut usize,
+    hop_accounts: &mut HopAccounts,
+    hop: usize,
+    proxy_swap: bool,
+    owner_seeds: Option<&[&[&[u8]]]>,
+) -> Result<u64> {
+    msg!("Dex::SplTokenSwap amount_in: {}, offset: {}", amount_in, offset);
+    require!(remaining_accounts.len() >= *offset + ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+
+    let mut swap_accounts = SplTokenSwapAccounts::parse_accounts(remaining_accounts, *offset)?;
+    if swap_accounts.dex_program_id.key != &amm_spl_token_swap::id() {
+        return Err(ErrorCode::InvalidProgramId.into());
+    }
+
+    // log pool address
+    swap_accounts.swap.key().log();
+
+    // check hop accounts & swap authority
+    let swap_source_token = swap_accounts.swap_source_token.key();
+    let swap_destination_token = swap_accounts.swap_destination_token.key();
+    before_check(swap_accounts.swap_authority_pubkey, &swap_accounts.swap_source_token, swap_destination_token, hop_accounts, hop, proxy_swap, owner_seeds)?;
+
+    let minimum_amount_out = 1u64;
+    let mut data = Vec::with_capacity(ARGS_LEN);
+    data.push(SPL_TOKEN_SWAP_SELECTOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+    // no host fee account, the whole owner fee goes to the pool fee account
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_accounts.swap.key(), false),
+        AccountMeta::new_readonly(swap_accounts.authority.key(), false),
+        AccountMeta::new_readonly(swap_accounts.swap_authority_pubkey.key(), true),
+        AccountMeta::new(swap_source_token, false),
+        AccountMeta::new(swap_accounts.pool_source.key(), false),
+        AccountMeta::new(swap_accounts.pool_destination.key(), false),
+        AccountMeta::new(swap_destination_token, false),
+        AccountMeta::new(swap_accounts.pool_mint.key(), false),
+        AccountMeta::new(swap_accounts.pool_fee.key(), false),
+        AccountMeta::new_readonly(swap_accounts.token_program.key(), false),
+    ];
+
+    let account_infos = vec![
+        swap_accounts.swap.to_account_info(),
+        swap_accounts.authority.to_account_info(),
+        swap_accounts.swap_authority_pubkey.to_account_info(),
+        swap_accounts.swap_source_token.to_account_info(),
+        swap_accounts.pool_source.to_account_info(),
+        swap_accounts.pool_destination.to_account_info(),
+        swap_accounts.swap_destination_token.to_account_info(),
+        swap_accounts.pool_mint.to_account_info(),
+        swap_accounts.pool_fee.to_account_info(),
+        swap_accounts.token_program.to_account_info(),
+    ];
+
+    let instruction = Instruction { program_id: swap_accounts.dex_program_id.key(), accounts, data };
+
+    let dex_processor = &SplTokenSwapProcessor;
+    let amount_out = invoke_process(
+        amount_in,
+        dex_processor,
+        &account_infos,
+        &mut swap_accounts.swap_source_token,
+        &mut swap_accounts.swap_destination_token,
+        hop_accounts,
+        instruction,
+        hop,
+        offset,
+        ACCOUNTS_LEN,
+        proxy_swap,
+        owner_seeds,
+    )?;
+    Ok(amount_out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_pack_spl_token_swap_instruction() {
+        let amount_in = 100u64;
+        let minimum_amount_out = 1u64;
+
+        let mut data = Vec::with_capacity(ARGS_LEN);
+        data.push(SPL_TOKEN_SWAP_SELECTOR);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+        assert!(data.len() == ARGS_LEN);
+    }
+}
@@ -0,0 +1,205 @@
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use arrayref::array_ref;
+use magnus_shared::amm_whirlpool::{self, ACCOUNTS_LEN, ARGS_LEN};
+
+use crate::{
+    adapters::common::{before_check, invoke_process, DexProcessor},
+    error::ErrorCode,
+    HopAccounts, SWAPV2_SELECTOR, WHIRLPOOL_MAX_SQRT_PRICE_X64, WHIRLPOOL_MIN_SQRT_PRICE_X64,
+};
+
+pub struct WhirlpoolSwapProcessor;
+impl DexProcessor for WhirlpoolSwapProcessor {}
+
+pub struct WhirlpoolAccounts<'info> {
+    pub dex_program_id: &'info AccountInfo<'info>,
+    pub swap_authority_pubkey: &'info AccountInfo<'info>,
+    pub swap_source_token: InterfaceAccount<'info, TokenAccount>,
+    pub swap_destination_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub whirlpool: &'info AccountInfo<'info>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub memo_program: &'info AccountInfo<'info>,
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+    pub token_vault_a: InterfaceAccount<'info, TokenAccount>,
+    pub token_vault_b: InterfaceAccount<'info, TokenAccount>,
+    pub tick_array0: &'info AccountInfo<'info>,
+    pub tick_array1: &'info AccountInfo<'info>,
+    pub tick_array2: &'info AccountInfo<'info>,
+    pub oracle: &'info AccountInfo<'info>,
+}
+
+impl<'info> WhirlpoolAccounts<'info> {
+    fn parse_accounts(accounts: &'info [AccountInfo<'info>], offset: usize) -> Result<Self> {
+        let [
+            dex_program_id,
+            swap_authority_pubkey,
+            swap_source_token,
+            swap_destination_token,
+            whirlpool,
+            token_program_a,
+            token_program_b,
+            memo_program,
+            token_mint_a,
+            token_mint_b,
+            token_vault_a,
+            token_vault_b,
+            tick_array0,
+            tick_array1,
+            tick_array2,
+            oracle,
+        ]: &[AccountInfo<'info>; ACCOUNTS_LEN] = array_ref![accounts, offset, ACCOUNTS_LEN];
+
+        Ok(Self {
+            dex_program_id,
+            swap_authority_pubkey,
+            swap_source_token: InterfaceAccount::try_from(swap_source_token)?,
+            swap_destination_token: InterfaceAccount::try_from(swap_destination_token)?,
+            whirlpool,
+            token_program_a: Interface::try_from(token_program_a)?,
+            token_program_b: Interface::try_from(token_program_b)?,
+            memo_program,
+            token_mint_a: InterfaceAccount::try_from(token_mint_a)?,
+            token_mint_b: InterfaceAccount::try_from(token_mint_b)?,
+            token_vault_a: InterfaceAccount::try_from(token_vault_a)?,
+            token_vault_b: InterfaceAccount::try_from(token_vault_b)?,
+            tick_array0,
+            tick_array1,
+            tick_array2,
+            oracle,
+        })
+    }
+}
+
+pub fn swap<'a>(
+    remaining_accounts: &'a [AccountInfo<'a>],
+    amount_in: u64,
+    offset: &mut usize,
+    hop_accounts: &mut HopAccounts,
+    hop: usize,
+    proxy_swap: bool,
+    owner_seeds: Option<&[&[&[u8]]]>,
+) -> Result<u64> {
+    msg!("Dex::Whirlpool amount_in: {}, offset: {}", amount_in, offset);
+    require!(remaining_accounts.len() >= *offset + ACCOUNTS_LEN, ErrorCode::InvalidAccountsLength);
+
+    let mut swap_accounts = WhirlpoolAccounts::parse_accounts(remaining_accounts, *offset)?;
+    if swap_accounts.dex_program_id.key != &amm_whirlpool::id() {
+        return Err(ErrorCode::InvalidProgramId.into());
+    }
+
+    // log pool address
+    swap_accounts.whirlpool.key().log();
+
+    // check hop accounts & swap authority
+    let swap_source_token = swap_accounts.swap_source_token.key();
+    let swap_destination_token = swap_accounts.swap_destination_token.key();
+    before_check(swap_accounts.swap_authority_pubkey, &swap_accounts.swap_source_token, swap_destination_token, hop_accounts, hop, proxy_swap, owner_seeds)?;
+
+    let a_to_b = swap_accounts.swap_source_token.mint == swap_accounts.token_mint_a.key();
+    let (token_owner_account_a, token_owner_account_b) = if a_to_b {
+        (swap_source_token, swap_destination_token)
+    } else {
+        (swap_destination_token, swap_source_token)
+    };
+
+    let other_amount_threshold = 1u64;
+    let sqrt_price_limit = if a_to_b { WHIRLPOOL_MIN_SQRT_PRICE_X64 } else { WHIRLPOOL_MAX_SQRT_PRICE_X64 };
+    let amount_specified_is_input = true;
+
+    let mut data = Vec::with_capacity(ARGS_LEN);
+    data.extend_from_slice(SWAPV2_SELECTOR);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&other_amount_threshold.to_le_bytes());
+    data.extend_from_slice(&sqrt_price_limit.to_le_bytes());
+    data.extend_from_slice(&(amount_specified_is_input as u8).to_le_bytes());
+    data.extend_from_slice(&(a_to_b as u8).to_le_bytes());
+    data.push(0); // remaining_accounts_info: None
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_accounts.token_program_a.key(), false),
+        AccountMeta::new_readonly(swap_accounts.token_program_b.key(), false),
+        AccountMeta::new_readonly(swap_accounts.memo_program.key(), false),
+        AccountMeta::new_readonly(swap_accounts.swap_authority_pubkey.key(), true),
+        AccountMeta::new(swap_accounts.whirlpool.key(), false),
+        AccountMeta::new_readonly(swap_accounts.token_mint_a.key(), false),
+        AccountMeta::new_readonly(swap_accounts.token_mint_b.key(), false),
+        AccountMeta::new(token_owner_account_a, false),
+        AccountMeta::new(swap_accounts.token_vault_a.key(), false),
+        AccountMeta::new(token_owner_account_b, false),
+        AccountMeta::new(swap_accounts.token_vault_b.key(), false),
+        AccountMeta::new(swap_accounts.tick_array0.key(), false),
+        AccountMeta::new(swap_accounts.tick_array1.key(), false),
+        AccountMeta::new(swap_accounts.tick_array2.key(), false),
+        AccountMeta::new(swap_accounts.oracle.key(), false),
+    ];
+
+    let (token_owner_account_a_info, token_owner_account_b_info) = if a_to_b {
+        (swap_accounts.swap_source_token.to_account_info(), swap_accounts.swap_destination_token.to_account_info())
+    } else {
+        (swap_accounts.swap_destination_token.to_account_info(), swap_accounts.swap_source_token.to_account_info())
+    };
+
+    let account_infos = vec![
+        swap_accounts.token_program_a.to_account_info(),
+        swap_accounts.token_program_b.to_account_info(),
+        swap_accounts.memo_program.to_account_info(),
+        swap_accounts.swap_authority_pubkey.to_account_info(),
+        swap_accounts.whirlpool.to_account_info(),
+        swap_accounts.token_mint_a.to_account_info(),
+        swap_accounts.token_mint_b.to_account_info(),
+        token_owner_account_a_info,
+        swap_accounts.token_vault_a.to_account_info(),
+        token_owner_account_b_info,
+        swap_accounts.token_vault_b.to_account_info(),
+        swap_accounts.tick_array0.to_account_info(),
+        swap_accounts.tick_array1.to_account_info(),
+        swap_accounts.tick_array2.to_account_info(),
+        swap_accounts.oracle.to_account_info(),
+    ];
+
+    let instruction = Instruction { program_id: swap_accounts.dex_program_id.key(), accounts, data };
+
+    let dex_processor = &WhirlpoolSwapProcessor;
+    let amount_out = invoke_process(
+        amount_in,
+        dex_processor,
+        &account_infos,
+        &mut swap_accounts.swap_source_token,
+        &mut swap_accounts.swap_destination_token,
+        hop_accounts,
+        instruction,
+        hop,
+        offset,
+        ACCOUNTS_LEN,
+        proxy_swap,
+        owner_seeds,
+    )?;
+    Ok(amount_out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_pack_whirlpool_instruction() {
+        let amount_in = 100u64;
+        let other_amount_threshold = 1u64;
+        let sqrt_price_limit = WHIRLPOOL_MIN_SQRT_PRICE_X64;
+
+        let mut data = Vec::with_capacity(ARGS_LEN);
+        data.extend_from_slice(SWAPV2_SELECTOR);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&other_amount_threshold.to_le_bytes());
+        data.extend_from_slice(&sqrt_price_limit.to_le_bytes());
+        data.extend_from_slice(&(true as u8).to_le_bytes());
+        data.extend_from_slice(&(true as u8).to_le_bytes());
+        data.push(0);
+
+        assert!(data.len() == ARGS_LEN);
+    }
+}
@@ -21,6 +21,20 @@ pub const CPSWAP_SELECTOR: &[u8; 8] = &[143, 190, 90, 218, 196, 30, 51, 222];
 pub const TESSERA_SWAP_SELECTOR: &[u8; 1] = &[16];
 pub const GOONFI_SWAP_SELECTOR: &[u8; 1] = &[2];
 pub const BISONFI_SWAP_SELECTOR: u8 = 0x2;
+pub const RAYDIUM_AMM_V4_SWAP_BASE_IN_SELECTOR: u8 = 0x9;
+pub const STAKE_POOL_DEPOSIT_SOL_SELECTOR: u8 = 0xe;
+pub const STAKE_POOL_WITHDRAW_SOL_SELECTOR: u8 = 0x10;
+pub const SPL_TOKEN_SWAP_SELECTOR: u8 = 0x1;
+
+// Whirlpool's sqrt price bounds, the (unreachable) price limits of a swap
+pub const WHIRLPOOL_MIN_SQRT_PRICE_X64: u128 = 4295048016;
+pub const WHIRLPOOL_MAX_SQRT_PRICE_X64: u128 = 79226673515401279992447579055;
+
+// Phoenix's swap instruction and the offsets of the market header fields a taker order needs
+pub const PHOENIX_SWAP_SELECTOR: u8 = 0x0;
+pub const PHOENIX_BASE_MINT_OFFSET: usize = 48;
+pub const PHOENIX_BASE_LOT_SIZE_OFFSET: usize = 112;
+pub const PHOENIX_QUOTE_LOT_SIZE_OFFSET: usize = 192;
 
 pub const HUMIDIFI_SWAP_SELECTOR: u8 = 0x4;
 const HUMIDIFI_IX_DATA_KEY_SEED: [u8; 32] =
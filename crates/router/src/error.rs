@@ -85,4 +85,31 @@ pub enum ErrorCode {
 
     #[msg("Invalid Goonfi parameters")]
     InvalidGoonfiParameters,
+
+    #[msg("Swap deadline exceeded")]
+    SwapDeadlineExceeded,
+
+    #[msg("Swap max slot exceeded")]
+    SwapMaxSlotExceeded,
+
+    #[msg("Hop min outs must match the routes")]
+    HopMinOutsMustMatchRoutes,
+
+    #[msg("Hop min return not reached")]
+    HopMinReturnNotReached,
+
+    #[msg("Quote route is simulation only")]
+    QuoteRouteSimulationOnly,
+
+    #[msg("Arb min profit not reached")]
+    ArbMinProfitNotReached,
+
+    #[msg("Leg accounts lens must match the routes")]
+    LegAccountsLensMustMatchRoutes,
+
+    #[msg("Invalid leg accounts len")]
+    InvalidLegAccountsLen,
+
+    #[msg("Invalid Phoenix market")]
+    InvalidPhoenixMarket,
 }
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{check_swap_deadline, error::ErrorCode, execute_routes, utils::*, Route};
+
+/// Circular arbitrage: the routes start and end in the same token account,
+/// so `source_mint == destination_mint` holds by construction.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ArbAccounts<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = payer,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct ArbArgs {
+    pub amount_in: u64,
+    pub min_profit: u64,                              // amount_out must reach amount_in + min_profit
+    pub amounts: Vec<u64>,                            // 1st level split amount
+    pub routes: Vec<Vec<Route>>,                      // 2nd level split route
+    pub deadline_unix_ts: Option<i64>,                // reject once the cluster clock is past it
+    pub max_slot: Option<u64>,                        // reject once the current slot is past it
+    pub leg_accounts_lens: Option<Vec<Vec<Vec<u8>>>>, // per split/hop/dex accounts count, 0 keeps the dex's default
+}
+
+#[event]
+#[derive(Debug)]
+pub struct ArbCompleted {
+    pub order_id: u64,
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub profit: u64,
+    pub leg_amounts_out: Vec<Vec<Vec<u64>>>, // per split/hop/dex amount out
+}
+
+pub fn arb_handler<'a>(ctx: Context<'_, '_, 'a, 'a, ArbAccounts<'a>>, args: ArbArgs, order_id: u64) -> Result<()> {
+    let ArbArgs { amount_in, min_profit, amounts, routes, deadline_unix_ts, max_slot, leg_accounts_lens } = args;
+    let token_account = &mut ctx.accounts.token_account;
+    log_swap_basic_info(order_id, &ctx.accounts.mint.key(), &ctx.accounts.mint.key(), &token_account.owner, &token_account.owner);
+
+    let before_balance = token_account.amount;
+    let min_amount_out = amount_in.checked_add(min_profit).ok_or(ErrorCode::CalculationError)?;
+    log_swap_balance_before(before_balance, before_balance, amount_in, min_amount_out, min_amount_out);
+
+    // Check the arb hasn't landed later than the caller allowed
+    check_swap_deadline(deadline_unix_ts, max_slot)?;
+
+    require!(amount_in > 0, ErrorCode::AmountInMustBeGreaterThanZero);
+    let (leg_amounts_out, swap_events) =
+        execute_routes(&token_account.key(), &token_account.key(), ctx.remaining_accounts, &amounts, &routes, None, leg_accounts_lens.as_deref(), amount_in, false, None)?;
+
+    token_account.reload()?;
+    let after_balance = token_account.amount;
+    // The account was debited amount_in and credited amount_out
+    let amount_out = after_balance.checked_add(amount_in).ok_or(ErrorCode::CalculationError)?.checked_sub(before_balance).ok_or(ErrorCode::CalculationError)?;
+    log_swap_end(after_balance, after_balance, amount_in, amount_out);
+
+    // Check min profit
+    if amount_out < min_amount_out {
+        msg!("ArbMinProfitNotReached: amount_in={}, amount_out={}, min_profit={}", amount_in, amount_out, min_profit);
+        return Err(ErrorCode::ArbMinProfitNotReached.into());
+    }
+
+    for event in swap_events {
+        emit_cpi!(event);
+    }
+    emit_cpi!(ArbCompleted {
+        order_id,
+        mint: ctx.accounts.mint.key(),
+        owner: ctx.accounts.token_account.owner,
+        amount_in,
+        amount_out,
+        profit: amount_out - amount_in,
+        leg_amounts_out,
+    });
+    Ok(())
+}
@@ -14,7 +14,7 @@ pub struct HopAccounts {
     pub to_account: Pubkey,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct Route {
     pub dexes: Vec<Dex>,
     pub weights: Vec<u8>,
@@ -29,6 +29,34 @@ pub struct SwapArgs {
     pub routes: Vec<Vec<Route>>, // 2nd level split route
 }
 
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct SwapArgsV2 {
+    pub amount_in: u64,
+    pub expect_amount_out: u64,
+    pub min_return: u64,
+    pub amounts: Vec<u64>,                            // 1st level split amount
+    pub routes: Vec<Vec<Route>>,                      // 2nd level split route
+    pub deadline_unix_ts: Option<i64>,                // reject once the cluster clock is past it
+    pub max_slot: Option<u64>,                        // reject once the current slot is past it
+    pub hop_min_outs: Option<Vec<Vec<Vec<u64>>>>,     // per split/hop/dex min out, 0 skips the check
+    pub leg_accounts_lens: Option<Vec<Vec<Vec<u8>>>>, // per split/hop/dex accounts count, 0 keeps the dex's default
+}
+
+impl From<SwapArgs> for SwapArgsV2 {
+    fn from(args: SwapArgs) -> Self {
+        let SwapArgs { amount_in, expect_amount_out, min_return, amounts, routes } = args;
+        SwapArgsV2 { amount_in, expect_amount_out, min_return, amounts, routes, deadline_unix_ts: None, max_slot: None, hop_min_outs: None, leg_accounts_lens: None }
+    }
+}
+
+pub type LegAmountsOut = Vec<Vec<Vec<u64>>>; // per split/hop/dex amount out
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, Default)]
+pub struct RouteQuote {
+    pub amount_out: u64,
+    pub leg_amounts_out: Vec<Vec<Vec<u64>>>, // per split/hop/dex amount out
+}
+
 #[event]
 #[derive(Debug)]
 pub struct SwapEvent {
@@ -37,6 +65,26 @@ pub struct SwapEvent {
     pub amount_out: u64,
 }
 
+#[event]
+#[derive(Debug)]
+pub struct SwapCompleted {
+    pub order_id: u64,
+    pub source_mint: Pubkey,
+    pub destination_mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub amounts: Vec<u64>,                   // 1st level split amount
+    pub routes: Vec<Vec<Route>>,             // 2nd level split route
+    pub leg_amounts_out: Vec<Vec<Vec<u64>>>, // per split/hop/dex amount out
+}
+
+/// What a `common_swap` produced, left to the caller to emit.
+pub struct SwapOutcome {
+    pub swap_events: Vec<SwapEvent>,
+    pub swap_completed: SwapCompleted,
+}
+
 pub fn common_swap<'info, T: CommonSwapProcessor<'info>>(
     swap_processor: &T,
     payer: &AccountInfo<'info>,
@@ -54,9 +102,9 @@ pub fn common_swap<'info, T: CommonSwapProcessor<'info>>(
     associated_token_program: &Option<Program<'info, AssociatedToken>>,
     system_program: &Option<Program<'info, System>>,
     remaining_accounts: &'info [AccountInfo<'info>],
-    args: SwapArgs,
+    args: SwapArgsV2,
     order_id: u64,
-) -> Result<u64> {
+) -> Result<SwapOutcome> {
     log_swap_basic_info(order_id, &source_mint.key(), &destination_mint.key(), &source_token_account.owner, &destination_token_account.owner);
 
     let before_source_balance = source_token_account.amount;
@@ -86,14 +134,26 @@ pub fn common_swap<'info, T: CommonSwapProcessor<'info>>(
         system_program,
     )?;
 
+    // Check the swap hasn't landed later than the caller allowed
+    check_swap_deadline(args.deadline_unix_ts, args.max_slot)?;
+
     // before swap hook
     let real_amount_in = swap_processor.before_swap(owner, source_token_account, source_mint, source_token_sa, source_token_program, args.amount_in, owner_seeds)?;
 
     // Common swap
-    let amount_out = execute_swap(&mut source_account, &mut destination_account, remaining_accounts, args, real_amount_in, source_token_sa.is_some(), owner_seeds)?;
+    let (route_quote, swap_events) =
+        execute_swap(&mut source_account, &mut destination_account, remaining_accounts, &args, real_amount_in, source_token_sa.is_some(), owner_seeds)?;
 
     // after swap hook
-    swap_processor.after_swap(sa_authority, destination_token_account, destination_mint, destination_token_sa, destination_token_program, amount_out, Some(SA_AUTHORITY_SEED))?;
+    swap_processor.after_swap(
+        sa_authority,
+        destination_token_account,
+        destination_mint,
+        destination_token_sa,
+        destination_token_program,
+        route_quote.amount_out,
+        Some(SA_AUTHORITY_SEED),
+    )?;
 
     // source token account has been closed in pumpfun buy
     let after_source_balance = if source_token_account.get_lamports() != 0 {
@@ -113,33 +173,131 @@ pub fn common_swap<'info, T: CommonSwapProcessor<'info>>(
 
     // Check min return
     require!(destination_token_change >= min_return, ErrorCode::MinReturnNotReached);
-    Ok(destination_token_change)
+    let swap_completed = SwapCompleted {
+        order_id,
+        source_mint: source_mint.key(),
+        destination_mint: destination_mint.key(),
+        owner: source_token_account.owner,
+        amount_in: source_token_change,
+        amount_out: destination_token_change,
+        amounts: args.amounts,
+        routes: args.routes,
+        leg_amounts_out: route_quote.leg_amounts_out,
+    };
+    Ok(SwapOutcome { swap_events, swap_completed })
+}
+
+pub(crate) fn check_swap_deadline(deadline_unix_ts: Option<i64>, max_slot: Option<u64>) -> Result<()> {
+    if deadline_unix_ts.is_none() && max_slot.is_none() {
+        return Ok(());
+    }
+
+    let clock = Clock::get()?;
+    if let Some(deadline_unix_ts) = deadline_unix_ts {
+        if clock.unix_timestamp > deadline_unix_ts {
+            msg!("SwapDeadlineExceeded: unix_timestamp={}, deadline_unix_ts={}", clock.unix_timestamp, deadline_unix_ts);
+            return Err(ErrorCode::SwapDeadlineExceeded.into());
+        }
+    }
+    if let Some(max_slot) = max_slot {
+        if clock.slot > max_slot {
+            msg!("SwapMaxSlotExceeded: slot={}, max_slot={}", clock.slot, max_slot);
+            return Err(ErrorCode::SwapMaxSlotExceeded.into());
+        }
+    }
+    Ok(())
+}
+
+/// Checks a per split/hop/dex vector has an entry for every leg of `routes`.
+fn check_legs_shape<T>(legs: &[Vec<Vec<T>>], routes: &[Vec<Route>], error: ErrorCode) -> Result<()> {
+    let matches = legs.len() == routes.len()
+        && legs.iter().zip(routes).all(|(hop_legs, hops)| hop_legs.len() == hops.len() && hop_legs.iter().zip(hops).all(|(dex_legs, route)| dex_legs.len() == route.dexes.len()));
+    if !matches {
+        return Err(error.into());
+    }
+    Ok(())
+}
+
+/// The number of accounts a leg takes, `0` falling back to the dex's fixed `ACCOUNTS_LEN`.
+fn leg_accounts_len(dex: &Dex, accounts_len: u8) -> Result<usize> {
+    if accounts_len == 0 {
+        return Ok(dex.accounts_len());
+    }
+
+    let accounts_len = accounts_len as usize;
+    let range = dex.accounts_len_range();
+    if !range.contains(&accounts_len) {
+        msg!("InvalidLegAccountsLen: dex={:?}, accounts_len={}, min={}, max={}", dex, accounts_len, range.start(), range.end());
+        return Err(ErrorCode::InvalidLegAccountsLen.into());
+    }
+    Ok(accounts_len)
 }
 
 fn execute_swap<'info>(
     source_account: &mut InterfaceAccount<'info, TokenAccount>,
     destination_account: &mut InterfaceAccount<'info, TokenAccount>,
     remaining_accounts: &'info [AccountInfo<'info>],
-    args: SwapArgs,
+    args: &SwapArgsV2,
     real_amount_in: u64,
     proxy_from: bool,
     owner_seeds: Option<&[&[&[u8]]]>,
-) -> Result<u64> {
+) -> Result<(RouteQuote, Vec<SwapEvent>)> {
     destination_account.reload()?;
     let before_destination_balance = destination_account.amount;
 
     // Check SwapArgs
-    let SwapArgs { amount_in: _, min_return, expect_amount_out, amounts, routes } = &args;
+    let SwapArgsV2 { amount_in: _, min_return, expect_amount_out, amounts, routes, hop_min_outs, leg_accounts_lens, .. } = args;
     require!(real_amount_in > 0, ErrorCode::AmountInMustBeGreaterThanZero);
     require!(*min_return > 0, ErrorCode::MinReturnMustBeGreaterThanZero);
     require!(*expect_amount_out >= *min_return, ErrorCode::InvalidExpectAmountOut);
+
+    // Swap by Routes
+    let (leg_amounts_out, swap_events) = execute_routes(
+        &source_account.key(),
+        &destination_account.key(),
+        remaining_accounts,
+        amounts,
+        routes,
+        hop_min_outs.as_deref(),
+        leg_accounts_lens.as_deref(),
+        real_amount_in,
+        proxy_from,
+        owner_seeds,
+    )?;
+
+    destination_account.reload()?;
+    let after_destination_balance = destination_account.amount;
+    let amount_out = after_destination_balance.checked_sub(before_destination_balance).ok_or(ErrorCode::CalculationError)?;
+    Ok((RouteQuote { amount_out, leg_amounts_out }, swap_events))
+}
+
+/// Runs `routes` leg by leg, returning the per split/hop/dex amounts out and the matching `SwapEvent`s.
+pub(crate) fn execute_routes<'info>(
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    amounts: &[u64],
+    routes: &[Vec<Route>],
+    hop_min_outs: Option<&[Vec<Vec<u64>>]>,
+    leg_accounts_lens: Option<&[Vec<Vec<u8>>]>,
+    real_amount_in: u64,
+    proxy_from: bool,
+    owner_seeds: Option<&[&[&[u8]]]>,
+) -> Result<(LegAmountsOut, Vec<SwapEvent>)> {
     require!(amounts.len() == routes.len(), ErrorCode::AmountsAndRoutesMustHaveTheSameLength);
+    if let Some(hop_min_outs) = hop_min_outs {
+        check_legs_shape(hop_min_outs, routes, ErrorCode::HopMinOutsMustMatchRoutes)?;
+    }
+    if let Some(leg_accounts_lens) = leg_accounts_lens {
+        check_legs_shape(leg_accounts_lens, routes, ErrorCode::LegAccountsLensMustMatchRoutes)?;
+    }
 
     let total_amounts: u64 = amounts.iter().try_fold(0u64, |acc, &x| acc.checked_add(x).ok_or(ErrorCode::CalculationError))?;
     require!(total_amounts == real_amount_in, ErrorCode::TotalAmountsMustBeEqualToAmountIn);
 
-    // Swap by Routes
     let mut offset: usize = 0;
+    let mut leg_amounts_out = Vec::with_capacity(routes.len());
+    let mut swap_events = Vec::new();
     // Level 1 split handling
     for (i, hops) in routes.iter().enumerate() {
         require!(hops.len() <= MAX_HOPS, ErrorCode::TooManyHops);
@@ -147,6 +305,7 @@ fn execute_swap<'info>(
 
         // Multi-hop handling
         let mut last_to_account = ZERO_ADDRESS;
+        let mut hop_amounts_out = Vec::with_capacity(hops.len());
         for (hop, route) in hops.iter().enumerate() {
             let dexes = &route.dexes;
             let weights = &route.weights;
@@ -158,6 +317,7 @@ fn execute_swap<'info>(
             let mut hop_accounts = HopAccounts { last_to_account, from_account: ZERO_ADDRESS, to_account: ZERO_ADDRESS };
             let mut amount_out: u64 = 0;
             let mut acc_fork_in: u64 = 0;
+            let mut dex_amounts_out = Vec::with_capacity(dexes.len());
             for (index, dex) in dexes.iter().enumerate() {
                 // Calculate 2 level split amount
                 let fork_amount_in = if index == dexes.len() - 1 {
@@ -171,35 +331,45 @@ fn execute_swap<'info>(
                 };
 
                 // Execute swap
-                let fork_amount_out = distribute_swap(dex, remaining_accounts, fork_amount_in, &mut offset, &mut hop_accounts, hop, proxy_from, owner_seeds)?;
+                let accounts_len = leg_accounts_len(dex, leg_accounts_lens.map_or(0, |leg_accounts_lens| leg_accounts_lens[i][hop][index]))?;
+                let fork_amount_out = distribute_swap(dex, remaining_accounts, fork_amount_in, &mut offset, &mut hop_accounts, hop, accounts_len, proxy_from, owner_seeds)?;
 
-                // Emit SwapEvent
+                // Record SwapEvent, emitted by the caller
                 let event = SwapEvent { dex: *dex, amount_in: fork_amount_in, amount_out: fork_amount_out };
-                emit!(event);
                 msg!("{:?}", event);
+                swap_events.push(event);
                 hop_accounts.from_account.log();
                 hop_accounts.to_account.log();
 
+                // CHECK: Verify the leg didn't degrade below the quoted min out
+                if let Some(hop_min_outs) = hop_min_outs {
+                    let min_out = hop_min_outs[i][hop][index];
+                    if fork_amount_out < min_out {
+                        msg!("HopMinReturnNotReached: route={}, hop={}, dex={:?}, amount_out={}, min_out={}", i, hop, dex, fork_amount_out, min_out);
+                        return Err(ErrorCode::HopMinReturnNotReached.into());
+                    }
+                }
+
                 amount_out = amount_out.checked_add(fork_amount_out).ok_or(ErrorCode::CalculationError)?;
+                dex_amounts_out.push(fork_amount_out);
             }
 
             if hop == 0 {
                 // CHECK: Verify the first hop's from_token must be consistent with ctx.accounts.source_token_account
-                require!(source_account.key() == hop_accounts.from_account, ErrorCode::InvalidSourceTokenAccount);
+                require!(*source_account == hop_accounts.from_account, ErrorCode::InvalidSourceTokenAccount);
             }
             if hop == hops.len() - 1 {
                 // CHECK: Verify the last hop's to_account must be consistent with ctx.accounts.destination_token_account
-                require!(destination_account.key() == hop_accounts.to_account, ErrorCode::InvalidDestinationTokenAccount);
+                require!(*destination_account == hop_accounts.to_account, ErrorCode::InvalidDestinationTokenAccount);
             }
             amount_in = amount_out;
             last_to_account = hop_accounts.to_account;
+            hop_amounts_out.push(dex_amounts_out);
         }
+        leg_amounts_out.push(hop_amounts_out);
     }
 
-    destination_account.reload()?;
-    let after_destination_balance = destination_account.amount;
-    let amount_out = after_destination_balance.checked_sub(before_destination_balance).ok_or(ErrorCode::CalculationError)?;
-    Ok(amount_out)
+    Ok((leg_amounts_out, swap_events))
 }
 
 fn distribute_swap<'a>(
@@ -209,12 +379,16 @@ fn distribute_swap<'a>(
     offset: &mut usize,
     hop_accounts: &mut HopAccounts,
     hop: usize,
+    accounts_len: usize,
     proxy_from: bool,
     owner_seeds: Option<&[&[&[u8]]]>,
 ) -> Result<u64> {
     let swap_function = match dex {
-        Dex::RaydiumClV2 => raydium_cl_v2::swap,
+        // take as many tick/bin arrays as the leg carries, the other dexes' `accounts_len` is fixed
+        Dex::RaydiumClV2 => return raydium_cl_v2::swap_with_accounts_len(remaining_accounts, amount_in, offset, hop_accounts, hop, accounts_len, proxy_from, owner_seeds),
+        Dex::MeteoraDlmm => return meteora_dlmm::swap_with_accounts_len(remaining_accounts, amount_in, offset, hop_accounts, hop, accounts_len, proxy_from, owner_seeds),
         Dex::RaydiumCp => raydium_cp::swap,
+        Dex::RaydiumAmmV4 => raydium_amm_v4::swap,
         Dex::ObricV2 => obric_v2::swap,
         Dex::ZeroFi => zerofi::swap,
         Dex::HumidiFi => humidifi::swap,
@@ -222,7 +396,44 @@ fn distribute_swap<'a>(
         Dex::Tessera => tessera::swap,
         Dex::GoonFi => goonfi::swap,
         Dex::BisonFi => bisonfi::swap,
+        Dex::Whirlpool => whirlpool::swap,
+        Dex::Phoenix => phoenix::swap,
+        Dex::SplStakePool => spl_stake_pool::swap,
+        Dex::SplTokenSwap => spl_token_swap::swap,
     };
 
     swap_function(remaining_accounts, amount_in, offset, hop_accounts, hop, proxy_from, owner_seeds)
 }
+
+#[cfg(test)]
+mod tests {
+    use magnus_shared::amm_raydium_cl_v2;
+
+    use super::*;
+
+    #[test]
+    fn leg_accounts_len_defaults_to_the_dex_accounts_len() {
+        assert_eq!(leg_accounts_len(&Dex::RaydiumClV2, 0).unwrap(), amm_raydium_cl_v2::ACCOUNTS_LEN);
+        assert_eq!(leg_accounts_len(&Dex::HumidiFi, 0).unwrap(), Dex::HumidiFi.accounts_len());
+    }
+
+    #[test]
+    fn leg_accounts_len_is_bounded_per_dex() {
+        assert_eq!(leg_accounts_len(&Dex::RaydiumClV2, amm_raydium_cl_v2::MIN_ACCOUNTS_LEN as u8).unwrap(), amm_raydium_cl_v2::MIN_ACCOUNTS_LEN);
+        assert_eq!(leg_accounts_len(&Dex::RaydiumClV2, amm_raydium_cl_v2::MAX_ACCOUNTS_LEN as u8).unwrap(), amm_raydium_cl_v2::MAX_ACCOUNTS_LEN);
+        assert!(leg_accounts_len(&Dex::RaydiumClV2, amm_raydium_cl_v2::MIN_ACCOUNTS_LEN as u8 - 1).is_err());
+        assert!(leg_accounts_len(&Dex::RaydiumClV2, amm_raydium_cl_v2::MAX_ACCOUNTS_LEN as u8 + 1).is_err());
+
+        // fixed venues only take their own accounts_len
+        assert!(leg_accounts_len(&Dex::RaydiumCp, Dex::RaydiumCp.accounts_len() as u8 + 1).is_err());
+    }
+
+    #[test]
+    fn legs_shape_must_match_routes() {
+        let routes = vec![vec![Route { dexes: vec![Dex::RaydiumClV2, Dex::RaydiumCp], weights: vec![50, 50] }]];
+
+        assert!(check_legs_shape(&[vec![vec![0u8, 16]]], &routes, ErrorCode::LegAccountsLensMustMatchRoutes).is_ok());
+        assert!(check_legs_shape(&[vec![vec![0u8]]], &routes, ErrorCode::LegAccountsLensMustMatchRoutes).is_err());
+        assert!(check_legs_shape::<u8>(&[], &routes, ErrorCode::LegAccountsLensMustMatchRoutes).is_err());
+    }
+}
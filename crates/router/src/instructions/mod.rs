@@ -1,5 +1,9 @@
+pub mod arb;
 pub mod common_swap;
+pub mod quote_route;
 pub mod swap;
 
+pub use arb::*;
 pub use common_swap::*;
+pub use quote_route::*;
 pub use swap::*;
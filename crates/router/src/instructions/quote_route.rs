@@ -0,0 +1,36 @@
+use anchor_lang::{prelude::*, solana_program::program::set_return_data};
+
+use crate::{error::ErrorCode, execute_routes, utils::*, RouteQuote, SwapAccounts, SwapArgsV2};
+
+/// Executes `args.routes` and writes the resulting [`crate::RouteQuote`] to return data.
+/// `expect_amount_out`, `min_return`, `hop_min_outs` & the deadline go unchecked, the amount out being what's asked for.
+/// Always fails afterwards so nothing commits; meant to be run through `simulateTransaction`.
+pub fn quote_route_handler<'a>(ctx: Context<'_, '_, 'a, 'a, SwapAccounts<'a>>, args: SwapArgsV2, order_id: u64) -> Result<()> {
+    let SwapArgsV2 { amount_in, amounts, routes, leg_accounts_lens, .. } = args;
+    let source_token_account = &ctx.accounts.source_token_account;
+    let destination_token_account = &mut ctx.accounts.destination_token_account;
+    log_swap_basic_info(order_id, &ctx.accounts.source_mint.key(), &ctx.accounts.destination_mint.key(), &source_token_account.owner, &destination_token_account.owner);
+
+    require!(amount_in > 0, ErrorCode::AmountInMustBeGreaterThanZero);
+    let before_destination_balance = destination_token_account.amount;
+    let (leg_amounts_out, _) = execute_routes(
+        &source_token_account.key(),
+        &destination_token_account.key(),
+        ctx.remaining_accounts,
+        &amounts,
+        &routes,
+        None,
+        leg_accounts_lens.as_deref(),
+        amount_in,
+        false,
+        None,
+    )?;
+
+    destination_token_account.reload()?;
+    let amount_out = destination_token_account.amount.checked_sub(before_destination_balance).ok_or(ErrorCode::CalculationError)?;
+
+    let route_quote = RouteQuote { amount_out, leg_amounts_out };
+    msg!("{:?}", route_quote);
+    set_return_data(&route_quote.try_to_vec()?);
+    Err(ErrorCode::QuoteRouteSimulationOnly.into())
+}
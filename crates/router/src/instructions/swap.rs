@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount};
 
-use crate::{common_swap, processor::swap_processor::SwapProcessor, SwapArgs};
+use crate::{common_swap, processor::swap_processor::SwapProcessor, SwapArgs, SwapArgsV2};
 
 #[derive(Accounts)]
 pub struct SwapAccounts<'info> {
@@ -25,8 +25,60 @@ pub struct SwapAccounts<'info> {
     pub destination_mint: InterfaceAccount<'info, Mint>,
 }
 
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SwapV2Accounts<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = source_mint,
+        token::authority = payer,
+    )]
+    pub source_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = destination_mint,
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub source_mint: InterfaceAccount<'info, Mint>,
+
+    pub destination_mint: InterfaceAccount<'info, Mint>,
+}
+
 pub fn swap_handler<'a>(ctx: Context<'_, '_, 'a, 'a, SwapAccounts<'a>>, args: SwapArgs, order_id: u64) -> Result<()> {
-    common_swap(
+    let outcome = common_swap(
+        &SwapProcessor,
+        &ctx.accounts.payer,
+        &ctx.accounts.payer,
+        None,
+        &mut ctx.accounts.source_token_account,
+        &mut ctx.accounts.destination_token_account,
+        &ctx.accounts.source_mint,
+        &ctx.accounts.destination_mint,
+        &None,
+        &mut None,
+        &mut None,
+        &None,
+        &None,
+        &None,
+        &None,
+        ctx.remaining_accounts,
+        args.into(),
+        order_id,
+    )?;
+
+    for event in outcome.swap_events {
+        emit!(event);
+    }
+    emit!(outcome.swap_completed);
+    Ok(())
+}
+
+pub fn swap_v2_handler<'a>(ctx: Context<'_, '_, 'a, 'a, SwapV2Accounts<'a>>, args: SwapArgsV2, order_id: u64) -> Result<()> {
+    let outcome = common_swap(
         &SwapProcessor,
         &ctx.accounts.payer,
         &ctx.accounts.payer,
@@ -46,5 +98,10 @@ pub fn swap_handler<'a>(ctx: Context<'_, '_, 'a, 'a, SwapAccounts<'a>>, args: Sw
         args,
         order_id,
     )?;
+
+    for event in outcome.swap_events {
+        emit_cpi!(event);
+    }
+    emit_cpi!(outcome.swap_completed);
     Ok(())
 }
@@ -21,4 +21,16 @@ pub mod router {
     pub fn swap<'a>(ctx: Context<'_, '_, 'a, 'a, SwapAccounts<'a>>, data: SwapArgs, order_id: u64) -> Result<()> {
         instructions::swap_handler(ctx, data, order_id)
     }
+
+    pub fn swap_v2<'a>(ctx: Context<'_, '_, 'a, 'a, SwapV2Accounts<'a>>, data: SwapArgsV2, order_id: u64) -> Result<()> {
+        instructions::swap_v2_handler(ctx, data, order_id)
+    }
+
+    pub fn arb<'a>(ctx: Context<'_, '_, 'a, 'a, ArbAccounts<'a>>, data: ArbArgs, order_id: u64) -> Result<()> {
+        instructions::arb_handler(ctx, data, order_id)
+    }
+
+    pub fn quote_route<'a>(ctx: Context<'_, '_, 'a, 'a, SwapAccounts<'a>>, data: SwapArgsV2, order_id: u64) -> Result<()> {
+        instructions::quote_route_handler(ctx, data, order_id)
+    }
 }
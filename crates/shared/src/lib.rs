@@ -4,6 +4,7 @@
 //! - the program id is used to indicate the route
 //! - accounts_len - the number of accounts required (and expected) for successful swap at a particular exchange
 //! - args_len - the number of bytes expected as instruction data
+//! - min/max_accounts_len - the bounds of a leg's accounts, for venues taking a variable number of (tick/bin) arrays
 
 use std::str::FromStr;
 
@@ -23,6 +24,52 @@ pub mod amm_raydium_cl_v2 {
     declare_id!("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK");
     pub const ACCOUNTS_LEN: usize = 18;
     pub const ARGS_LEN: usize = 41;
+    // the fixed accounts and a single tick array, up to 10 tick arrays
+    pub const MIN_ACCOUNTS_LEN: usize = 16;
+    pub const MAX_ACCOUNTS_LEN: usize = 25;
+}
+
+pub mod amm_raydium_amm_v4 {
+    use anchor_lang::prelude::*;
+
+    declare_id!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+    pub const ACCOUNTS_LEN: usize = 18;
+    pub const ARGS_LEN: usize = 17;
+}
+
+pub mod amm_whirlpool {
+    use anchor_lang::prelude::*;
+
+    declare_id!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+    pub const ACCOUNTS_LEN: usize = 16;
+    pub const ARGS_LEN: usize = 43;
+}
+
+pub mod amm_meteora_dlmm {
+    use anchor_lang::prelude::*;
+
+    declare_id!("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo");
+    pub const ACCOUNTS_LEN: usize = 18;
+    pub const ARGS_LEN: usize = 24;
+    // the fixed accounts and a single bin array, up to 8 bin arrays
+    pub const MIN_ACCOUNTS_LEN: usize = 16;
+    pub const MAX_ACCOUNTS_LEN: usize = 23;
+}
+
+pub mod amm_spl_token_swap {
+    use anchor_lang::prelude::*;
+
+    declare_id!("SwaPpA9LAaLfeLi3a68M4DjnLqgtticKg6CnyNwgAC8");
+    pub const ACCOUNTS_LEN: usize = 11;
+    pub const ARGS_LEN: usize = 17;
+}
+
+pub mod clob_phoenix {
+    use anchor_lang::prelude::*;
+
+    declare_id!("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY");
+    pub const ACCOUNTS_LEN: usize = 9;
+    pub const ARGS_LEN: usize = 57;
 }
 
 pub mod pmm_solfi_v2 {
@@ -81,6 +128,16 @@ pub mod pmm_bisonfi {
     pub const ARGS_LEN: usize = 18;
 }
 
+pub mod lst_spl_stake_pool {
+    use anchor_lang::prelude::*;
+
+    declare_id!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+    pub const ACCOUNTS_LEN: usize = 16;
+    pub const ARGS_LEN: usize = 9;
+    // the seed of the token account SOL is unwrapped through ahead of a deposit, created with the swap authority as base
+    pub const UNWRAP_SEED: &str = "magnus-unwrap";
+}
+
 pub mod spl_token {
     use anchor_lang::prelude::*;
 
@@ -122,6 +179,12 @@ pub enum Dex {
     Tessera,
     GoonFi,
     BisonFi,
+    Whirlpool,
+    MeteoraDlmm,
+    Phoenix,
+    RaydiumAmmV4,
+    SplStakePool,
+    SplTokenSwap,
 }
 
 impl std::fmt::Display for Dex {
@@ -129,6 +192,10 @@ impl std::fmt::Display for Dex {
         match self {
             Dex::RaydiumClV2 => f.write_str("raydium-cl-v2"),
             Dex::RaydiumCp => f.write_str("raydium-cp"),
+            Dex::RaydiumAmmV4 => f.write_str("raydium-amm-v4"),
+            Dex::Whirlpool => f.write_str("whirlpool"),
+            Dex::MeteoraDlmm => f.write_str("meteora-dlmm"),
+            Dex::SplTokenSwap => f.write_str("spl-token-swap"),
 
             // pmms
             Dex::ObricV2 => f.write_str("obric-v2"),
@@ -138,18 +205,44 @@ impl std::fmt::Display for Dex {
             Dex::Tessera => f.write_str("tessera"),
             Dex::GoonFi => f.write_str("goonfi"),
             Dex::BisonFi => f.write_str("bisonfi"),
+
+            // clobs
+            Dex::Phoenix => f.write_str("phoenix"),
+
+            // lsts
+            Dex::SplStakePool => f.write_str("spl-stake-pool"),
         }
     }
 }
 
 impl Dex {
-    pub const ALL: [Dex; 9] = [Dex::RaydiumClV2, Dex::RaydiumCp, Dex::ObricV2, Dex::SolfiV2, Dex::ZeroFi, Dex::HumidiFi, Dex::Tessera, Dex::GoonFi, Dex::BisonFi];
+    pub const ALL: [Dex; 15] = [
+        Dex::RaydiumClV2,
+        Dex::RaydiumCp,
+        Dex::ObricV2,
+        Dex::SolfiV2,
+        Dex::ZeroFi,
+        Dex::HumidiFi,
+        Dex::Tessera,
+        Dex::GoonFi,
+        Dex::BisonFi,
+        Dex::Whirlpool,
+        Dex::MeteoraDlmm,
+        Dex::Phoenix,
+        Dex::RaydiumAmmV4,
+        Dex::SplStakePool,
+        Dex::SplTokenSwap,
+    ];
     pub const PMM: [Dex; 7] = [Dex::ObricV2, Dex::SolfiV2, Dex::ZeroFi, Dex::HumidiFi, Dex::Tessera, Dex::GoonFi, Dex::BisonFi];
 
     pub fn program_id(&self) -> anchor_lang::solana_program::pubkey::Pubkey {
         match self {
             Dex::RaydiumClV2 => crate::amm_raydium_cl_v2::id(),
             Dex::RaydiumCp => crate::amm_raydium_cp::id(),
+            Dex::RaydiumAmmV4 => crate::amm_raydium_amm_v4::id(),
+            Dex::Whirlpool => crate::amm_whirlpool::id(),
+            Dex::MeteoraDlmm => crate::amm_meteora_dlmm::id(),
+            Dex::SplTokenSwap => crate::amm_spl_token_swap::id(),
 
             // pmms
             Dex::HumidiFi => crate::pmm_humidifi::id(),
@@ -159,6 +252,48 @@ impl Dex {
             Dex::Tessera => crate::pmm_tessera::id(),
             Dex::GoonFi => crate::pmm_goonfi::id(),
             Dex::BisonFi => crate::pmm_bisonfi::id(),
+
+            // clobs
+            Dex::Phoenix => crate::clob_phoenix::id(),
+
+            // lsts
+            Dex::SplStakePool => crate::lst_spl_stake_pool::id(),
+        }
+    }
+
+    /// The number of accounts a leg takes when the route doesn't say otherwise.
+    pub fn accounts_len(&self) -> usize {
+        match self {
+            Dex::RaydiumClV2 => crate::amm_raydium_cl_v2::ACCOUNTS_LEN,
+            Dex::RaydiumCp => crate::amm_raydium_cp::ACCOUNTS_LEN,
+            Dex::RaydiumAmmV4 => crate::amm_raydium_amm_v4::ACCOUNTS_LEN,
+            Dex::Whirlpool => crate::amm_whirlpool::ACCOUNTS_LEN,
+            Dex::MeteoraDlmm => crate::amm_meteora_dlmm::ACCOUNTS_LEN,
+            Dex::SplTokenSwap => crate::amm_spl_token_swap::ACCOUNTS_LEN,
+
+            // pmms
+            Dex::HumidiFi => crate::pmm_humidifi::ACCOUNTS_LEN,
+            Dex::SolfiV2 => crate::pmm_solfi_v2::ACCOUNTS_LEN,
+            Dex::ZeroFi => crate::pmm_zerofi::ACCOUNTS_LEN,
+            Dex::ObricV2 => crate::pmm_obric_v2::ACCOUNTS_LEN,
+            Dex::Tessera => crate::pmm_tessera::ACCOUNTS_LEN,
+            Dex::GoonFi => crate::pmm_goonfi::ACCOUNTS_LEN,
+            Dex::BisonFi => crate::pmm_bisonfi::ACCOUNTS_LEN,
+
+            // clobs
+            Dex::Phoenix => crate::clob_phoenix::ACCOUNTS_LEN,
+
+            // lsts
+            Dex::SplStakePool => crate::lst_spl_stake_pool::ACCOUNTS_LEN,
+        }
+    }
+
+    /// The number of accounts a leg may take, fixed venues only accept their `accounts_len`.
+    pub fn accounts_len_range(&self) -> std::ops::RangeInclusive<usize> {
+        match self {
+            Dex::RaydiumClV2 => crate::amm_raydium_cl_v2::MIN_ACCOUNTS_LEN..=crate::amm_raydium_cl_v2::MAX_ACCOUNTS_LEN,
+            Dex::MeteoraDlmm => crate::amm_meteora_dlmm::MIN_ACCOUNTS_LEN..=crate::amm_meteora_dlmm::MAX_ACCOUNTS_LEN,
+            _ => self.accounts_len()..=self.accounts_len(),
         }
     }
 }
@@ -170,6 +305,7 @@ impl FromStr for Dex {
         match s.to_lowercase().as_str() {
             "raydium-cl-v2" | "raydiumclv2" => Ok(Dex::RaydiumClV2),
             "raydium-cp" | "raydiumcp" => Ok(Dex::RaydiumCp),
+            "raydium-amm-v4" | "raydiumammv4" | "raydium-v4" => Ok(Dex::RaydiumAmmV4),
             "obric-v2" | "obricv2" => Ok(Dex::ObricV2),
             "solfi-v2" | "solfiv2" => Ok(Dex::SolfiV2),
             "zerofi" => Ok(Dex::ZeroFi),
@@ -177,6 +313,11 @@ impl FromStr for Dex {
             "tessera" => Ok(Dex::Tessera),
             "goonfi" => Ok(Dex::GoonFi),
             "bisonfi" => Ok(Dex::BisonFi),
+            "whirlpool" | "orca-whirlpool" => Ok(Dex::Whirlpool),
+            "meteora-dlmm" | "meteoradlmm" => Ok(Dex::MeteoraDlmm),
+            "spl-token-swap" | "spltokenswap" | "token-swap" => Ok(Dex::SplTokenSwap),
+            "phoenix" => Ok(Dex::Phoenix),
+            "spl-stake-pool" | "splstakepool" | "stake-pool" => Ok(Dex::SplStakePool),
             _ => Err(format!("unknown dex '{}'", s)),
         }
     }
@@ -194,6 +335,12 @@ impl From<magnus_router_client::types::Dex> for Dex {
             magnus_router_client::types::Dex::Tessera => Dex::Tessera,
             magnus_router_client::types::Dex::GoonFi => Dex::GoonFi,
             magnus_router_client::types::Dex::BisonFi => Dex::BisonFi,
+            magnus_router_client::types::Dex::Whirlpool => Dex::Whirlpool,
+            magnus_router_client::types::Dex::MeteoraDlmm => Dex::MeteoraDlmm,
+            magnus_router_client::types::Dex::Phoenix => Dex::Phoenix,
+            magnus_router_client::types::Dex::RaydiumAmmV4 => Dex::RaydiumAmmV4,
+            magnus_router_client::types::Dex::SplStakePool => Dex::SplStakePool,
+            magnus_router_client::types::Dex::SplTokenSwap => Dex::SplTokenSwap,
         }
     }
 }
@@ -210,6 +357,12 @@ impl From<Dex> for magnus_router_client::types::Dex {
             Dex::Tessera => magnus_router_client::types::Dex::Tessera,
             Dex::GoonFi => magnus_router_client::types::Dex::GoonFi,
             Dex::BisonFi => magnus_router_client::types::Dex::BisonFi,
+            Dex::Whirlpool => magnus_router_client::types::Dex::Whirlpool,
+            Dex::MeteoraDlmm => magnus_router_client::types::Dex::MeteoraDlmm,
+            Dex::Phoenix => magnus_router_client::types::Dex::Phoenix,
+            Dex::RaydiumAmmV4 => magnus_router_client::types::Dex::RaydiumAmmV4,
+            Dex::SplStakePool => magnus_router_client::types::Dex::SplStakePool,
+            Dex::SplTokenSwap => magnus_router_client::types::Dex::SplTokenSwap,
         }
     }
 }